@@ -1,6 +1,7 @@
 use paypal_rs::{common::*, errors::*, invoice::*, client::{Client, HeaderParams}};
 
 #[tokio::main]
+#[allow(clippy::result_large_err)]
 async fn main() -> Result<(), ResponseError> {
     dotenv::dotenv().ok();
 
@@ -0,0 +1,221 @@
+//! Helpers to make many api calls concurrently, like capturing every authorized order at the end of the day.
+//!
+//! A failed call doesn't stop the batch, every item gets its own [BatchResult], in the same order the items were given.
+//!
+//! ```no_run
+//! use paypal_rs::{batch::BatchOptions, Client};
+//!
+//! # async fn run(mut client: Client) {
+//! let results = client.capture_orders(&["5O190127TN364715T", "8MC585209K746392H"], BatchOptions::new(8)).await;
+//!
+//! for item in results {
+//!     if let Err(e) = item.result {
+//!         eprintln!("couldn't capture order {}: {}", item.id, e);
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::capture::RefundPayload;
+use crate::client::{Client, HeaderParams};
+use crate::errors::{decode, ResponseError};
+use crate::orders::{Order, Refund};
+use futures_util::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How a batch of api calls is made.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BatchOptions {
+    /// The maximum number of requests in flight at the same time.
+    pub concurrency: usize,
+    /// How long the requests of the batch not sent yet wait once one of them is rate limited,
+    /// they are sent right away if not set.
+    pub rate_limit_pause: Option<Duration>,
+}
+
+impl BatchOptions {
+    /// Makes up to `concurrency` requests at the same time, pausing for a second when rate limited.
+    pub fn new(concurrency: usize) -> Self {
+        BatchOptions {
+            concurrency,
+            rate_limit_pause: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions::new(4)
+    }
+}
+
+/// The result of a single item of a batch.
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    /// The ID of the resource the call was made for.
+    pub id: String,
+    /// The result of the call.
+    pub result: Result<T, ResponseError>,
+}
+
+/// Holds back the requests of a batch while paypal is rate limiting it.
+#[derive(Debug, Default)]
+struct RateLimitGate {
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl RateLimitGate {
+    async fn wait(&self) {
+        let paused_until = *self.paused_until.lock().unwrap();
+        if let Some(until) = paused_until {
+            tokio::time::sleep_until(until).await;
+        }
+    }
+
+    fn pause(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut paused_until = self.paused_until.lock().unwrap();
+        if *paused_until < Some(until) {
+            *paused_until = Some(until);
+        }
+    }
+}
+
+impl Client {
+    /// Captures payment for many orders, see [capture_order](Client::capture_order).
+    pub async fn capture_orders<S: AsRef<str>>(
+        &mut self,
+        order_ids: &[S],
+        options: BatchOptions,
+    ) -> Vec<BatchResult<Order>> {
+        let requests = order_ids
+            .iter()
+            .map(|id| {
+                let id = id.as_ref();
+                let builder = self
+                    .client
+                    .post(format!("{}/v2/checkout/orders/{}/capture", self.endpoint(), id));
+                (id.to_owned(), builder)
+            })
+            .collect();
+
+        self.run_batch(requests, options).await
+    }
+
+    /// Refunds many captured payments, given as pairs of capture ID and refund payload,
+    /// see [refund_capture](Client::refund_capture).
+    pub async fn refund_captures<S: AsRef<str>>(
+        &mut self,
+        refunds: &[(S, RefundPayload)],
+        options: BatchOptions,
+    ) -> Vec<BatchResult<Refund>> {
+        let requests = refunds
+            .iter()
+            .map(|(id, payload)| {
+                let id = id.as_ref();
+                let builder = self
+                    .client
+                    .post(format!("{}/v2/payments/captures/{}/refund", self.endpoint(), id))
+                    .json(payload);
+                (id.to_owned(), builder)
+            })
+            .collect();
+
+        self.run_batch(requests, options).await
+    }
+
+    /// Sends the requests with up to `options.concurrency` of them in flight, sharing one access token.
+    async fn run_batch<T: DeserializeOwned>(
+        &mut self,
+        requests: Vec<(String, reqwest::RequestBuilder)>,
+        options: BatchOptions,
+    ) -> Vec<BatchResult<T>> {
+        if let Err(e) = self.get_access_token().await {
+            log::warn!(target: "paypal-rs", "error getting access token: {:?}", e);
+        }
+
+        let client = &*self;
+        let gate = RateLimitGate::default();
+
+        stream::iter(requests)
+            .map(|(id, builder)| {
+                let gate = &gate;
+                async move {
+                    gate.wait().await;
+                    let result = client.send_batch_request(builder).await;
+
+                    if let (Err(e), Some(pause)) = (&result, options.rate_limit_pause) {
+                        if e.is_rate_limited() {
+                            gate.pause(pause);
+                        }
+                    }
+
+                    BatchResult { id, result }
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    async fn send_batch_request<T: DeserializeOwned>(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<T, ResponseError> {
+        let res = self
+            .execute(self.with_headers(builder, HeaderParams::default()))
+            .await?;
+
+        if res.status().is_success() {
+            decode::<T>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "vcr"))]
+mod tests {
+    use super::*;
+    use crate::orders::OrderStatus;
+    use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+    #[tokio::test]
+    async fn test_capture_orders() {
+        let interactions = [
+            token_interaction(),
+            interaction(
+                "POST",
+                "/v2/checkout/orders/5O190127TN364715T/capture",
+                b"",
+                201,
+                r#"{"id":"5O190127TN364715T","status":"COMPLETED","links":[]}"#,
+            ),
+            interaction(
+                "POST",
+                "/v2/checkout/orders/8MC585209K746392H/capture",
+                b"",
+                422,
+                r#"{"name":"UNPROCESSABLE_ENTITY","details":[{"issue":"ORDER_NOT_APPROVED"}],"links":[]}"#,
+            ),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        let results = client
+            .capture_orders(&["5O190127TN364715T", "8MC585209K746392H"], BatchOptions::new(2))
+            .await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "5O190127TN364715T");
+        assert_eq!(results[0].result.as_ref().unwrap().status, OrderStatus::Completed);
+        assert_eq!(results[1].id, "8MC585209K746392H");
+        match &results[1].result {
+            Err(ResponseError::ApiError(e)) => assert!(e.has_issue("ORDER_NOT_APPROVED")),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
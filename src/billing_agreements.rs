@@ -0,0 +1,371 @@
+//! Use the Billing Agreements API to set up reference transactions, which let you charge a payer
+//! later without them being present. The payer approves an agreement token, which is then exchanged for a billing agreement.
+//!
+//! This is a legacy API, new integrations should prefer vaulting payment methods.
+//!
+//! Reference: https://developer.paypal.com/docs/api/payments/v1/#billing-agreements
+
+use crate::client::{Client, HeaderParams};
+use crate::common::*;
+use crate::countries::Country;
+use crate::errors::{decode, ResponseError};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// The type of billing agreement.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PlanType {
+    /// The merchant charges the payer, allows several agreements per payer.
+    MerchantInitiatedBilling,
+    /// The merchant charges the payer, with a single agreement per payer.
+    MerchantInitiatedBillingSingleAgreement,
+    /// The payer initiates the payments through a channel partner.
+    ChannelInitiatedBilling,
+    /// The payer initiates the payments through a channel partner, with a single agreement per payer.
+    ChannelInitiatedBillingSingleAgreement,
+    /// The payment is initiated by the payer through a channel partner, with a recurring schedule.
+    RecurringPayments,
+}
+
+display_from_str!(PlanType {
+    MerchantInitiatedBilling = "MERCHANT_INITIATED_BILLING",
+    MerchantInitiatedBillingSingleAgreement = "MERCHANT_INITIATED_BILLING_SINGLE_AGREEMENT",
+    ChannelInitiatedBilling = "CHANNEL_INITIATED_BILLING",
+    ChannelInitiatedBillingSingleAgreement = "CHANNEL_INITIATED_BILLING_SINGLE_AGREEMENT",
+    RecurringPayments = "RECURRING_PAYMENTS",
+});
+
+/// The accepted payment type.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AcceptedPaymentType {
+    /// Only instant payments, like balance or cards.
+    Instant,
+    /// Any payment type, including eChecks.
+    Any,
+}
+
+display_from_str!(AcceptedPaymentType {
+    Instant = "INSTANT",
+    Any = "ANY",
+});
+
+/// The merchant preferences for the agreement approval flow.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct MerchantPreferences {
+    /// The URL where the payer is redirected after approving the agreement.
+    pub return_url: String,
+    /// The URL where the payer is redirected after cancelling the approval.
+    pub cancel_url: String,
+    /// The URL where notifications about the agreement are sent.
+    pub notify_url: Option<String>,
+    /// The accepted payment type.
+    #[serde(rename = "accepted_pymt_type")]
+    pub accepted_payment_type: Option<AcceptedPaymentType>,
+    /// Whether the payer doesn't need to provide a shipping address.
+    pub skip_shipping_address: Option<bool>,
+    /// Whether the payer can't change the shipping address provided by the merchant.
+    pub immutable_shipping_address: Option<bool>,
+}
+
+/// The plan of the agreement.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Plan {
+    /// The type of billing agreement.
+    #[serde(rename = "type")]
+    pub type_: PlanType,
+    /// The merchant preferences.
+    pub merchant_preferences: Option<MerchantPreferences>,
+}
+
+/// The payment method of the payer, only paypal is supported.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentMethod {
+    /// A paypal account.
+    #[default]
+    Paypal,
+}
+
+display_from_str!(PaymentMethod {
+    Paypal = "PAYPAL",
+});
+
+/// Information about the payer.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PayerInfo {
+    /// The email address of the payer.
+    pub email: Option<String>,
+    /// The first name of the payer.
+    pub first_name: Option<String>,
+    /// The last name of the payer.
+    pub last_name: Option<String>,
+    /// The paypal assigned ID of the payer.
+    pub payer_id: Option<String>,
+}
+
+/// The payer of the agreement.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct Payer {
+    /// The payment method.
+    pub payment_method: PaymentMethod,
+    /// Information about the payer.
+    pub payer_info: Option<PayerInfo>,
+}
+
+/// A shipping address, in the format of the v1 apis.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ShippingAddress {
+    /// The first line of the address.
+    pub line1: String,
+    /// The second line of the address.
+    pub line2: Option<String>,
+    /// The city name.
+    pub city: String,
+    /// The state or province code.
+    pub state: Option<String>,
+    /// The postal code.
+    pub postal_code: Option<String>,
+    /// The country code.
+    pub country_code: Country,
+    /// The name of the recipient.
+    pub recipient_name: Option<String>,
+}
+
+/// The payload to create an agreement token.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgreementTokenPayload {
+    /// The description of the agreement, shown to the payer.
+    pub description: Option<String>,
+    /// The shipping address.
+    pub shipping_address: Option<ShippingAddress>,
+    /// The payer.
+    pub payer: Payer,
+    /// The plan.
+    pub plan: Plan,
+}
+
+impl AgreementTokenPayload {
+    /// Creates a payload for the given plan type, redirecting the payer to the given urls.
+    pub fn new<R: Into<String>, C: Into<String>>(type_: PlanType, return_url: R, cancel_url: C) -> Self {
+        AgreementTokenPayload {
+            description: None,
+            shipping_address: None,
+            payer: Payer::default(),
+            plan: Plan {
+                type_,
+                merchant_preferences: Some(MerchantPreferences {
+                    return_url: return_url.into(),
+                    cancel_url: cancel_url.into(),
+                    ..Default::default()
+                }),
+            },
+        }
+    }
+}
+
+/// An agreement token, the payer must approve it through its approval_url link.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgreementToken {
+    /// The token ID.
+    pub token_id: String,
+    /// An array of request-related HATEOAS links.
+    pub links: Vec<LinkDescription>,
+}
+
+impl AgreementToken {
+    /// The url where the payer must be redirected to approve the agreement.
+    pub fn approval_url(&self) -> Option<&str> {
+        LinkDescription::find(&self.links, LinkRel::Other("approval_url".to_owned())).map(|link| link.href.as_str())
+    }
+}
+
+string_enum! {
+    /// The state of a billing agreement.
+    pub enum AgreementState {
+        /// The agreement can be used to charge the payer.
+        Active = "ACTIVE",
+        /// The agreement was cancelled.
+        Cancelled = "CANCELLED",
+    }
+}
+
+/// A billing agreement.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Agreement {
+    /// The agreement ID, used to reference it in payments.
+    pub id: String,
+    /// The state of the agreement.
+    pub state: AgreementState,
+    /// The description of the agreement.
+    pub description: Option<String>,
+    /// The payer of the agreement.
+    pub payer: Option<Payer>,
+    /// The shipping address.
+    pub shipping_address: Option<ShippingAddress>,
+    /// The plan of the agreement.
+    pub plan: Option<Plan>,
+    /// The date and time when the agreement was created.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the agreement was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenId<'a> {
+    token_id: &'a str,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+struct CancelAgreement<'a> {
+    description: Option<&'a str>,
+}
+
+impl Client {
+    /// Creates an agreement token, the payer must then approve it before an agreement can be created.
+    pub async fn create_agreement_token(
+        &mut self,
+        payload: AgreementTokenPayload,
+        header_params: HeaderParams,
+    ) -> Result<AgreementToken, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v1/billing-agreements/agreement-tokens", self.endpoint())),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(&payload)).await?;
+
+        if res.status().is_success() {
+            decode::<AgreementToken>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Creates a billing agreement from an agreement token the payer approved.
+    pub async fn create_agreement(
+        &mut self,
+        token_id: &str,
+        header_params: HeaderParams,
+    ) -> Result<Agreement, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v1/billing-agreements/agreements", self.endpoint())),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(&TokenId { token_id })).await?;
+
+        if res.status().is_success() {
+            decode::<Agreement>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Shows the details of a billing agreement.
+    pub async fn show_agreement(
+        &mut self,
+        agreement_id: &str,
+        header_params: HeaderParams,
+    ) -> Result<Agreement, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client.get(format!(
+                    "{}/v1/billing-agreements/agreements/{}",
+                    self.endpoint(),
+                    agreement_id
+                )),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder).await?;
+
+        if res.status().is_success() {
+            decode::<Agreement>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Cancels a billing agreement, it can't be used to charge the payer anymore.
+    pub async fn cancel_agreement(
+        &mut self,
+        agreement_id: &str,
+        description: Option<&str>,
+        header_params: HeaderParams,
+    ) -> Result<(), ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client.post(format!(
+                    "{}/v1/billing-agreements/agreements/{}/cancel",
+                    self.endpoint(),
+                    agreement_id
+                )),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(&CancelAgreement { description })).await?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agreement_token() {
+        let payload = AgreementTokenPayload::new(
+            PlanType::MerchantInitiatedBilling,
+            "https://example.com/return".to_owned(),
+            "https://example.com/cancel",
+        );
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["payer"]["payment_method"], "PAYPAL");
+        assert_eq!(json["plan"]["type"], "MERCHANT_INITIATED_BILLING");
+        assert_eq!(
+            json["plan"]["merchant_preferences"]["return_url"],
+            "https://example.com/return"
+        );
+
+        let token: AgreementToken = serde_json::from_str(
+            r#"{
+                "links": [
+                    {"href": "https://www.sandbox.paypal.com/agreements/approve?ba_token=BA-8A802366G0648845Y", "rel": "approval_url", "method": "POST"},
+                    {"href": "https://api-m.sandbox.paypal.com/v1/billing-agreements/BA-8A802366G0648845Y/agreements", "rel": "self", "method": "POST"}
+                ],
+                "token_id": "BA-8A802366G0648845Y"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            token.approval_url(),
+            Some("https://www.sandbox.paypal.com/agreements/approve?ba_token=BA-8A802366G0648845Y")
+        );
+    }
+}
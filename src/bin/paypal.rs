@@ -0,0 +1,102 @@
+//! A command line tool for common operations, to poke at the sandbox or live api without writing a program.
+//!
+//! The client is configured from the environment with [Client::from_env]: the credentials are read from the
+//! `PAYPAL_CLIENT_ID` and `PAYPAL_SECRET` variables, and the sandbox is used unless `PAYPAL_ENV` is `live`.
+//! Responses are printed as json.
+//!
+//! Requires the `cli` feature: `cargo run --features cli --bin paypal -- show-order 5O190127TN364715T`
+
+use paypal_rs::capture::RefundPayload;
+use paypal_rs::common::{Currency, Money};
+use paypal_rs::orders::{Amount, Intent, OrderPayload, PurchaseUnit};
+use paypal_rs::webhooks::{EventType, SimulateEventPayload};
+use paypal_rs::{Client, HeaderParams};
+use serde::Serialize;
+use std::error::Error;
+
+const USAGE: &str = "Usage: paypal <command> [arguments]
+
+Commands:
+    create-order <currency> <value> [authorize]   Creates an order with a single purchase unit
+    show-order <order-id>                         Shows the details of an order
+    capture <order-id>                            Captures the payment of an approved order
+    refund <capture-id> [<currency> <value>]      Refunds a captured payment, entirely if no amount is given
+    list-webhooks                                 Lists the webhooks configured for the app
+    simulate-event <event-type> <webhook-id|url>  Sends a sample event to a webhook, sandbox only
+
+Environment:
+    PAYPAL_CLIENT_ID   The client id of the app
+    PAYPAL_SECRET      The secret of the app
+    PAYPAL_ENV         sandbox (default) or live
+    PAYPAL_BN_CODE     The partner attribution id sent with every request, optional";
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    if let Err(e) = run(&args).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: &[&str]) -> Result<(), Box<dyn Error>> {
+    if matches!(args, [] | ["help"] | ["-h"] | ["--help"]) {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let mut client = Client::from_env()?;
+    match *args {
+        ["create-order", currency, value] => create_order(&mut client, currency, value, Intent::Capture).await,
+        ["create-order", currency, value, "authorize"] => {
+            create_order(&mut client, currency, value, Intent::Authorize).await
+        }
+        ["show-order", order_id] => print(&client.show_order_details(order_id).await?),
+        ["capture", order_id] => print(&client.capture_order(order_id, HeaderParams::default()).await?),
+        ["refund", capture_id] => refund(&mut client, capture_id, None).await,
+        ["refund", capture_id, currency, value] => {
+            let amount = Money {
+                currency_code: currency.parse()?,
+                value: value.to_owned(),
+            };
+            refund(&mut client, capture_id, Some(amount)).await
+        }
+        ["list-webhooks"] => print(&client.list_webhooks(HeaderParams::default()).await?),
+        ["simulate-event", event_type, target] => {
+            let is_url = target.starts_with("http://") || target.starts_with("https://");
+            let payload = SimulateEventPayload {
+                webhook_id: Some(target.to_owned()).filter(|_| !is_url),
+                url: Some(target.to_owned()).filter(|_| is_url),
+                event_type: event_type.parse::<EventType>()?,
+                resource_version: None,
+            };
+            print(&client.simulate_event(&payload, HeaderParams::default()).await?)
+        }
+        _ => Err(format!("invalid arguments\n\n{}", USAGE).into()),
+    }
+}
+
+async fn create_order(client: &mut Client, currency: &str, value: &str, intent: Intent) -> Result<(), Box<dyn Error>> {
+    let currency: Currency = currency.parse()?;
+    let order = OrderPayload::new(intent, vec![PurchaseUnit::new(Amount::new(currency, value))]);
+    print(&client.create_order(order, HeaderParams::default()).await?)
+}
+
+async fn refund(client: &mut Client, capture_id: &str, amount: Option<Money>) -> Result<(), Box<dyn Error>> {
+    let payload = RefundPayload {
+        amount,
+        ..Default::default()
+    };
+    print(
+        &client
+            .refund_capture(capture_id, &payload, HeaderParams::default())
+            .await?,
+    )
+}
+
+fn print<T: Serialize>(value: &T) -> Result<(), Box<dyn Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
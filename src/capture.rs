@@ -1,35 +1,53 @@
 //! A capture
 
+use crate::client::{Client, HeaderParams};
+use crate::errors::{decode, ResponseError};
 use crate::{common::*, orders::*};
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 
-/// Seller protection status
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-#[allow(non_camel_case_types)]
-pub enum SellerProtectionStatus {
-    /// Your PayPal balance remains intact if the customer claims that they did not receive an item or the account holder claims that they did not authorize the payment.
-    Eligible,
-    /// Your PayPal balance remains intact if the customer claims that they did not receive an item.
-    PartiallyEligible,
-    /// This transaction is not eligible for seller protection.
-    NotEligible
+string_enum! {
+    /// Seller protection status
+    pub enum SellerProtectionStatus {
+        /// Your PayPal balance remains intact if the customer claims that they did not receive an item or the account holder claims that they did not authorize the payment.
+        Eligible = "ELIGIBLE" | "ELIGABLE",
+        /// Your PayPal balance remains intact if the customer claims that they did not receive an item.
+        PartiallyEligible = "PARTIALLY_ELIGIBLE",
+        /// This transaction is not eligible for seller protection.
+        NotEligible = "NOT_ELIGIBLE",
+    }
+}
+
+string_enum! {
+    /// A condition covered by seller protection.
+    pub enum DisputeCategory {
+        /// The payer paid for an item that they did not receive.
+        ItemNotReceived = "ITEM_NOT_RECEIVED",
+        /// The payer did not authorize the payment.
+        UnauthorizedTransaction = "UNAUTHORIZED_TRANSACTION",
+    }
 }
 
 /// Seller protection
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SellerProtection {
-    /// Dispute categories 
-    pub dispute_categories: Vec<String>,
+    /// The conditions that are covered for the transaction.
+    #[serde(default)]
+    pub dispute_categories: Vec<DisputeCategory>,
     /// Status
     pub status: SellerProtectionStatus,
 }
 
 /// Related identifiers
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RelatedIds {
     /// Order ID
     pub order_id: String,
+    /// The ID of the authorization that was captured, when the order intent was authorize.
+    pub authorization_id: Option<String>,
+    /// The ID of the capture that was refunded, only set on refunds.
+    pub capture_id: Option<String>,
 }
 
 /// Supplementary data
@@ -40,6 +58,7 @@ pub struct SupplementaryData {
 }
 
 /// Seller receivable breakdown
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SellerReceivableBreakdown {
     /// Paypal fee
@@ -47,10 +66,13 @@ pub struct SellerReceivableBreakdown {
     /// Gross amount
     pub gross_amount: Amount,
     /// Net amount
-    pub net_amount: Amount
+    pub net_amount: Amount,
+    /// The platform fees taken from the payment, on PayPal Commerce Platform.
+    pub platform_fees: Option<Vec<PlatformFee>>,
 }
 
-/// Payment
+/// A captured payment, also the resource of the `PAYMENT.CAPTURE.*` webhook events.
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Payment {
     /// Payment amount
@@ -67,10 +89,129 @@ pub struct Payment {
     pub seller_receivable_breakdown: Option<SellerReceivableBreakdown>,
     /// Custom identifier
     pub custom_id: Option<String>,
+    /// The API caller-provided external invoice number for this order.
+    pub invoice_id: Option<String>,
+    /// The funds that are held on behalf of the merchant.
+    pub disbursement_mode: Option<DisbursementMode>,
+    /// The details of the captured payment status, set when it is pending or denied.
+    pub status_details: Option<CaptureStatusDetails>,
+    /// The resources related to this payment, like the order it belongs to.
+    pub supplementary_data: Option<SupplementaryData>,
+    /// The processor response for card payments.
+    pub processor_response: Option<ProcessorResponse>,
+    /// The reference to the transaction assigned by the card network, store it to make later merchant initiated payments.
+    pub network_transaction_reference: Option<NetworkTransactionReference>,
     /// An array of request-related HATEOAS links. To complete payer approval, use the approve link to redirect the payer.
+    #[serde(default)]
     pub links: Vec<LinkDescription>,
     /// Capture identifier
     pub id: String,
     /// Capture status
     pub status: Option<CaptureStatus>,
 }
+
+impl Payment {
+    /// The ID of the order this payment belongs to.
+    pub fn order_id(&self) -> Option<&str> {
+        self.supplementary_data
+            .as_ref()
+            .map(|data| data.related_ids.order_id.as_str())
+    }
+}
+
+/// The payload to refund a captured payment.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RefundPayload {
+    /// The amount to refund, the whole remaining captured amount is refunded if not set.
+    pub amount: Option<Money>,
+    /// The API caller-provided external invoice number for this refund.
+    pub invoice_id: Option<String>,
+    /// The reason for the refund, shown to the payer.
+    pub note_to_payer: Option<String>,
+    /// The platform fees to return, for PayPal Commerce Platform partners.
+    pub payment_instruction: Option<RefundPaymentInstruction>,
+}
+
+/// The platform fees to return with a refund.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RefundPaymentInstruction {
+    /// The platform fees to return to the payer. When not set, PayPal returns them in the same proportion as the
+    /// refunded amount.
+    pub platform_fees: Option<Vec<PlatformFee>>,
+}
+
+/// The payload to capture an authorized payment.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuthorizationCapturePayload {
+    /// The amount to capture, the whole authorized amount is captured if not set.
+    pub amount: Option<Money>,
+    /// The API caller-provided external invoice number for this capture.
+    pub invoice_id: Option<String>,
+    /// Whether no more captures can be made against the authorization, true by default.
+    pub final_capture: Option<bool>,
+    /// An informational note about this capture, shown to the payer.
+    pub note_to_payer: Option<String>,
+    /// The payment descriptor on the payer's account statement.
+    pub soft_descriptor: Option<String>,
+    /// The platform fees and disbursement mode of the capture, for PayPal Commerce Platform partners.
+    pub payment_instruction: Option<PaymentInstruction>,
+}
+
+impl Client {
+    /// Captures an authorized payment, by ID.
+    pub async fn capture_authorization(
+        &mut self,
+        authorization_id: &str,
+        payload: &AuthorizationCapturePayload,
+        header_params: HeaderParams,
+    ) -> Result<Payment, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client.post(format!(
+                    "{}/v2/payments/authorizations/{}/capture",
+                    self.endpoint(),
+                    authorization_id
+                )),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(payload)).await?;
+
+        if res.status().is_success() {
+            decode::<Payment>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Refunds a captured payment, by ID.
+    pub async fn refund_capture(
+        &mut self,
+        capture_id: &str,
+        payload: &RefundPayload,
+        header_params: HeaderParams,
+    ) -> Result<Refund, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client.post(format!(
+                    "{}/v2/payments/captures/{}/refund",
+                    self.endpoint(),
+                    capture_id
+                )),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(payload)).await?;
+
+        if res.status().is_success() {
+            decode::<Refund>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
@@ -0,0 +1,345 @@
+//! A high level checkout flow on top of the orders api.
+//!
+//! [start_checkout](Client::start_checkout) creates the order and tells where to send the buyer to approve it, then
+//! [complete_checkout](Client::complete_checkout) captures or authorizes it once the buyer comes back.
+//! Declined payments are reported as a [CheckoutOutcome] instead of an error, since they're part of the flow:
+//! when the funding source of a paypal buyer is declined, the buyer is sent back to choose another one
+//! and the same order is completed again.
+//!
+//! ```no_run
+//! use paypal_rs::checkout::CheckoutOutcome;
+//! use paypal_rs::common::Currency;
+//! use paypal_rs::orders::{Amount, Intent, OrderPayload, PurchaseUnit};
+//! use paypal_rs::Client;
+//!
+//! # async fn run(mut client: Client) -> Result<(), paypal_rs::errors::ResponseError> {
+//! let order = OrderPayload::new(Intent::Capture, vec![PurchaseUnit::new(Amount::new(Currency::EUR, "10.0"))]);
+//!
+//! let order_id = match client.start_checkout(order, Default::default()).await? {
+//!     CheckoutOutcome::PayerActionRequired { order_id, url } => {
+//!         println!("send the buyer to {}", url);
+//!         order_id
+//!     }
+//!     CheckoutOutcome::Approved(order) => order.id,
+//!     outcome => panic!("unexpected outcome: {:?}", outcome),
+//! };
+//!
+//! // Once the buyer is redirected back to the return url.
+//! match client.complete_checkout(&order_id, Intent::Capture, Default::default()).await? {
+//!     CheckoutOutcome::Completed(order) => println!("paid {}", order.id),
+//!     CheckoutOutcome::PayerActionRequired { url, .. } => println!("send the buyer to {}", url),
+//!     CheckoutOutcome::InstrumentDeclined { restartable: true, url, .. } => {
+//!         println!("send the buyer back to {:?} to choose another funding source", url)
+//!     }
+//!     _ => println!("the payment was declined, start a new checkout"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{Client, HeaderParams};
+use crate::common::{LinkDescription, LinkRel};
+use crate::errors::ResponseError;
+use crate::orders::{AuthorizationStatus, CaptureStatus, Intent, Order, OrderPayload, OrderStatus};
+
+/// Where a checkout stands after a step of the flow.
+#[derive(Debug)]
+pub enum CheckoutOutcome {
+    /// The buyer must be sent to the given url, to approve the order or complete an action like 3D Secure,
+    /// then the checkout is completed once they are redirected back.
+    PayerActionRequired {
+        /// The ID of the order.
+        order_id: String,
+        /// Where to send the buyer.
+        url: String,
+    },
+    /// The order is approved, as when created with a vaulted payment source, and can be completed right away.
+    Approved(Box<Order>),
+    /// The funding source of the buyer was declined.
+    InstrumentDeclined {
+        /// The ID of the order.
+        order_id: String,
+        /// Whether the buyer can choose another funding source for the same order, then the checkout is completed
+        /// again. Otherwise a new checkout must be started.
+        restartable: bool,
+        /// Where to send the buyer to choose another funding source, when paypal gave one.
+        /// With the javascript sdk, call `actions.restart()` instead.
+        url: Option<String>,
+    },
+    /// The payment was captured or authorized.
+    Completed(Box<Order>),
+    /// The order was voided, a new checkout must be started.
+    Voided(Box<Order>),
+    /// The order waits for paypal to approve it, as with pay upon invoice until Ratepay accepts the payer.
+    /// Fetch it again later to know how it ended.
+    PendingApproval(Box<Order>),
+    /// The checkout can't tell what to do next with the order, as when it waits for the buyer but has no link
+    /// to send them to, or its status is unknown to this version of the crate.
+    Unexpected(Box<Order>),
+}
+
+impl CheckoutOutcome {
+    fn from_order(order: Order) -> Self {
+        let action_link = LinkDescription::find(&order.links, LinkRel::PayerAction)
+            .or_else(|| LinkDescription::find(&order.links, LinkRel::Approve));
+
+        match (&order.status, action_link) {
+            (OrderStatus::Completed, _) if is_declined(&order) => CheckoutOutcome::InstrumentDeclined {
+                order_id: order.id,
+                restartable: false,
+                url: None,
+            },
+            (OrderStatus::Completed, _) => CheckoutOutcome::Completed(Box::new(order)),
+            (OrderStatus::Created, Some(link)) | (OrderStatus::PayerActionRequired, Some(link)) => {
+                CheckoutOutcome::PayerActionRequired {
+                    url: link.href.clone(),
+                    order_id: order.id,
+                }
+            }
+            (OrderStatus::Approved, _) | (OrderStatus::Saved, _) => CheckoutOutcome::Approved(Box::new(order)),
+            (OrderStatus::Voided, _) => CheckoutOutcome::Voided(Box::new(order)),
+            (OrderStatus::PendingApproval, _) => CheckoutOutcome::PendingApproval(Box::new(order)),
+            _ => CheckoutOutcome::Unexpected(Box::new(order)),
+        }
+    }
+
+    /// The outcome of a failed completion, if the buyer can resolve it.
+    fn from_error(order_id: &str, error: &ResponseError) -> Option<Self> {
+        let e = match error {
+            ResponseError::ApiError(e) => e,
+            _ => return None,
+        };
+
+        let link = |rel: LinkRel| LinkDescription::find(&e.links, rel).map(|link| link.href.clone());
+
+        if e.has_issue("INSTRUMENT_DECLINED") {
+            Some(CheckoutOutcome::InstrumentDeclined {
+                order_id: order_id.to_owned(),
+                restartable: true,
+                url: link(LinkRel::Other("redirect".to_owned())).or_else(|| link(LinkRel::Approve)),
+            })
+        } else if e.has_issue("PAYER_ACTION_REQUIRED") {
+            link(LinkRel::PayerAction).map(|url| CheckoutOutcome::PayerActionRequired {
+                order_id: order_id.to_owned(),
+                url,
+            })
+        } else if error.is_buyer_actionable() {
+            Some(CheckoutOutcome::InstrumentDeclined {
+                order_id: order_id.to_owned(),
+                restartable: false,
+                url: None,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the capture or authorization of a completed order was declined by the processor.
+fn is_declined(order: &Order) -> bool {
+    order
+        .purchase_units
+        .iter()
+        .flatten()
+        .filter_map(|unit| unit.payments.as_ref())
+        .any(|payments| {
+            payments
+                .captures
+                .iter()
+                .any(|capture| capture.status == CaptureStatus::Declined)
+                || payments
+                    .authorizations
+                    .iter()
+                    .any(|authorization| authorization.status == AuthorizationStatus::Denied)
+        })
+}
+
+impl Client {
+    /// Creates the order of a checkout, usually returning where the buyer approves it.
+    ///
+    /// Set the return and cancel urls in the application context of the order, the buyer is redirected there
+    /// once done, with the order ID in the `token` query parameter.
+    pub async fn start_checkout(
+        &mut self,
+        order: OrderPayload,
+        header_params: HeaderParams,
+    ) -> Result<CheckoutOutcome, ResponseError> {
+        let order = self.create_order(order, header_params).await?;
+        Ok(CheckoutOutcome::from_order(order))
+    }
+
+    /// Captures or authorizes an approved order, depending on the intent it was created with.
+    ///
+    /// Only errors the buyer can't resolve are returned as errors.
+    pub async fn complete_checkout(
+        &mut self,
+        order_id: &str,
+        intent: Intent,
+        header_params: HeaderParams,
+    ) -> Result<CheckoutOutcome, ResponseError> {
+        let result = match intent {
+            Intent::Capture => self.capture_order(order_id, header_params).await,
+            Intent::Authorize => self.authorize_order(order_id, header_params).await,
+        };
+
+        match result {
+            Ok(order) => Ok(CheckoutOutcome::from_order(order)),
+            Err(e) => CheckoutOutcome::from_error(order_id, &e).ok_or(e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "vcr"))]
+mod tests {
+    use super::*;
+    use crate::common::Currency;
+    use crate::orders::{Amount, PurchaseUnit};
+    use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+    #[tokio::test]
+    async fn test_checkout() {
+        let order = OrderPayload::new(
+            Intent::Capture,
+            vec![PurchaseUnit::new(Amount::new(Currency::EUR, "10.0"))],
+        );
+        let interactions = [
+            token_interaction(),
+            interaction(
+                "POST",
+                "/v2/checkout/orders",
+                &serde_json::to_vec(&order).unwrap(),
+                201,
+                r#"{"id":"5O190127TN364715T","status":"CREATED","links":[
+                    {"href":"https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T","rel":"self","method":"GET"},
+                    {"href":"https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T","rel":"approve","method":"GET"}
+                ]}"#,
+            ),
+            interaction(
+                "POST",
+                "/v2/checkout/orders/5O190127TN364715T/capture",
+                b"",
+                422,
+                r#"{"name":"UNPROCESSABLE_ENTITY","details":[{"issue":"INSTRUMENT_DECLINED"}],"links":[
+                    {"href":"https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T","rel":"redirect","method":"GET"}
+                ]}"#,
+            ),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        match client.start_checkout(order, HeaderParams::default()).await.unwrap() {
+            CheckoutOutcome::PayerActionRequired { order_id, url } => {
+                assert_eq!(order_id, "5O190127TN364715T");
+                assert_eq!(
+                    url,
+                    "https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T"
+                );
+            }
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+
+        match client
+            .complete_checkout("5O190127TN364715T", Intent::Capture, HeaderParams::default())
+            .await
+            .unwrap()
+        {
+            CheckoutOutcome::InstrumentDeclined { restartable, url, .. } => {
+                assert!(restartable);
+                assert_eq!(
+                    url.as_deref(),
+                    Some("https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T")
+                );
+            }
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkout_outcomes() {
+        let order = OrderPayload::new(
+            Intent::Capture,
+            vec![PurchaseUnit::new(Amount::new(Currency::EUR, "10.0"))],
+        );
+        let capture = |order_id: &str, response: &str| {
+            interaction(
+                "POST",
+                &format!("/v2/checkout/orders/{}/capture", order_id),
+                b"",
+                201,
+                response,
+            )
+        };
+        let interactions = [
+            token_interaction(),
+            interaction(
+                "POST",
+                "/v2/checkout/orders",
+                &serde_json::to_vec(&order).unwrap(),
+                201,
+                r#"{"id":"5O190127TN364715T","status":"CREATED","links":[]}"#,
+            ),
+            capture(
+                "1AB23456CD789012E",
+                r#"{"id":"1AB23456CD789012E","status":"VOIDED","links":[]}"#,
+            ),
+            capture(
+                "2AB23456CD789012E",
+                r#"{"id":"2AB23456CD789012E","status":"PAYER_ACTION_REQUIRED","links":[]}"#,
+            ),
+            capture(
+                "3AB23456CD789012E",
+                r#"{"id":"3AB23456CD789012E","status":"ARCHIVED","links":[]}"#,
+            ),
+            capture(
+                "4AB23456CD789012E",
+                r#"{"id":"4AB23456CD789012E","status":"SAVED","links":[]}"#,
+            ),
+            interaction(
+                "POST",
+                "/v2/checkout/orders/5AB23456CD789012E/authorize",
+                b"",
+                201,
+                r#"{"id":"5AB23456CD789012E","status":"COMPLETED","links":[],"purchase_units":[{
+                    "amount":{"currency_code":"EUR","value":"10.00"},
+                    "payments":{"authorizations":[{"status":"DENIED"}]}
+                }]}"#,
+            ),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        // Created without a link to send the buyer to.
+        let outcome = client.start_checkout(order, HeaderParams::default()).await.unwrap();
+        assert!(matches!(outcome, CheckoutOutcome::Unexpected(_)), "{:?}", outcome);
+
+        type Expected = fn(&CheckoutOutcome) -> bool;
+        let cases: [(&str, Intent, Expected); 5] = [
+            ("1AB23456CD789012E", Intent::Capture, |outcome| {
+                matches!(outcome, CheckoutOutcome::Voided(_))
+            }),
+            // Waiting for the buyer without a link to send them to.
+            ("2AB23456CD789012E", Intent::Capture, |outcome| {
+                matches!(outcome, CheckoutOutcome::Unexpected(_))
+            }),
+            // A status this version of the crate doesn't know.
+            ("3AB23456CD789012E", Intent::Capture, |outcome| {
+                matches!(outcome, CheckoutOutcome::Unexpected(_))
+            }),
+            ("4AB23456CD789012E", Intent::Capture, |outcome| {
+                matches!(outcome, CheckoutOutcome::Approved(_))
+            }),
+            ("5AB23456CD789012E", Intent::Authorize, |outcome| {
+                matches!(outcome, CheckoutOutcome::InstrumentDeclined { restartable: false, .. })
+            }),
+        ];
+        for (order_id, intent, expected) in cases {
+            let outcome = client
+                .complete_checkout(order_id, intent, HeaderParams::default())
+                .await
+                .unwrap();
+            assert!(expected(&outcome), "{}: {:?}", order_id, outcome);
+        }
+    }
+}
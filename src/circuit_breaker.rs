@@ -0,0 +1,202 @@
+//! A circuit breaker, to fail fast while paypal is unavailable instead of waiting for every request to time out.
+//!
+//! The [CircuitBreaker] counts the consecutive requests that failed because of paypal: connection errors, timeouts
+//! and 5xx statuses. Once the threshold is reached it opens, and requests fail right away with
+//! [ResponseError::CircuitOpen](crate::errors::ResponseError::CircuitOpen), which the application can handle
+//! by queueing the operation for later. After the open duration a single probe request is let through: the circuit
+//! closes again if it succeeds, or stays open for another period if it fails.
+//!
+//! ```
+//! use paypal_rs::circuit_breaker::CircuitBreaker;
+//! use std::time::Duration;
+//!
+//! let breaker = CircuitBreaker::new(5, Duration::from_secs(30)).on_state_change(|from, to| {
+//!     println!("paypal circuit went from {:?} to {:?}", from, to);
+//! });
+//! ```
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The state of a circuit breaker.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CircuitState {
+    /// Requests are sent.
+    Closed,
+    /// Requests fail right away.
+    Open,
+    /// A probe request is sent to find out whether paypal is available again, the others fail right away.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    probe_started: Option<Instant>,
+}
+
+type StateChangeCallback = Box<dyn Fn(CircuitState, CircuitState) + Send + Sync>;
+
+/// Opens after a number of consecutive failures, see the [module documentation](self).
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    inner: Mutex<Inner>,
+    callbacks: Vec<StateChangeCallback>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("failure_threshold", &self.failure_threshold)
+            .field("open_duration", &self.open_duration)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker, opening after the given number of consecutive failures
+    /// and probing paypal again after the open duration.
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+                probe_started: None,
+            }),
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Adds a callback called with the previous and the new state each time the state changes.
+    ///
+    /// It is called while a request is being sent, so it should return quickly.
+    pub fn on_state_change<F: Fn(CircuitState, CircuitState) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// The current state, an open circuit past its open duration is reported as half open.
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Open if inner.opened_at.elapsed() >= self.open_duration => CircuitState::HalfOpen,
+            state => state,
+        }
+    }
+
+    /// Whether a request can be sent now, the request must then be reported with [record](Self::record).
+    pub fn allow(&self) -> bool {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        let previous = inner.state;
+        let allowed = match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open if now.duration_since(inner.opened_at) < self.open_duration => false,
+            CircuitState::Open => {
+                inner.state = CircuitState::HalfOpen;
+                inner.probe_started = Some(now);
+                true
+            }
+            // A probe whose future was dropped never reports back, so let another one through after a while.
+            CircuitState::HalfOpen => match inner.probe_started {
+                Some(started) if now.duration_since(started) < self.open_duration => false,
+                _ => {
+                    inner.probe_started = Some(now);
+                    true
+                }
+            },
+        };
+        let state = inner.state;
+        drop(inner);
+        self.notify(previous, state);
+        allowed
+    }
+
+    /// Reports the outcome of a request, a failure being an outage on paypal's side.
+    pub fn record(&self, failure: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let previous = inner.state;
+        if failure {
+            inner.failures = inner.failures.saturating_add(1);
+            if inner.state == CircuitState::HalfOpen || inner.failures >= self.failure_threshold {
+                inner.state = CircuitState::Open;
+                inner.opened_at = Instant::now();
+                inner.probe_started = None;
+            }
+        } else {
+            inner.failures = 0;
+            inner.state = CircuitState::Closed;
+            inner.probe_started = None;
+        }
+        let state = inner.state;
+        drop(inner);
+        self.notify(previous, state);
+    }
+
+    fn notify(&self, previous: CircuitState, state: CircuitState) {
+        if previous == state {
+            return;
+        }
+        log::warn!(target: "paypal-rs", "circuit breaker went from {:?} to {:?}", previous, state);
+        for callback in &self.callbacks {
+            callback(previous, state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(50))
+            .on_state_change(move |from, to| recorded.lock().unwrap().push((from, to)));
+
+        // Successes reset the count of consecutive failures.
+        for failure in [true, true, false, true, true] {
+            assert!(breaker.allow());
+            breaker.record(failure);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record(true);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allow());
+
+        // A single probe goes through once the open duration is over, and its failure opens the circuit again.
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+        breaker.record(true);
+        assert!(!breaker.allow());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow());
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allow());
+
+        use CircuitState::*;
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                (Closed, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Closed)
+            ]
+        );
+    }
+}
@@ -1,21 +1,66 @@
 //! Top level paypal client
 
-use crate::errors::{PaypalError, ResponseError};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::common::{LinkDescription, LinkMethod};
+use crate::errors::{decode, ClientBuildError, DecodeMode, InvalidVariantError, ResponseError};
+use crate::middleware::Middleware;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{self, ErrorClass, ExponentialBackoff, RetryContext, RetryPolicy};
+use crate::token_store::{CachedToken, MemoryTokenStore, RefreshLocks, TokenStore};
 use reqwest::header;
 use reqwest::header::HeaderMap;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::{borrow::Cow, time::{Duration, Instant}};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 /// The paypal api endpoint used on a live application.
 pub const LIVE_ENDPOINT: &str = "https://api-m.paypal.com";
 /// The paypal api endpoint used on when testing.
 pub const SANDBOX_ENDPOINT: &str = "https://api-m.sandbox.paypal.com";
 
+/// The paypal environment a client talks to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    /// The sandbox environment, used for testing.
+    #[default]
+    Sandbox,
+    /// The live environment, used on production.
+    Live,
+}
+
+impl Environment {
+    /// The api endpoint of this environment.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            Environment::Sandbox => SANDBOX_ENDPOINT,
+            Environment::Live => LIVE_ENDPOINT,
+        }
+    }
+}
+
+impl std::str::FromStr for Environment {
+    type Err = InvalidVariantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sandbox" => Ok(Environment::Sandbox),
+            "live" => Ok(Environment::Live),
+            _ => Err(InvalidVariantError(s.to_owned())),
+        }
+    }
+}
+
 /// Represents the access token returned by the OAuth2 authentication.
 ///
 /// https://developer.paypal.com/docs/api/get-an-access-token-postman/
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessToken {
     /// The OAuth2 scopes.
     pub scope: String,
@@ -45,6 +90,12 @@ pub struct Auth {
 }
 
 /// Represents a client used to interact with the paypal api.
+///
+/// # Cancellation
+///
+/// Dropping the future of a call, like with `tokio::time::timeout` or `select!`, leaves the client usable:
+/// the access token is only replaced once fully fetched. The call itself may still have reached paypal though,
+/// send a [request_id](HeaderParams::request_id) to safely retry calls that create or capture payments.
 #[derive(Debug)]
 pub struct Client {
     /// Internal http client
@@ -53,6 +104,39 @@ pub struct Client {
     pub sandbox: bool,
     /// Api Auth information
     pub auth: Auth,
+    /// Decides when failed requests are retried.
+    pub(crate) retry_policy: Arc<dyn RetryPolicy>,
+    /// Hooks run around every request.
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    /// The PayPal-Partner-Attribution-Id sent when the request doesn't specify one.
+    pub(crate) partner_attribution_id: Option<String>,
+    /// The payer id of the merchant the requests are made on behalf of, when the request doesn't specify one.
+    pub(crate) merchant_payer_id: Option<String>,
+    /// The PayPal-Client-Metadata-Id sent when the request doesn't specify one.
+    pub(crate) client_metadata_id: Option<String>,
+    /// How strictly response bodies are deserialized.
+    pub(crate) decode_mode: DecodeMode,
+    /// Where access tokens are cached.
+    pub(crate) token_store: Arc<dyn TokenStore>,
+    /// Serializes the token refreshes of this client when the token store has no locks of its own.
+    pub(crate) refresh_locks: RefreshLocks,
+    /// Set when paypal rejected the access token and a new one was saved in the token store.
+    pub(crate) token_rejected: AtomicBool,
+    /// The headers sent with every request, built again only when the access token changes.
+    pub(crate) base_headers: HeaderMap,
+    /// Delays the requests to stay within the rate limits.
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    /// Fails the requests right away while paypal is unavailable.
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+    /// The id of the webhook whose deliveries are verified.
+    #[cfg(feature = "webhooks")]
+    pub(crate) webhook_id: Option<String>,
+    /// Receives the metrics of the requests.
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Arc<dyn crate::metrics::MetricsRecorder>>,
+    /// The cassette used to record or replay requests.
+    #[cfg(feature = "vcr")]
+    pub(crate) cassette: Option<crate::vcr::Cassette>,
 }
 
 /// Represents the query used in most GET api requests.
@@ -95,21 +179,16 @@ pub struct Query {
 }
 
 /// The preferred server response upon successful completion of the request.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Default)]
 pub enum Prefer {
     /// The server returns a minimal response to optimize communication between the API caller and the server.
     /// A minimal response includes the id, status and HATEOAS links.
     Minimal,
     /// The server returns a complete resource representation, including the current state of the resource.
+    #[default]
     Representation,
 }
 
-impl Default for Prefer {
-    fn default() -> Self {
-        Prefer::Representation
-    }
-}
-
 /// Represents the optional header values used on paypal requests.
 ///
 /// https://developer.paypal.com/docs/api/reference/api-requests/#paypal-auth-assertion
@@ -131,6 +210,11 @@ pub struct HeaderParams {
     pub prefer: Prefer,
     /// The media type. Required for operations with a request body.
     pub content_type: Option<String>,
+    /// The deadline of the call, overriding the timeout of the client.
+    ///
+    /// It covers the access token refresh made before sending the request, the retries, each attempt only getting
+    /// the time left, and the refresh and second attempt made when paypal rejects the access token.
+    pub timeout: Option<Duration>,
 }
 
 #[derive(Debug, Serialize)]
@@ -139,9 +223,381 @@ struct AuthAssertionClaims {
     pub payer_id: String,
 }
 
+/// A request header whose value can't be sent, like one with a line break.
+#[derive(Debug)]
+pub(crate) struct InvalidHeader {
+    name: &'static str,
+    value: String,
+}
+
+/// Converts the value of a request header.
+fn header_value(name: &'static str, value: String) -> Result<header::HeaderValue, InvalidHeader> {
+    header::HeaderValue::from_str(&value).map_err(|_| InvalidHeader { name, value })
+}
+
+/// Builds a [Client] with custom configuration.
+///
+/// Created with [Client::builder], the configuration is validated when calling [build](ClientBuilder::build).
+#[derive(Default, Clone)]
+pub struct ClientBuilder {
+    client_id: Option<String>,
+    secret: Option<String>,
+    environment: Environment,
+    user_agent: Option<String>,
+    partner_attribution_id: Option<String>,
+    merchant_payer_id: Option<String>,
+    client_metadata_id: Option<String>,
+    #[cfg(feature = "webhooks")]
+    webhook_id: Option<String>,
+    default_headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    #[cfg(any(feature = "gzip", feature = "brotli"))]
+    compression: Option<bool>,
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    root_certificates: Vec<Vec<u8>>,
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    built_in_root_certificates: Option<bool>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    decode_mode: DecodeMode,
+    pub(crate) token_store: Option<Arc<dyn TokenStore>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<dyn crate::metrics::MetricsRecorder>>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<crate::vcr::Cassette>,
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ClientBuilder");
+        debug
+            .field("client_id", &self.client_id)
+            .field("secret", &"REDACTED")
+            .field("environment", &self.environment)
+            .field("user_agent", &self.user_agent)
+            .field("partner_attribution_id", &self.partner_attribution_id)
+            .field("merchant_payer_id", &self.merchant_payer_id)
+            .field("client_metadata_id", &self.client_metadata_id);
+        #[cfg(feature = "webhooks")]
+        debug.field("webhook_id", &self.webhook_id);
+        debug
+            .field("default_headers", &self.default_headers)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout);
+        #[cfg(any(feature = "gzip", feature = "brotli"))]
+        debug.field("compression", &self.compression);
+        #[cfg(any(feature = "rustls", feature = "native-tls"))]
+        debug
+            .field("root_certificates", &self.root_certificates)
+            .field("built_in_root_certificates", &self.built_in_root_certificates);
+        debug
+            .field("retry_policy", &self.retry_policy)
+            .field("middleware", &self.middleware)
+            .field("decode_mode", &self.decode_mode)
+            .field("token_store", &self.token_store)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("circuit_breaker", &self.circuit_breaker);
+        #[cfg(feature = "metrics")]
+        debug.field("metrics", &self.metrics);
+        #[cfg(feature = "vcr")]
+        debug.field("cassette", &self.cassette);
+        debug.finish()
+    }
+}
+
+impl ClientBuilder {
+    /// Sets the client id of your app. Required.
+    pub fn client_id<S: Into<String>>(mut self, client_id: S) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    /// Sets the secret of your app. Required.
+    pub fn secret<S: Into<String>>(mut self, secret: S) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Sets the environment, sandbox by default.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Sets the User-Agent header sent on every request.
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets the build notation (BN) code sent as PayPal-Partner-Attribution-Id on every request,
+    /// unless the request's [HeaderParams] specify one.
+    pub fn partner_attribution_id<S: Into<String>>(mut self, partner_attribution_id: S) -> Self {
+        self.partner_attribution_id = Some(partner_attribution_id.into());
+        self
+    }
+
+    /// Makes every request on behalf of the merchant with the given payer id, through the PayPal-Auth-Assertion header,
+    /// unless the request's [HeaderParams] specify one.
+    pub fn merchant_payer_id<S: Into<String>>(mut self, merchant_payer_id: S) -> Self {
+        self.merchant_payer_id = Some(merchant_payer_id.into());
+        self
+    }
+
+    /// Sets the PayPal-Client-Metadata-Id sent on every request, unless the request's [HeaderParams] specify one.
+    pub fn client_metadata_id<S: Into<String>>(mut self, client_metadata_id: S) -> Self {
+        self.client_metadata_id = Some(client_metadata_id.into());
+        self
+    }
+
+    /// Sets the id of the webhook whose deliveries are verified, see [Client::webhook_id].
+    #[cfg(feature = "webhooks")]
+    pub fn webhook_id<S: Into<String>>(mut self, webhook_id: S) -> Self {
+        self.webhook_id = Some(webhook_id.into());
+        self
+    }
+
+    /// Adds a header sent on every request, like a tracing header required by a corporate proxy.
+    ///
+    /// Headers set by the request itself, like Authorization, take precedence.
+    pub fn default_header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the total timeout of every request, from connecting until the response body is read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for connecting to paypal.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether compressed responses are requested, with the encodings of the enabled `gzip` and `brotli`
+    /// features. Enabled by default, it mostly helps with large responses like transaction search pages.
+    #[cfg(any(feature = "gzip", feature = "brotli"))]
+    pub fn compression(mut self, enabled: bool) -> Self {
+        self.compression = Some(enabled);
+        self
+    }
+
+    /// Trusts the root certificates of a PEM encoded bundle, like the CA of a corporate proxy inspecting the traffic.
+    ///
+    /// They are trusted along with the built in ones, unless disabled with
+    /// [built_in_root_certificates](ClientBuilder::built_in_root_certificates).
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn root_certificate<P: Into<Vec<u8>>>(mut self, pem: P) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Sets whether the built in root certificates are trusted: the Mozilla ones bundled with the `rustls` feature,
+    /// or the ones of the system with the `native-tls` feature. Enabled by default.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn built_in_root_certificates(mut self, enabled: bool) -> Self {
+        self.built_in_root_certificates = Some(enabled);
+        self
+    }
+
+    /// Sets the policy deciding when failed requests are retried, an [ExponentialBackoff] is used by default.
+    pub fn retry_policy<P: RetryPolicy + 'static>(mut self, policy: P) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Adds a middleware, they run in the order they are added.
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets how strictly response bodies are deserialized, [DecodeMode::Lenient] by default.
+    ///
+    /// Use [DecodeMode::Strict] in tests to detect fields and enum values this crate doesn't know about.
+    pub fn decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decode_mode = mode;
+        self
+    }
+
+    /// Sets where access tokens are cached, a [MemoryTokenStore] only used by this client by default.
+    pub fn token_store<S: TokenStore + 'static>(mut self, store: S) -> Self {
+        self.token_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Throttles the requests with the given rate limiter, share it with an [Arc] to limit several clients together.
+    pub fn rate_limiter<L: Into<Arc<RateLimiter>>>(mut self, limiter: L) -> Self {
+        self.rate_limiter = Some(limiter.into());
+        self
+    }
+
+    /// Fails the requests right away while the circuit breaker is open,
+    /// share it with an [Arc] to use the same circuit for several clients.
+    pub fn circuit_breaker<B: Into<Arc<CircuitBreaker>>>(mut self, breaker: B) -> Self {
+        self.circuit_breaker = Some(breaker.into());
+        self
+    }
+
+    /// Sends the metrics of every request to the given recorder.
+    #[cfg(feature = "metrics")]
+    pub fn metrics<R: crate::metrics::MetricsRecorder + 'static>(mut self, recorder: R) -> Self {
+        self.metrics = Some(Arc::new(recorder));
+        self
+    }
+
+    /// Records or replays every request made by the client with the given cassette.
+    #[cfg(feature = "vcr")]
+    pub fn cassette(mut self, cassette: crate::vcr::Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Validates the configuration and builds the client.
+    pub fn build(self) -> Result<Client, ClientBuildError> {
+        let client_id = self
+            .client_id
+            .filter(|id| !id.is_empty())
+            .ok_or(ClientBuildError::MissingField("client_id"))?;
+        let secret = self
+            .secret
+            .filter(|secret| !secret.is_empty())
+            .ok_or(ClientBuildError::MissingField("secret"))?;
+
+        if let Some(partner_attribution_id) = &self.partner_attribution_id {
+            header::HeaderValue::from_str(partner_attribution_id)
+                .map_err(|_| ClientBuildError::InvalidHeader("PayPal-Partner-Attribution-Id"))?;
+        }
+        if let Some(client_metadata_id) = &self.client_metadata_id {
+            header::HeaderValue::from_str(client_metadata_id)
+                .map_err(|_| ClientBuildError::InvalidHeader("PayPal-Client-Metadata-Id"))?;
+        }
+
+        let mut default_headers = HeaderMap::new();
+        for (name, value) in &self.default_headers {
+            let name = header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ClientBuildError::InvalidDefaultHeader(name.clone()))?;
+            let value = header::HeaderValue::from_str(value)
+                .map_err(|_| ClientBuildError::InvalidDefaultHeader(name.to_string()))?;
+            default_headers.append(name, value);
+        }
+
+        let mut builder = reqwest::Client::builder().default_headers(default_headers);
+        if let Some(user_agent) = self.user_agent {
+            let user_agent = header::HeaderValue::from_str(&user_agent)
+                .map_err(|_| ClientBuildError::InvalidHeader("User-Agent"))?;
+            builder = builder.user_agent(user_agent);
+        }
+        for (name, timeout) in [("timeout", self.timeout), ("connect_timeout", self.connect_timeout)] {
+            if timeout == Some(Duration::from_secs(0)) {
+                return Err(ClientBuildError::InvalidTimeout(name));
+            }
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        #[cfg(any(feature = "rustls", feature = "native-tls"))]
+        {
+            for pem in &self.root_certificates {
+                let certificates = reqwest::Certificate::from_pem_bundle(pem)
+                    .ok()
+                    .filter(|certificates| !certificates.is_empty())
+                    .ok_or(ClientBuildError::InvalidRootCertificate)?;
+                for certificate in certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+            if let Some(enabled) = self.built_in_root_certificates {
+                builder = builder.tls_built_in_root_certs(enabled);
+            }
+        }
+        #[cfg(feature = "gzip")]
+        if let Some(enabled) = self.compression {
+            builder = builder.gzip(enabled);
+        }
+        #[cfg(feature = "brotli")]
+        if let Some(enabled) = self.compression {
+            builder = builder.brotli(enabled);
+        }
+
+        let mut client = Client {
+            client: builder.build()?,
+            sandbox: self.environment == Environment::Sandbox,
+            auth: Auth {
+                client_id,
+                secret,
+                access_token: None,
+                expires: None,
+            },
+            retry_policy: self
+                .retry_policy
+                .unwrap_or_else(|| Arc::new(ExponentialBackoff::default())),
+            middleware: self.middleware,
+            partner_attribution_id: self.partner_attribution_id,
+            merchant_payer_id: self.merchant_payer_id,
+            client_metadata_id: self.client_metadata_id,
+            decode_mode: self.decode_mode,
+            token_store: self.token_store.unwrap_or_else(|| Arc::new(MemoryTokenStore::new())),
+            refresh_locks: RefreshLocks::new(),
+            token_rejected: AtomicBool::new(false),
+            base_headers: HeaderMap::new(),
+            rate_limiter: self.rate_limiter,
+            circuit_breaker: self.circuit_breaker,
+            #[cfg(feature = "webhooks")]
+            webhook_id: self.webhook_id,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+            #[cfg(feature = "vcr")]
+            cassette: self.cassette,
+        };
+        client.base_headers = client.build_base_headers();
+        Ok(client)
+    }
+}
+
+/// The access token of the authorization header, if it's a bearer token.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
 impl Client {
+    /// Returns a builder to configure a client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use paypal_rs::client::{Client, Environment};
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::builder()
+    ///     .client_id("client_id")
+    ///     .secret("secret")
+    ///     .environment(Environment::Sandbox)
+    ///     .user_agent("my-shop/1.0")
+    ///     .timeout(Duration::from_secs(30))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
     /// Returns a new client, you must get_access_token afterwards to interact with the api.
     ///
+    /// Use [Client::builder] for more configuration options.
+    ///
     /// # Examples
     ///
     /// ```
@@ -162,7 +618,7 @@ impl Client {
     /// }
     /// ```
     pub fn new(client_id: String, secret: String, sandbox: bool) -> Client {
-        Client {
+        let mut client = Client {
             client: reqwest::Client::new(),
             sandbox,
             auth: Auth {
@@ -171,105 +627,535 @@ impl Client {
                 access_token: None,
                 expires: None,
             },
-        }
+            retry_policy: Arc::new(ExponentialBackoff::default()),
+            middleware: Vec::new(),
+            partner_attribution_id: None,
+            merchant_payer_id: None,
+            client_metadata_id: None,
+            decode_mode: DecodeMode::default(),
+            token_store: Arc::new(MemoryTokenStore::new()),
+            refresh_locks: RefreshLocks::new(),
+            token_rejected: AtomicBool::new(false),
+            base_headers: HeaderMap::new(),
+            rate_limiter: None,
+            circuit_breaker: None,
+            #[cfg(feature = "webhooks")]
+            webhook_id: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
+        };
+        client.base_headers = client.build_base_headers();
+        client
+    }
+
+    /// Sets the policy deciding when failed requests are retried, an [ExponentialBackoff] is used by default.
+    ///
+    /// Use [NoRetry](crate::retry::NoRetry) to disable retries.
+    pub fn set_retry_policy<P: RetryPolicy + 'static>(&mut self, policy: P) {
+        self.retry_policy = Arc::new(policy);
+    }
+
+    /// Records every request made by this client into the given cassette, or replays them from it.
+    ///
+    /// See the [vcr](crate::vcr) module for more information.
+    #[cfg(feature = "vcr")]
+    pub fn set_cassette(&mut self, cassette: crate::vcr::Cassette) {
+        self.cassette = Some(cassette);
     }
 
     /// Paypal endpoint being used
     pub fn endpoint(&self) -> &str {
+        self.environment().endpoint()
+    }
+
+    /// Paypal environment being used
+    pub fn environment(&self) -> Environment {
         if self.sandbox {
-            SANDBOX_ENDPOINT
+            Environment::Sandbox
         } else {
-            LIVE_ENDPOINT
+            Environment::Live
         }
     }
 
+    /// The id of the webhook set with [ClientBuilder::webhook_id], to pass to
+    /// [verify_webhook_request](Self::verify_webhook_request).
+    #[cfg(feature = "webhooks")]
+    pub fn webhook_id(&self) -> Option<&str> {
+        self.webhook_id.as_deref()
+    }
+
     /// Sets up the request headers as required on https://developer.paypal.com/docs/api/reference/api-requests/#http-request-headers
     pub async fn setup_headers(
         &mut self,
         builder: reqwest::RequestBuilder,
         header_params: HeaderParams,
     ) -> reqwest::RequestBuilder {
+        let timeout = header_params.timeout;
+        let start = Instant::now();
+
         // Check if the token hasn't expired here, since it's called before any other call.
-        if let Err(e) = self.get_access_token().await {
-            log::warn!(target: "paypal-rs", "error getting access token: {:?}", e);
+        let refresh = self.get_access_token();
+        let refreshed = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, refresh).await.ok(),
+            None => Some(refresh.await),
+        };
+        match refreshed {
+            Some(Ok(())) => {}
+            Some(Err(e)) => log::warn!(target: "paypal-rs", "error getting access token: {:?}", e),
+            None => log::warn!(target: "paypal-rs", "timed out getting access token"),
         }
 
-        let mut headers = HeaderMap::new();
-
-        headers.append(header::ACCEPT, "application/json".parse().unwrap());
+        let builder = self.with_headers(builder, header_params);
+        match timeout {
+            // The refresh used part of the deadline, the request only gets the time left.
+            Some(timeout) => builder.timeout(timeout.saturating_sub(start.elapsed())),
+            None => builder,
+        }
+    }
 
-        if let Some(token) = &self.auth.access_token {
-            headers.append(
-                header::AUTHORIZATION,
-                format!("Bearer {}", token.access_token).parse().unwrap(),
-            );
+    /// Sets the request headers with the current access token, without refreshing it.
+    ///
+    /// A header value that can't be sent is left for reqwest to reject, so the call fails with a builder error
+    /// instead of panicking.
+    pub(crate) fn with_headers(
+        &self,
+        builder: reqwest::RequestBuilder,
+        header_params: HeaderParams,
+    ) -> reqwest::RequestBuilder {
+        match self.headers(header_params) {
+            Ok(headers) => builder.headers(headers),
+            Err(InvalidHeader { name, value }) => builder.header(name, value),
         }
+    }
+
+    /// Builds the request headers with the current access token, without refreshing it.
+    pub(crate) fn headers(&self, header_params: HeaderParams) -> Result<HeaderMap, InvalidHeader> {
+        // The access token can also be replaced through the public auth field, which doesn't update the base headers.
+        let token = self.auth.access_token.as_ref().map(|token| token.access_token.as_str());
+        let mut headers = if bearer_token(&self.base_headers) == token {
+            self.base_headers.clone()
+        } else {
+            self.build_base_headers()
+        };
 
         if let Some(merchant_payer_id) = header_params.merchant_payer_id {
-            let claims = AuthAssertionClaims {
-                iss: self.auth.client_id.clone(),
-                payer_id: merchant_payer_id,
-            };
-            let jwt_header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256);
-            let token = jsonwebtoken::encode(
-                &jwt_header,
-                &claims,
-                &jsonwebtoken::EncodingKey::from_secret(self.auth.secret.as_ref()),
-            )
-            .unwrap();
-            let encoded_token = base64::encode(token);
-            headers.append("PayPal-Auth-Assertion", encoded_token.parse().unwrap());
+            headers.insert("PayPal-Auth-Assertion", self.auth_assertion(merchant_payer_id));
+        }
+
+        let values = [
+            ("PayPal-Client-Metadata-Id", header_params.client_metadata_id),
+            ("PayPal-Partner-Attribution-Id", header_params.partner_attribution_id),
+            ("PayPal-Request-Id", header_params.request_id),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                headers.insert(name, header_value(name, value)?);
+            }
         }
 
-        if let Some(client_metadata_id) = header_params.client_metadata_id {
-            headers.append("PayPal-Client-Metadata-Id", client_metadata_id.parse().unwrap());
+        let prefer = match header_params.prefer {
+            Prefer::Minimal => "return=minimal",
+            Prefer::Representation => "return=representation",
+        };
+        headers.insert("Prefer", header::HeaderValue::from_static(prefer));
+
+        if let Some(content_type) = header_params.content_type {
+            headers.insert(header::CONTENT_TYPE, header_value("Content-Type", content_type)?);
         }
 
-        if let Some(partner_attribution_id) = header_params.partner_attribution_id {
-            headers.append("PayPal-Partner-Attribution-Id", partner_attribution_id.parse().unwrap());
+        Ok(headers)
+    }
+
+    /// Builds the headers that don't depend on the request: the accept and authorization headers,
+    /// and the defaults of the client.
+    fn build_base_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+        if let Some(token) = &self.auth.access_token {
+            let mut authorization: header::HeaderValue = format!("Bearer {}", token.access_token).parse().unwrap();
+            authorization.set_sensitive(true);
+            headers.insert(header::AUTHORIZATION, authorization);
         }
 
-        if let Some(request_id) = header_params.request_id {
-            headers.append("PayPal-Request-Id", request_id.parse().unwrap());
+        if let Some(merchant_payer_id) = &self.merchant_payer_id {
+            headers.insert("PayPal-Auth-Assertion", self.auth_assertion(merchant_payer_id.clone()));
         }
 
-        match header_params.prefer {
-            Prefer::Minimal => headers.append("Prefer", "return=minimal".parse().unwrap()),
-            Prefer::Representation => headers.append("Prefer", "return=representation".parse().unwrap()),
-        };
+        if let Some(client_metadata_id) = &self.client_metadata_id {
+            headers.insert("PayPal-Client-Metadata-Id", client_metadata_id.parse().unwrap());
+        }
 
-        if let Some(content_type) = header_params.content_type {
-            headers.append(header::CONTENT_TYPE, content_type.parse().unwrap());
+        if let Some(partner_attribution_id) = &self.partner_attribution_id {
+            headers.insert("PayPal-Partner-Attribution-Id", partner_attribution_id.parse().unwrap());
         }
 
-        builder.headers(headers)
+        headers
+    }
+
+    /// Builds the PayPal-Auth-Assertion header to make a request on behalf of the given merchant.
+    fn auth_assertion(&self, merchant_payer_id: String) -> header::HeaderValue {
+        let claims = AuthAssertionClaims {
+            iss: self.auth.client_id.clone(),
+            payer_id: merchant_payer_id,
+        };
+        let jwt_header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256);
+        let token = jsonwebtoken::encode(
+            &jwt_header,
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(self.auth.secret.as_ref()),
+        )
+        .unwrap();
+        base64::encode(token).parse().unwrap()
     }
 
     /// Gets a access token used in all the api calls.
+    ///
+    /// The token is looked up in the [TokenStore] first, and saved there once fetched from paypal.
     pub async fn get_access_token(&mut self) -> Result<(), ResponseError> {
-        if !self.access_token_expired() {
+        let rejected = self.token_rejected.swap(false, Ordering::Relaxed);
+        if !rejected && !self.access_token_expired() {
             return Ok(());
         }
-        let res = self
+
+        let key = self.token_key();
+        if let Some(cached) = self.token_store.get(&key).await {
+            if self.restore_token(cached) {
+                return Ok(());
+            }
+        }
+
+        let token = self.refresh_access_token(None).await?;
+        self.restore_token(token);
+        Ok(())
+    }
+
+    /// Gets the access token and opens a connection to paypal ahead of the first request,
+    /// so the first checkout after a deploy doesn't wait for the token and the TLS handshake.
+    ///
+    /// The connection is kept in the pool of the http client until it is idle for too long.
+    pub async fn warm_up(&mut self) -> Result<(), ResponseError> {
+        self.get_access_token().await?;
+
+        // The token may come from the token store, so a request is needed to be sure a connection is open.
+        let request = self.client.head(self.endpoint()).build()?;
+        if let Err(e) = self.send_raw(request).await {
+            log::debug!(target: "paypal-rs", "error opening a connection to paypal: {:?}", e);
+        }
+        Ok(())
+    }
+
+    /// Fetches a new access token, unless a concurrent refresh already got one.
+    ///
+    /// The refreshes of the clients sharing a [TokenStore] are made one at a time, the ones waiting use the token
+    /// fetched by the first one, so many requests hitting an expired token only make a single call to paypal.
+    /// The token paypal rejected, if any, is never reused.
+    async fn refresh_access_token(&self, rejected: Option<&str>) -> Result<CachedToken, ResponseError> {
+        let key = self.token_key();
+        let locks = self.token_store.refresh_locks().unwrap_or(&self.refresh_locks);
+        let slot = locks.slot(&key);
+        let mut latest = slot.lock().await;
+        let usable =
+            |cached: &CachedToken| !cached.is_expired() && Some(cached.token.access_token.as_str()) != rejected;
+
+        if let Some(cached) = latest.clone().filter(usable) {
+            return Ok(cached);
+        }
+        if let Some(cached) = self.token_store.get(&key).await.filter(usable) {
+            *latest = Some(cached.clone());
+            return Ok(cached);
+        }
+
+        let cached = self.fetch_access_token().await?;
+        *latest = Some(cached.clone());
+        Ok(cached)
+    }
+
+    /// Fetches a new access token from paypal and saves it in the [TokenStore].
+    async fn fetch_access_token(&self) -> Result<CachedToken, ResponseError> {
+        let builder = self
             .client
             .post(format!("{}/v1/oauth2/token", self.endpoint()).as_str())
             .basic_auth(&self.auth.client_id, Some(&self.auth.secret))
             .header("Content-Type", "x-www-form-urlencoded")
             .header("Accept", "application/json")
-            .body("grant_type=client_credentials")
-            .send()
-            .await
-            .map_err(ResponseError::HttpError)?;
+            .body("grant_type=client_credentials");
+        let request = builder.build()?;
+        let timeout = request.timeout().copied();
+        let res = self.execute_with_retries(request, Instant::now(), timeout).await;
+
+        #[cfg(feature = "metrics")]
+        self.record_metric(crate::metrics::MetricEvent::TokenRefresh {
+            success: res.as_ref().is_ok_and(|res| res.status().is_success()),
+        });
 
+        let res = res?;
         if res.status().is_success() {
-            let token = res.json::<AccessToken>().await.map_err(ResponseError::HttpError)?;
-            self.auth.expires = Some((Instant::now(), Duration::new(token.expires_in, 0)));
-            self.auth.access_token = Some(token);
-            Ok(())
+            let token = decode::<AccessToken>(res, self.decode_mode).await?;
+            let cached = CachedToken {
+                expires_at: chrono::Utc::now() + chrono::Duration::seconds(token.expires_in as i64),
+                token: token.clone(),
+            };
+            self.token_store.put(&self.token_key(), cached.clone()).await;
+            Ok(cached)
         } else {
-            Err(ResponseError::ApiError(
-                res.json::<PaypalError>().await.map_err(ResponseError::HttpError)?,
-            ))
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Sends a request built with the inner http client, retrying it as the retry policy says.
+    ///
+    /// Every api call goes through here, so this is the place to hook anything that applies to all requests.
+    ///
+    /// When paypal rejects the access token with a 401, as when it was revoked, a new one is fetched and the request
+    /// is sent again once. The timeout of the request bounds both passes and the token refresh between them.
+    pub(crate) async fn execute(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::Response, ResponseError> {
+        let request = builder.build()?;
+        #[cfg(feature = "validation")]
+        {
+            let violations = crate::validation::validate_request(&request);
+            if !violations.is_empty() {
+                return Err(ResponseError::InvalidRequest(violations));
+            }
+        }
+
+        let bearer = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .is_some_and(|value| value.as_bytes().starts_with(b"Bearer "));
+        let reauth = if bearer { request.try_clone() } else { None };
+        let rejected = bearer_token(request.headers()).map(ToOwned::to_owned);
+        let timeout = request.timeout().copied();
+        let start = Instant::now();
+        let remaining = || timeout.map(|timeout| timeout.saturating_sub(start.elapsed()));
+
+        let res = self.execute_with_retries(request, start, timeout).await?;
+        let mut request = match reauth {
+            Some(request) if res.status() == reqwest::StatusCode::UNAUTHORIZED => request,
+            _ => return Ok(res),
+        };
+        if remaining().is_some_and(|remaining| remaining.is_zero()) {
+            log::debug!(target: "paypal-rs", "access token rejected, no time left to fetch a new one");
+            return Ok(res);
+        }
+
+        log::debug!(target: "paypal-rs", "access token rejected, fetching a new one");
+        let refresh = self.refresh_access_token(rejected.as_deref());
+        let refreshed = match remaining() {
+            Some(remaining) => tokio::time::timeout(remaining, refresh).await.ok(),
+            None => Some(refresh.await),
+        };
+        let token = match refreshed {
+            Some(Ok(token)) => token,
+            Some(Err(e)) => {
+                log::warn!(target: "paypal-rs", "error getting access token: {:?}", e);
+                return Ok(res);
+            }
+            None => {
+                log::warn!(target: "paypal-rs", "timed out getting access token");
+                return Ok(res);
+            }
+        };
+        self.token_rejected.store(true, Ordering::Relaxed);
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", token.token.access_token).parse().unwrap(),
+        );
+        if let Some(remaining) = remaining() {
+            if remaining.is_zero() {
+                return Ok(res);
+            }
+            *request.timeout_mut() = Some(remaining);
+        }
+        self.execute_with_retries(request, start, timeout).await
+    }
+
+    /// Sends a request, retrying it as the retry policy says.
+    ///
+    /// The attempts are counted as started at `start`, and none is sent once `timeout` elapsed since then.
+    async fn execute_with_retries(
+        &self,
+        mut request: reqwest::Request,
+        start: Instant,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response, ResponseError> {
+        let idempotent = retry::is_idempotent(&request);
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            // Requests with a streaming body can't be cloned, and so can't be retried.
+            let next = request.try_clone();
+            let result = self.send(request).await;
+
+            let mut next = match next {
+                Some(next) => next,
+                None => return result,
+            };
+
+            let context = match &result {
+                Ok(res) if res.status().is_success() => return result,
+                Ok(res) => RetryContext {
+                    attempt,
+                    error_class: ErrorClass::from_status(res.status()),
+                    status: Some(res.status()),
+                    elapsed: start.elapsed(),
+                    retry_after: retry::retry_after(res),
+                    idempotent,
+                },
+                Err(ResponseError::HttpError(e)) => RetryContext {
+                    attempt,
+                    error_class: ErrorClass::from_error(e),
+                    status: e.status(),
+                    elapsed: start.elapsed(),
+                    retry_after: None,
+                    idempotent,
+                },
+                Err(_) => return result,
+            };
+
+            match self.retry_policy.retry_delay(&context) {
+                Some(delay) => {
+                    if let Some(timeout) = timeout {
+                        match timeout.checked_sub(start.elapsed() + delay) {
+                            Some(remaining) if !remaining.is_zero() => *next.timeout_mut() = Some(remaining),
+                            _ => return result,
+                        }
+                    }
+                    log::debug!(target: "paypal-rs", "retrying request in {:?}: {:?}", delay, context);
+                    #[cfg(feature = "metrics")]
+                    self.record_metric(crate::metrics::MetricEvent::Retry {
+                        method: next.method().as_str(),
+                        endpoint: &crate::metrics::endpoint(next.url().path()),
+                        attempt,
+                    });
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                }
+                None => return result,
+            }
+        }
+    }
+
+    /// Sends a single request through the middleware, without retrying.
+    async fn send(&self, mut request: reqwest::Request) -> Result<reqwest::Response, ResponseError> {
+        if let Some(breaker) = &self.circuit_breaker {
+            if !breaker.allow() {
+                return Err(ResponseError::CircuitOpen);
+            }
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(request.url().path()).await;
+        }
+
+        for middleware in &self.middleware {
+            middleware.on_request(&mut request);
+        }
+
+        #[cfg(feature = "metrics")]
+        let (method, endpoint, start) = (
+            request.method().clone(),
+            crate::metrics::endpoint(request.url().path()),
+            Instant::now(),
+        );
+
+        let result = self.send_raw(request).await;
+
+        if let Some(breaker) = &self.circuit_breaker {
+            // Only failures on paypal's side count, a rejected request shows that paypal is up.
+            breaker.record(match &result {
+                Ok(res) => res.status().is_server_error(),
+                Err(ResponseError::HttpError(e)) => !matches!(
+                    ErrorClass::from_error(e),
+                    ErrorClass::ClientError | ErrorClass::RateLimited
+                ),
+                Err(_) => false,
+            });
+        }
+
+        #[cfg(feature = "metrics")]
+        self.record_metric(crate::metrics::MetricEvent::Request {
+            method: method.as_str(),
+            endpoint: &endpoint,
+            status: match &result {
+                Ok(res) => Some(res.status().as_u16()),
+                Err(e) => e.status(),
+            },
+            latency: start.elapsed(),
+        });
+
+        for middleware in &self.middleware {
+            match &result {
+                Ok(res) => middleware.on_response(res),
+                Err(ResponseError::HttpError(e)) => middleware.on_error(e),
+                Err(_) => {}
+            }
+        }
+
+        result
+    }
+
+    /// Sends the event to the metrics recorder, if there is one.
+    #[cfg(feature = "metrics")]
+    fn record_metric(&self, event: crate::metrics::MetricEvent<'_>) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&event);
+        }
+    }
+
+    /// Sends a single request over the network, or through the cassette if there is one.
+    async fn send_raw(&self, request: reqwest::Request) -> Result<reqwest::Response, ResponseError> {
+        #[cfg(feature = "vcr")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.execute(&self.client, request).await;
+        }
+
+        Ok(self.client.execute(request).await?)
+    }
+
+    /// The key the access token of this client is stored under in the [TokenStore].
+    fn token_key(&self) -> String {
+        let environment = match self.environment() {
+            Environment::Sandbox => "sandbox",
+            Environment::Live => "live",
+        };
+        format!("{}:{}", environment, self.auth.client_id)
+    }
+
+    /// Uses the given access token instead of fetching a new one, unless it expired.
+    ///
+    /// Short lived processes can save the token returned by [cached_token](Client::cached_token)
+    /// and restore it the next time they run, instead of authenticating every time.
+    pub fn with_access_token(mut self, token: CachedToken) -> Self {
+        self.restore_token(token);
+        self
+    }
+
+    /// The current access token along with when it expires, if there is one.
+    pub fn cached_token(&self) -> Option<CachedToken> {
+        let token = self.auth.access_token.clone()?;
+        let (fetched, lifetime) = self.auth.expires?;
+        let remaining = lifetime.checked_sub(fetched.elapsed())?;
+
+        Some(CachedToken {
+            token,
+            expires_at: chrono::Utc::now() + chrono::Duration::from_std(remaining).ok()?,
+        })
+    }
+
+    /// Sets the given token as the current one, returns false if it expired.
+    fn restore_token(&mut self, cached: CachedToken) -> bool {
+        match (cached.expires_at - chrono::Utc::now()).to_std() {
+            Ok(remaining) if !remaining.is_zero() => {
+                self.auth.expires = Some((Instant::now(), remaining));
+                self.auth.access_token = Some(cached.token);
+                self.base_headers = self.build_base_headers();
+                true
+            }
+            _ => false,
         }
     }
 
@@ -281,39 +1167,44 @@ impl Client {
             true
         }
     }
-}
-
-pub(crate) trait FromResponse: Sized {
-    type Response;
-
-    fn from_response(res: Self::Response) -> Self;
-}
 
-pub(crate) trait Endpoint {
-    type Query: Serialize;
-    type Body: Serialize;
-    type Response: FromResponse;
-
-    fn path(&self) -> Cow<str>;
+    /// Follows a HATEOAS link returned by paypal, using the link's method, and decodes the response.
+    ///
+    /// Only links to the api endpoint of this client can be followed, since the access token is sent along.
+    /// Links meant for the buyer, like [approve](crate::common::LinkRel::Approve), must be opened in a browser instead.
+    pub async fn follow_link<T: DeserializeOwned>(&mut self, link: &LinkDescription) -> Result<T, ResponseError> {
+        let endpoint = format!("{}/", self.endpoint());
+        if !link.href.starts_with(&endpoint) {
+            return Err(ResponseError::InvalidLink(link.href.clone()));
+        }
 
-    fn method(&self) -> reqwest::Method {
-        reqwest::Method::GET
-    }
+        let method = link.method.unwrap_or(LinkMethod::Get);
+        let builder = self
+            .setup_headers(self.client.request(method.into(), &link.href), HeaderParams::default())
+            .await;
 
-    fn query(&self) -> Option<&Self::Query> {
-        None
-    }
+        let res = self.execute(builder).await?;
 
-    fn body(&self) -> Option<&Self::Body> {
-        None
+        if res.status().is_success() {
+            decode::<T>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "orders",
+    feature = "payments",
+    feature = "invoicing",
+    feature = "webhooks"
+))]
 mod tests {
-    use crate::common::Currency;
+    use crate::common::{Currency, Money, Patch, PhoneWithCountryCode};
     use crate::countries::Country;
-    use crate::{webhooks::*, orders::*, client::Client, client::HeaderParams};
+    use crate::errors::ClientBuildError;
+    use crate::{client::*, orders::*};
     use std::env;
     use std::str::FromStr;
 
@@ -368,10 +1259,1205 @@ mod tests {
     }
 
     #[test]
-    fn test_currency() {
-        assert_eq!(Currency::EUR.to_string(), "EUR");
-        assert_eq!(Currency::JPY.to_string(), "JPY");
-        assert_eq!(Currency::JPY, Currency::from_str("JPY").unwrap());
+    fn test_builder() {
+        let builder = Client::builder()
+            .client_id("id")
+            .secret("EGnHDxD_qRPdaLdZz8")
+            .environment(Environment::Live)
+            .partner_attribution_id("BN-CODE");
+        let debug = format!("{:?}", builder);
+        assert!(debug.contains(r#"secret: "REDACTED""#) && !debug.contains("EGnHDxD_qRPdaLdZz8"));
+        let client = builder.build().unwrap();
+        assert_eq!(client.endpoint(), LIVE_ENDPOINT);
+        assert_eq!(client.partner_attribution_id.as_deref(), Some("BN-CODE"));
+
+        let client = Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .client_metadata_id("metadata-id")
+            .default_header("X-Correlation-Id", "abc")
+            .build()
+            .unwrap();
+        let headers = client.headers(HeaderParams::default()).unwrap();
+        assert_eq!(headers["PayPal-Client-Metadata-Id"], "metadata-id");
+        let headers = client
+            .headers(HeaderParams {
+                client_metadata_id: Some("request-id".to_owned()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(headers["PayPal-Client-Metadata-Id"], "request-id");
+
+        let token: AccessToken = serde_json::from_str(
+            r#"{"scope":"","access_token":"A21AA","token_type":"Bearer","app_id":"APP-80W284485P519543T","expires_in":32400,"nonce":"nonce"}"#,
+        )
+        .unwrap();
+        let mut client = client.with_access_token(CachedToken {
+            token: token.clone(),
+            expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+        });
+        assert_eq!(
+            client.headers(HeaderParams::default()).unwrap()[header::AUTHORIZATION],
+            "Bearer A21AA"
+        );
+        client.auth.access_token = Some(AccessToken {
+            access_token: "A21AB".to_owned(),
+            ..token
+        });
+        let headers = client.headers(HeaderParams::default()).unwrap();
+        assert_eq!(headers[header::AUTHORIZATION], "Bearer A21AB");
+        assert_eq!(headers["PayPal-Client-Metadata-Id"], "metadata-id");
+        assert!(matches!(
+            Client::builder()
+                .client_id("id")
+                .secret("secret")
+                .default_header("X Bad", "abc")
+                .build(),
+            Err(ClientBuildError::InvalidDefaultHeader(name)) if name == "X Bad"
+        ));
+
+        assert!(matches!(
+            Client::builder().client_id("id").build(),
+            Err(ClientBuildError::MissingField("secret"))
+        ));
+        assert!(matches!(
+            Client::builder()
+                .client_id("id")
+                .secret("secret")
+                .user_agent("bad\n")
+                .build(),
+            Err(ClientBuildError::InvalidHeader("User-Agent"))
+        ));
+    }
+
+    #[test]
+    fn test_restore_token() {
+        let saved = r#"{
+            "token": {"scope":"","access_token":"A21AA","token_type":"Bearer","app_id":"APP-80W284485P519543T","expires_in":32400,"nonce":"nonce"},
+            "expires_at": "2999-01-01T00:00:00Z"
+        }"#;
+        let token: CachedToken = serde_json::from_str(saved).unwrap();
+        let client = Client::new("id".to_owned(), "secret".to_owned(), true).with_access_token(token);
+        assert!(!client.access_token_expired());
+
+        let cached = client.cached_token().unwrap();
+        assert_eq!(cached.token.access_token, "A21AA");
+        let json = serde_json::to_value(&cached).unwrap();
+        assert_eq!(json["token"]["access_token"], "A21AA");
+        assert!(json["expires_at"].as_str().unwrap().starts_with("2999-01-01T00:00"));
+
+        let mut expired: CachedToken = serde_json::from_str(saved).unwrap();
+        expired.expires_at = chrono::Utc::now() - chrono::Duration::seconds(1);
+        let client = Client::new("id".to_owned(), "secret".to_owned(), true).with_access_token(expired);
+        assert!(client.access_token_expired());
+        assert!(client.cached_token().is_none());
+    }
+
+    #[test]
+    fn test_currency() {
+        assert_eq!(Currency::EUR.to_string(), "EUR");
+        assert_eq!(Currency::JPY.to_string(), "JPY");
+        assert_eq!(Currency::JPY, Currency::from_str("JPY").unwrap());
+        assert_eq!(Currency::SEK, Currency::from_str("SEK").unwrap());
+        assert_eq!(Currency::from_str("XYZ").unwrap(), Currency::Other("XYZ".to_owned()));
+        assert!(Currency::from_str("euro").is_err());
+
+        assert_eq!(serde_json::to_string(&Currency::TND).unwrap(), r#""TND""#);
+        let other: Currency = serde_json::from_str(r#""XYZ""#).unwrap();
+        assert_eq!(other.to_string(), "XYZ");
+        assert_eq!(serde_json::to_string(&other).unwrap(), r#""XYZ""#);
+    }
+
+    #[test]
+    fn test_money_arithmetic() {
+        use crate::errors::MoneyError;
+
+        let money = |currency, value: &str| Money {
+            currency_code: currency,
+            value: value.to_owned(),
+        };
+        let total = money(Currency::USD, "10.5")
+            .checked_add(&money(Currency::USD, "0.25"))
+            .unwrap();
+        assert_eq!(total, money(Currency::USD, "10.75"));
+        let remaining = total.checked_sub(&money(Currency::USD, "20.00")).unwrap();
+        assert_eq!(remaining.value, "-9.25");
+        assert_eq!(money(Currency::JPY, "150").mul_quantity(3).unwrap().value, "450");
+        assert_eq!(money(Currency::TND, "1.5").mul_quantity(2).unwrap().value, "3.000");
+
+        assert!(matches!(
+            money(Currency::USD, "1.00").checked_add(&money(Currency::EUR, "1.00")),
+            Err(MoneyError::CurrencyMismatch(Currency::USD, Currency::EUR))
+        ));
+        assert!(matches!(
+            money(Currency::USD, "1.005").mul_quantity(2),
+            Err(MoneyError::InvalidAmount(_))
+        ));
+        assert!(money(Currency::USD, "1.").mul_quantity(2).is_err());
+        assert_eq!(money(Currency::JPY, "100.00").mul_quantity(1).unwrap().value, "100");
+
+        let amount = Amount::new(Currency::EUR, "19.99").mul_quantity(2).unwrap();
+        let amount = amount.checked_sub(&Amount::new(Currency::EUR, "5")).unwrap();
+        assert_eq!(amount.value, "34.98");
+
+        // Breakdowns are combined field by field, and can't be mixed with amounts without one.
+        let with_breakdown = |value: &str, item_total: &str, shipping: Option<&str>| Amount {
+            breakdown: Some(Breakdown {
+                item_total: Some(money(Currency::EUR, item_total)),
+                shipping: shipping.map(|shipping| money(Currency::EUR, shipping)),
+                ..Default::default()
+            }),
+            ..Amount::new(Currency::EUR, value)
+        };
+        let total = with_breakdown("12.00", "10.00", Some("2"))
+            .checked_add(&with_breakdown("5.5", "5.5", None))
+            .unwrap()
+            .mul_quantity(2)
+            .unwrap();
+        assert_eq!(total.value, "35.00");
+        let breakdown = total.breakdown.unwrap();
+        assert_eq!(breakdown.item_total, Some(money(Currency::EUR, "31.00")));
+        assert_eq!(breakdown.shipping, Some(money(Currency::EUR, "4.00")));
+        assert!(matches!(
+            with_breakdown("12.00", "12.00", None).checked_sub(&Amount::new(Currency::EUR, "1.00")),
+            Err(MoneyError::MissingBreakdown)
+        ));
+        assert_eq!(
+            money(Currency::EUR, "1.50")
+                .checked_cmp(&money(Currency::EUR, "1.5"))
+                .unwrap(),
+            std::cmp::Ordering::Equal
+        );
+        assert_eq!(Money::zero(Currency::JPY).value, "0");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_money_decimal() {
+        use crate::common::Money;
+        use rust_decimal::Decimal;
+
+        let money = Money::from_decimal(Currency::EUR, Decimal::new(105, 1)).unwrap();
+        assert_eq!(money.value, "10.50");
+        assert_eq!(money.decimal().unwrap(), Decimal::new(1050, 2));
+
+        assert_eq!(
+            Money::from_decimal(Currency::JPY, Decimal::new(1000, 0)).unwrap().value,
+            "1000"
+        );
+        assert!(Money::from_decimal(Currency::JPY, Decimal::new(10005, 1)).is_err());
+        assert!(Money::from_decimal(Currency::USD, Decimal::new(10005, 3)).is_err());
+    }
+
+    #[test]
+    fn test_item_breakdown() {
+        use crate::common::Money;
+
+        let eur = |value: &str| Money {
+            currency_code: Currency::EUR,
+            value: value.to_owned(),
+        };
+        let items = vec![
+            Item::new("Shirt", eur("10.50"), 2).tax(eur("2.10")).sku("SHIRT-1"),
+            Item::new("Hat", eur("5.00"), 1).category(ItemCategoryType::Physical),
+        ];
+        assert_eq!(items[0].quantity, "2");
+        assert_eq!(items[0].quantity_count(), Some(2));
+
+        let breakdown = Breakdown::from_items(&items).unwrap();
+        assert_eq!(breakdown.item_total, Some(eur("26.00")));
+        assert_eq!(breakdown.tax_total, Some(eur("4.20")));
+    }
+
+    #[test]
+    fn test_payment_collection_totals() {
+        use crate::common::Money;
+        use crate::orders::PaymentCollection;
+
+        let payments: PaymentCollection = serde_json::from_str(
+            r#"{
+                "captures": [
+                    {"id": "1", "status": "PARTIALLY_REFUNDED", "amount": {"currency_code": "EUR", "value": "10.10"},
+                     "create_time": "2022-01-01T10:00:00Z"},
+                    {"id": "2", "status": "COMPLETED", "amount": {"currency_code": "EUR", "value": "0.20"},
+                     "create_time": "2022-01-02T10:00:00Z"},
+                    {"id": "3", "status": "DECLINED", "amount": {"currency_code": "EUR", "value": "5.00"},
+                     "create_time": "2022-01-01T12:00:00Z"},
+                    {"id": "4", "status": "COMPLETED", "amount": {"currency_code": "JPY", "value": "1000"},
+                     "create_time": "2022-01-01T11:00:00Z"}
+                ],
+                "refunds": [
+                    {"id": "5", "status": "COMPLETED", "amount": {"currency_code": "EUR", "value": "0.10"}},
+                    {"id": "6", "status": "PENDING", "amount": {"currency_code": "EUR", "value": "3"}},
+                    {"id": "7", "status": "CANCELLED", "amount": {"currency_code": "JPY", "value": "1000"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let money = |currency_code: Currency, value: &str| Money {
+            currency_code,
+            value: value.to_owned(),
+        };
+
+        assert_eq!(
+            payments.total_captured().unwrap(),
+            vec![money(Currency::EUR, "10.30"), money(Currency::JPY, "1000")]
+        );
+        assert_eq!(payments.total_refunded().unwrap(), vec![money(Currency::EUR, "0.10")]);
+        assert_eq!(
+            payments.refundable_remaining().unwrap(),
+            vec![money(Currency::EUR, "7.20"), money(Currency::JPY, "1000")]
+        );
+        assert_eq!(payments.latest_capture().unwrap().id.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_request_deadline() {
+        // Connections are accepted by the kernel but never answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/slow", listener.local_addr().unwrap());
+
+        let client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        let start = Instant::now();
+        let result = client
+            .execute(client.client.get(&url).timeout(Duration::from_millis(300)))
+            .await;
+
+        match result {
+            Err(ResponseError::HttpError(e)) => assert!(e.is_timeout()),
+            other => panic!("expected a timeout: {:?}", other),
+        }
+        // The first retry would be sent after the deadline.
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    #[test]
+    fn test_root_certificates() {
+        const CORPORATE_CA: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIBlTCCATugAwIBAgIUOntFZBfFLxT5o7FzWc+8ObddKgcwCgYIKoZIzj0EAwIw\n\
+HzEdMBsGA1UEAwwURXhhbXBsZSBDb3Jwb3JhdGUgQ0EwIBcNMjYxMDE2MTYwOTMz\n\
+WhgPMjEyNjA5MjIxNjA5MzNaMB8xHTAbBgNVBAMMFEV4YW1wbGUgQ29ycG9yYXRl\n\
+IENBMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE43AXXIxfuFLjKzOw3jYqWZIK\n\
+F56cMvnALeHBuYVb3cM/JTmiDbE/WoCwUVo0n/C2V56jl+gnCXcObcqrwecCrqNT\n\
+MFEwHQYDVR0OBBYEFKqwA3h4kpkpwATvHna8yH1MudLGMB8GA1UdIwQYMBaAFKqw\n\
+A3h4kpkpwATvHna8yH1MudLGMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwID\n\
+SAAwRQIhAJ7tANoO0v537WHg6CU6hqk25txr+XeARk0qkVNAyCSEAiBmfLZRb949\n\
+oIpptS9mZQdP514qlZQrR4Q/Kog7fOW4bA==\n\
+-----END CERTIFICATE-----";
+
+        let builder = Client::builder().client_id("id").secret("secret");
+        assert!(builder
+            .clone()
+            .root_certificate(CORPORATE_CA)
+            .built_in_root_certificates(false)
+            .build()
+            .is_ok());
+        assert!(matches!(
+            builder.root_certificate("not a certificate").build(),
+            Err(ClientBuildError::InvalidRootCertificate)
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_compression() {
+        use std::io::{Read, Write};
+
+        // Answers with the Accept-Encoding header of the request.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/reporting/transactions", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let encoding = request
+                    .lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("accept-encoding: ").map(str::to_owned))
+                    .unwrap_or_default();
+                let body = format!(r#"{{"accept_encoding":"{}"}}"#, encoding);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        for (enabled, expected) in [(true, true), (false, false)] {
+            let client = Client::builder()
+                .client_id("id")
+                .secret("secret")
+                .compression(enabled)
+                .build()
+                .unwrap();
+            let res = client.execute(client.client.get(&url)).await.unwrap();
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["accept_encoding"].as_str().unwrap().contains("gzip"), expected);
+        }
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_reauth_on_unauthorized() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction, Interaction};
+
+        let order = |status: u16, response: &str| {
+            interaction("GET", "/v2/checkout/orders/5O190127TN364715T/", b"", status, response)
+        };
+        let interactions = [
+            token_interaction(),
+            order(
+                401,
+                r#"{"error":"invalid_token","error_description":"Token signature verification failed"}"#,
+            ),
+            Interaction {
+                body: token_interaction().body.replace("A21AA", "A21AB"),
+                ..token_interaction()
+            },
+            order(200, r#"{"id":"5O190127TN364715T","status":"APPROVED","links":[]}"#),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        let order = client.show_order_details("5O190127TN364715T").await.unwrap();
+        assert_eq!(order.status, OrderStatus::Approved);
+
+        // The new token is picked up from the token store, without fetching another one.
+        client.get_access_token().await.unwrap();
+        assert_eq!(client.auth.access_token.as_ref().unwrap().access_token, "A21AB");
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_reauth_deadline() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction, Interaction};
+
+        let order = |status: u16, response: &str| {
+            interaction("GET", "/v2/checkout/orders/5O190127TN364715T", b"", status, response)
+        };
+        let interactions = [
+            order(
+                401,
+                r#"{"error":"invalid_token","error_description":"Token signature verification failed"}"#,
+            ),
+            Interaction {
+                body: token_interaction().body.replace("A21AA", "A21AB"),
+                ..token_interaction()
+            },
+            order(200, r#"{"id":"5O190127TN364715T","status":"APPROVED","links":[]}"#),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        // The timeout elapsed during the first pass, so the request is not sent again with a new token.
+        let url = format!("{}/v2/checkout/orders/5O190127TN364715T", client.endpoint());
+        let builder = client
+            .client
+            .get(&url)
+            .bearer_auth("A21AA")
+            .timeout(Duration::from_nanos(1));
+        let res = client.execute(builder).await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert!(!client.token_rejected.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_deadline() {
+        use futures_util::future::BoxFuture;
+
+        /// A store that takes longer to answer than the deadline of the call.
+        #[derive(Debug)]
+        struct SlowTokenStore;
+
+        impl TokenStore for SlowTokenStore {
+            fn get<'a>(&'a self, _key: &'a str) -> BoxFuture<'a, Option<CachedToken>> {
+                Box::pin(async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    None
+                })
+            }
+
+            fn put<'a>(&'a self, _key: &'a str, _token: CachedToken) -> BoxFuture<'a, ()> {
+                Box::pin(async {})
+            }
+        }
+
+        let mut client = Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .token_store(SlowTokenStore)
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        let header_params = HeaderParams {
+            timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let builder = client.client.get(client.endpoint());
+        let request = client.setup_headers(builder, header_params).await.build().unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(request.headers().get(header::AUTHORIZATION).is_none());
+        assert!(request.timeout().unwrap() < &Duration::from_millis(50));
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_invalid_header_params() {
+        use crate::vcr::{replay_cassette, token_interaction};
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&[token_interaction()]));
+
+        // The request is never sent, the cassette has no capture to replay.
+        let header_params = HeaderParams {
+            request_id: Some("capture-1\nX-Injected: 1".to_owned()),
+            ..Default::default()
+        };
+        match client.capture_order("5O190127TN364715T", header_params).await {
+            Err(ResponseError::HttpError(e)) => assert!(e.is_builder()),
+            res => panic!("unexpected result: {:?}", res.map(|order| order.id)),
+        }
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_warm_up() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let interactions = vec![token_interaction(), interaction("HEAD", "/", b"", 404, "")];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        client.warm_up().await.unwrap();
+        assert_eq!(client.auth.access_token.as_ref().unwrap().access_token, "A21AA");
+        // The connection was opened with the recorded HEAD request.
+        let head = client.client.head(client.endpoint()).build().unwrap();
+        assert!(matches!(client.send_raw(head).await, Err(ResponseError::VcrError(_))));
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_order_fields() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let interactions = vec![
+            token_interaction(),
+            interaction(
+                "GET",
+                "/v2/checkout/orders/5O190127TN364715T?fields=payment_source",
+                b"",
+                200,
+                r#"{"id":"5O190127TN364715T","status":"COMPLETED","links":[],"payment_source":{"card":{
+                    "last_digits":"1111","brand":"VISA","type":"CREDIT",
+                    "attributes":{"vault":{"id":"8kk8451t","status":"VAULTED","customer":{"id":"customer_4029352050"}}}
+                }}}"#,
+            ),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        let order = client
+            .show_order_details_with_fields("5O190127TN364715T", &[OrderField::PaymentSource])
+            .await
+            .unwrap();
+        let payment_source = order.payment_source.unwrap();
+        assert_eq!(payment_source.vault_id(), Some("8kk8451t"));
+        let vault = payment_source.card.unwrap().attributes.unwrap().vault.unwrap();
+        assert_eq!(vault.status, Some(VaultStatus::Vaulted));
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_create_order_idempotent() {
+        use crate::idempotency::{MemoryOrderKeyStore, OrderKeyStore};
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let order = || {
+            OrderPayload::new(
+                Intent::Capture,
+                vec![PurchaseUnit::new(Amount::new(Currency::EUR, "10.00"))],
+            )
+        };
+        let order_body = r#"{"id":"5O190127TN364715T","status":"CREATED","links":[]}"#;
+        let interactions = vec![
+            token_interaction(),
+            interaction(
+                "POST",
+                "/v2/checkout/orders",
+                &serde_json::to_vec(&order()).unwrap(),
+                201,
+                order_body,
+            ),
+            interaction("GET", "/v2/checkout/orders/5O190127TN364715T/", b"", 200, order_body),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+        let store = MemoryOrderKeyStore::new();
+
+        let created = client
+            .create_order_idempotent(order(), "cart-1", &store, HeaderParams::default())
+            .await
+            .unwrap();
+        assert_eq!(store.get("cart-1").await.as_deref(), Some("5O190127TN364715T"));
+
+        // The cassette has no second creation, the order must be read back instead.
+        let again = client
+            .create_order_idempotent(order(), "cart-1", &store, HeaderParams::default())
+            .await
+            .unwrap();
+        assert_eq!(again.id, created.id);
+
+        let key = "k".repeat(109);
+        assert!(matches!(
+            client
+                .create_order_idempotent(order(), &key, &store, HeaderParams::default())
+                .await,
+            Err(ResponseError::InvalidIdempotencyKey(_))
+        ));
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let interactions = vec![
+            token_interaction(),
+            interaction(
+                "GET",
+                "/v2/checkout/orders/5O190127TN364715T/",
+                b"",
+                503,
+                r#"{"name":"SERVICE_UNAVAILABLE","message":"Service Unavailable.","details":[]}"#,
+            ),
+        ];
+
+        let breaker = Arc::new(CircuitBreaker::new(1, Duration::from_secs(60)));
+        let mut client = Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .retry_policy(crate::retry::NoRetry)
+            .circuit_breaker(breaker.clone())
+            .cassette(replay_cassette(&interactions))
+            .build()
+            .unwrap();
+
+        let err = client.show_order_details("5O190127TN364715T").await.unwrap_err();
+        assert_eq!(err.status(), Some(503));
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // The cassette has no more interactions, the request must not be sent.
+        let err = client.show_order_details("5O190127TN364715T").await.unwrap_err();
+        assert!(matches!(err, ResponseError::CircuitOpen));
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_wait_for_order_status() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let poll = |status: &str| {
+            interaction(
+                "GET",
+                "/v2/checkout/orders/5O190127TN364715T/",
+                b"",
+                200,
+                &format!(r#"{{"id":"5O190127TN364715T","status":"{}","links":[]}}"#, status),
+            )
+        };
+        let interactions = vec![
+            token_interaction(),
+            poll("PAYER_ACTION_REQUIRED"),
+            poll("PAYER_ACTION_REQUIRED"),
+            poll("APPROVED"),
+            poll("PAYER_ACTION_REQUIRED"),
+            poll("VOIDED"),
+            poll("PAYER_ACTION_REQUIRED"),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+        let config = PollConfig {
+            initial_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(10),
+            timeout: Duration::from_secs(5),
+        };
+
+        let order = client
+            .wait_for_order_status("5O190127TN364715T", OrderStatus::Approved, config.clone())
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Approved);
+
+        // A terminal status ends the polling early.
+        let order = client
+            .wait_for_order_status("5O190127TN364715T", OrderStatus::Approved, config.clone())
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Voided);
+
+        let order = client
+            .wait_for_order_status(
+                "5O190127TN364715T",
+                OrderStatus::Approved,
+                PollConfig {
+                    timeout: Duration::ZERO,
+                    ..config
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::PayerActionRequired);
+    }
+
+    #[tokio::test]
+    async fn test_follow_link() {
+        use crate::common::{LinkDescription, LinkRel};
+
+        let links: Vec<LinkDescription> = serde_json::from_str(
+            r#"[
+                {"href":"https://api-m.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T","rel":"self","method":"GET"},
+                {"href":"https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T","rel":"approve","method":"GET"},
+                {"href":"https://api-m.sandbox.paypal.com/v1/notifications/webhooks","rel":"webhooks","method":"GET"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(links[0].rel, Some(LinkRel::SelfLink));
+        assert_eq!(links[2].rel, Some(LinkRel::Other("webhooks".to_owned())));
+
+        let approve = LinkDescription::find(&links, LinkRel::Approve).unwrap();
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        assert!(matches!(
+            client.follow_link::<Order>(approve).await,
+            Err(ResponseError::InvalidLink(_))
+        ));
+    }
+
+    #[test]
+    fn test_webhook_time() {
+        use crate::webhooks::Webhook;
+
+        let webhook: Webhook<serde_json::Value> = serde_json::from_str(
+            r#"{
+                "id": "WH-2WR32451HC0233532-67976317FL4543714",
+                "create_time": "2014-10-23T17:23:52.794Z",
+                "event_type": "PAYMENT.CAPTURE.COMPLETED",
+                "resource_type": "capture",
+                "resource_version": "2.0",
+                "summary": "Payment completed",
+                "resource": {},
+                "event_version": "1.0",
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            webhook.create_time,
+            chrono::DateTime::parse_from_rfc3339("2014-10-23T17:23:52.794Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_phone_number() {
+        use crate::common::PhoneType;
+        use crate::invoice::PhoneDetail;
+
+        assert_eq!(
+            PhoneNumber::parse("(408) 555-0123").unwrap().national_number,
+            "4085550123"
+        );
+        assert_eq!(
+            PhoneNumber::parse("+44 20 7946 0958").unwrap().national_number,
+            "2079460958"
+        );
+        assert!(PhoneNumber::parse("").is_err());
+        assert!(PhoneNumber::parse("408 555 O123").is_err());
+        assert!(PhoneNumber::parse("1234567890123456").is_err());
+
+        let (country_code, number) = PhoneNumber::parse_international("+1 408-555-0123").unwrap();
+        assert_eq!(country_code, "1");
+        assert_eq!(number.national_number, "4085550123");
+        assert!(PhoneNumber::parse_international("4085550123").is_err());
+
+        let phone = PhoneDetail::parse(Some(PhoneType::Mobile), "+353 87 123 4567").unwrap();
+        assert_eq!(phone.phone.country_code, "353");
+        assert_eq!(phone.phone.national_number, "871234567");
+        let json = serde_json::to_value(&phone).unwrap();
+        assert_eq!(json["country_code"], "353");
+        assert_eq!(json["phone_type"], "MOBILE");
+    }
+
+    #[test]
+    fn test_shipping_options() {
+        let money = |value: &str| Money {
+            currency_code: Currency::USD,
+            value: value.to_owned(),
+        };
+        let mut shipping = ShippingDetail {
+            options: Some(vec![
+                ShippingOption::new("standard", "Standard", ShippingType::Shipping, money("5.00")),
+                ShippingOption::new(
+                    String::from("pickup"),
+                    "Pick up in store",
+                    ShippingType::PickupInStore,
+                    money("0.00"),
+                ),
+            ]),
+            ..Default::default()
+        };
+        assert!(shipping.select_option("pickup"));
+        assert!(!shipping.select_option("express"));
+
+        let options = shipping.options.as_ref().unwrap();
+        assert!(!options[0].selected && options[1].selected);
+
+        let patch = serde_json::to_value(Patch::shipping_options("default", options)).unwrap();
+        assert_eq!(patch["op"], "replace");
+        assert_eq!(
+            patch["path"],
+            "/purchase_units/@reference_id=='default'/shipping/options"
+        );
+        assert_eq!(patch["value"][1]["type"], "PICKUP_IN_STORE");
+        assert_eq!(patch["value"][1]["selected"], true);
+    }
+
+    #[test]
+    fn test_order_patches() {
+        let shipping = ShippingDetail {
+            name: Some("John Doe".into()),
+            type_: Some(ShippingType::Shipping),
+            ..Default::default()
+        };
+        assert_eq!(shipping.full_name(), Some("John Doe"));
+        let patches = serde_json::to_value(Patch::shipping("default", &shipping)).unwrap();
+        assert_eq!(patches.as_array().unwrap().len(), 2);
+        assert_eq!(
+            patches[0]["path"],
+            "/purchase_units/@reference_id=='default'/shipping/name"
+        );
+        assert_eq!(patches[0]["value"]["full_name"], "John Doe");
+        assert_eq!(
+            patches[1]["path"],
+            "/purchase_units/@reference_id=='default'/shipping/type"
+        );
+
+        let patch = serde_json::to_value(Patch::amount("default", &Amount::new(Currency::USD, "12.50"))).unwrap();
+        assert_eq!(patch["op"], "replace");
+        assert_eq!(patch["path"], "/purchase_units/@reference_id=='default'/amount");
+        assert_eq!(patch["value"]["value"], "12.50");
+
+        let patch = serde_json::to_value(Patch::invoice_id("default", "INV-1234")).unwrap();
+        assert_eq!(patch["op"], "add");
+        assert_eq!(patch["path"], "/purchase_units/@reference_id=='default'/invoice_id");
+        assert_eq!(patch["value"], "INV-1234");
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_update_order() {
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let patches = br#"[{"op":"replace","path":"/intent","value":"CAPTURE"},{"op":"replace","path":"/purchase_units/@reference_id=='default'","value":{"amount":{"currency_code":"USD","value":"10.00"},"reference_id":"default"}},{"op":"replace","path":"/purchase_units/@reference_id=='shipping'","value":{"amount":{"currency_code":"USD","value":"5.00"},"reference_id":"shipping"}}]"#;
+        let interactions = vec![
+            token_interaction(),
+            interaction("PATCH", "/v2/checkout/orders/5O190127TN364715T", patches, 204, ""),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+        client.get_access_token().await.unwrap();
+
+        // Every purchase unit is sent, the replay fails if the body doesn't match.
+        let unit = |reference_id: &str, value: &str| PurchaseUnit {
+            reference_id: Some(reference_id.to_owned()),
+            ..PurchaseUnit::new(Amount::new(Currency::USD, value))
+        };
+        client
+            .update_order(
+                "5O190127TN364715T",
+                Some(Intent::Capture),
+                Some(vec![unit("default", "10.00"), unit("shipping", "5.00")]),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_capture_payment_instruction() {
+        use crate::capture::AuthorizationCapturePayload;
+
+        let instruction = || PaymentInstruction {
+            platform_fees: Some(vec![PlatformFee {
+                amount: Money {
+                    currency_code: Currency::USD,
+                    value: "1.50".to_owned(),
+                },
+                payee: None,
+            }]),
+            disbursement_mode: Some(DisbursementMode::Delayed),
+        };
+
+        let payload = AuthorizationCapturePayload {
+            final_capture: Some(true),
+            payment_instruction: Some(instruction()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["final_capture"], true);
+        assert_eq!(json["payment_instruction"]["disbursement_mode"], "DELAYED");
+        assert_eq!(
+            json["payment_instruction"]["platform_fees"][0]["amount"]["value"],
+            "1.50"
+        );
+        assert!(json.get("amount").is_none());
+
+        let payload = OrderCapturePayload {
+            payment_instruction: Some(instruction()),
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            json["payment_instruction"]["platform_fees"][0]["amount"]["currency_code"],
+            "USD"
+        );
+    }
+
+    #[test]
+    fn test_refund_breakdown() {
+        use crate::capture::{RefundPayload, RefundPaymentInstruction};
+
+        let payload = RefundPayload {
+            amount: Some(Money {
+                currency_code: Currency::USD,
+                value: "10.00".to_owned(),
+            }),
+            payment_instruction: Some(RefundPaymentInstruction {
+                platform_fees: Some(vec![PlatformFee {
+                    amount: Money {
+                        currency_code: Currency::USD,
+                        value: "1.00".to_owned(),
+                    },
+                    payee: None,
+                }]),
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            json["payment_instruction"]["platform_fees"][0]["amount"]["value"],
+            "1.00"
+        );
+
+        let refund: Refund = serde_json::from_str(
+            r#"{
+                "id": "1JU08902781691411",
+                "status": "COMPLETED",
+                "amount": {"currency_code": "USD", "value": "10.00"},
+                "seller_payable_breakdown": {
+                    "gross_amount": {"currency_code": "USD", "value": "10.00"},
+                    "paypal_fee": {"currency_code": "USD", "value": "0.29"},
+                    "platform_fees": [{"amount": {"currency_code": "USD", "value": "1.00"}}],
+                    "net_amount": {"currency_code": "USD", "value": "8.71"},
+                    "total_refunded_amount": {"currency_code": "USD", "value": "15.00"}
+                }
+            }"#,
+        )
+        .unwrap();
+        let breakdown = refund.seller_payable_breakdown.unwrap();
+        assert_eq!(breakdown.platform_fees[0].amount.value, "1.00");
+        assert_eq!(breakdown.net_amount.unwrap().value, "8.71");
+        assert_eq!(breakdown.total_refunded_amount.unwrap().value, "15.00");
+    }
+
+    #[test]
+    fn test_stored_credential() {
+        let mut order = OrderPayload::new(Intent::Capture, vec![]);
+        order.payment_source = Some(PaymentSource {
+            card: Some(Card {
+                vault_id: Some("8kk8451t".to_owned()),
+                stored_credential: Some(StoredCredential {
+                    payment_initiator: PaymentInitiator::Merchant,
+                    payment_type: StoredPaymentType::Unscheduled,
+                    usage: Some(StoredCredentialUsage::Subsequent),
+                    previous_network_transaction_reference: Some(NetworkTransactionReference {
+                        id: "156GHJ654SFH543".to_owned(),
+                        date: None,
+                        network: Some(CardBrand::Visa),
+                        acquirer_reference_number: None,
+                    }),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        order.processing_instruction = Some(ProcessingInstruction::OrderCompleteOnPaymentApproval);
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["processing_instruction"], "ORDER_COMPLETE_ON_PAYMENT_APPROVAL");
+        let stored = &json["payment_source"]["card"]["stored_credential"];
+        assert_eq!(stored["payment_initiator"], "MERCHANT");
+        assert_eq!(stored["payment_type"], "UNSCHEDULED");
+        assert_eq!(stored["previous_network_transaction_reference"]["network"], "VISA");
+        assert!(stored["previous_network_transaction_reference"].get("date").is_none());
+
+        let card = Card {
+            number: Some("4111111111111111".to_owned()),
+            expiry: Some("2027-02".to_owned()),
+            security_code: Some("123".to_owned()),
+            ..Default::default()
+        };
+        let debug = format!("{:?}", card);
+        assert!(!debug.contains("4111111111111111") && !debug.contains("123"));
+        assert!(debug.contains(r#"number: Some("REDACTED")"#) && debug.contains("2027-02"));
+    }
+
+    #[test]
+    fn test_item_fields() {
+        let money = Money {
+            currency_code: Currency::USD,
+            value: "25.00".to_owned(),
+        };
+        let item = Item::new("Donation", money, 1)
+            .category(ItemCategoryType::Donation)
+            .url("https://example.com/donate")
+            .upc(UpcType::UpcA, "123456789012");
+
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["category"], "DONATION");
+        assert_eq!(json["upc"]["type"], "UPC-A");
+        assert_eq!(json["upc"]["code"], "123456789012");
+        assert!(json.get("image_url").is_none());
+    }
+
+    #[test]
+    fn test_processor_response() {
+        let capture: Capture = serde_json::from_str(
+            r#"{
+                "status": "DECLINED",
+                "processor_response": {
+                    "avs_code": "Y",
+                    "cvv_code": "N",
+                    "response_code": "5120",
+                    "payment_advice_code": "21"
+                },
+                "network_transaction_reference": {
+                    "id": "156GHJ654SFH543",
+                    "network": "VISA",
+                    "date": "0429"
+                }
+            }"#,
+        )
+        .unwrap();
+        let reference = capture.network_transaction_reference.unwrap();
+        assert_eq!(reference.id, "156GHJ654SFH543");
+        assert_eq!(reference.network, Some(CardBrand::Visa));
+        let response = capture.processor_response.unwrap();
+        assert_eq!(response.avs_code.as_deref(), Some("Y"));
+        assert!(!response.is_approved());
+        assert!(response.should_not_retry());
+    }
+
+    #[test]
+    fn test_seller_protection() {
+        use crate::capture::{DisputeCategory, SellerProtection, SellerProtectionStatus};
+
+        let protection: SellerProtection = serde_json::from_str(
+            r#"{"status":"ELIGIBLE","dispute_categories":["ITEM_NOT_RECEIVED","UNAUTHORIZED_TRANSACTION"]}"#,
+        )
+        .unwrap();
+        assert_eq!(protection.status, SellerProtectionStatus::Eligible);
+        assert_eq!(
+            protection.dispute_categories,
+            vec![
+                DisputeCategory::ItemNotReceived,
+                DisputeCategory::UnauthorizedTransaction
+            ]
+        );
+
+        let legacy: SellerProtection = serde_json::from_str(r#"{"status":"ELIGABLE"}"#).unwrap();
+        assert_eq!(legacy.status, SellerProtectionStatus::Eligible);
+    }
+
+    #[test]
+    fn test_pay_upon_invoice() {
+        let order: Order = serde_json::from_str(
+            r#"{
+                "id": "5O190127TN364715T",
+                "status": "PENDING_APPROVAL",
+                "payment_source": {
+                    "pay_upon_invoice": {
+                        "birth_date": "1990-01-01",
+                        "name": {"given_name": "John", "surname": "Doe"},
+                        "email": "buyer@example.com",
+                        "phone": {"national_number": "6912345678", "country_code": "49"},
+                        "payment_reference": "b8a1525dlYzu6Mn62umI",
+                        "deposit_bank_details": {
+                            "bic": "DEUTDEFFXXX",
+                            "bank_name": "Deutsche Bank",
+                            "iban": "DE89370400440532013000",
+                            "account_holder_name": "Paypal - Ratepay GmbH - Test Bank Account"
+                        }
+                    }
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(order.status, OrderStatus::PendingApproval);
+        let pui = order.payment_source.unwrap().pay_upon_invoice.unwrap();
+        assert_eq!(pui.payment_reference.as_deref(), Some("b8a1525dlYzu6Mn62umI"));
+        assert_eq!(pui.deposit_bank_details.unwrap().iban, "DE89370400440532013000");
+        assert_eq!(
+            PhoneWithCountryCode::parse("+49 69 12345678").unwrap(),
+            PhoneWithCountryCode {
+                country_code: "49".to_owned(),
+                national_number: "6912345678".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_polish_payment_sources() {
+        let mut blik = Blik::new("John Doe");
+        blik.level_0 = Some(BlikLevel0 {
+            auth_code: "777123".to_owned(),
+        });
+        let source = PaymentSource {
+            blik: Some(blik),
+            p24: Some(P24::new("John Doe".to_owned(), "buyer@example.com")),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&source).unwrap();
+        assert_eq!(json["blik"]["country_code"], "PL");
+        assert_eq!(json["blik"]["level_0"]["auth_code"], "777123");
+        assert!(json["blik"].get("one_click").is_none());
+        assert_eq!(json["p24"]["email"], "buyer@example.com");
+
+        let response: PaymentSourceResponse = serde_json::from_str(
+            r#"{
+                "p24": {
+                    "name": "John Doe",
+                    "email": "buyer@example.com",
+                    "country_code": "PL",
+                    "payment_descriptor": "PayPal P24 payment",
+                    "method_id": "227",
+                    "method_description": "Santander"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(response.p24.unwrap().method_description.as_deref(), Some("Santander"));
+    }
+
+    #[test]
+    fn test_bank_payment_sources() {
+        let mut trustly = BankPaymentSource::new("John Doe", Country::SE);
+        trustly.email = Some("buyer@example.com".to_owned());
+        let source = PaymentSource {
+            bancontact: Some(BankPaymentSource::bancontact("John Doe")),
+            trustly: Some(trustly),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&source).unwrap();
+        assert_eq!(json["bancontact"]["country_code"], "BE");
+        assert!(json["bancontact"].get("email").is_none());
+        assert_eq!(json["trustly"]["country_code"], "SE");
+
+        let response: PaymentSourceResponse =
+            serde_json::from_str(r#"{"eps":{"name":"John Doe","country_code":"AT","bic":"BAWAATWW"}}"#).unwrap();
+        assert_eq!(response.eps.unwrap().bic.as_deref(), Some("BAWAATWW"));
+    }
+
+    #[test]
+    fn test_experience_context() {
+        let context = ApplicationContext {
+            brand_name: Some("EXAMPLE INC".to_owned()),
+            user_action: Some(UserAction::PayNow),
+            return_url: Some("https://example.com/returnUrl".to_owned()),
+            ..Default::default()
+        };
+        let source = PaymentSource {
+            paypal: Some(PaypalWallet {
+                experience_context: Some(context.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&source).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "paypal": {
+                    "experience_context": {
+                        "brand_name": "EXAMPLE INC",
+                        "user_action": "PAY_NOW",
+                        "return_url": "https://example.com/returnUrl"
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_enum_values() {
+        let status: OrderStatus = serde_json::from_str(r#""SOMETHING_NEW""#).unwrap();
+        assert_eq!(status, OrderStatus::Unknown("SOMETHING_NEW".to_owned()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""SOMETHING_NEW""#);
+        assert_eq!(OrderStatus::from_str("COMPLETED").unwrap(), OrderStatus::Completed);
+        assert_eq!(OrderStatus::PayerActionRequired.to_string(), "PAYER_ACTION_REQUIRED");
+
+        let card_type: CardType = serde_json::from_str(r#""VIRTUAL""#).unwrap();
+        assert_eq!(card_type, CardType::Unrecognized("VIRTUAL".to_owned()));
+        assert_eq!(
+            serde_json::from_str::<CardType>(r#""UNKNOWN""#).unwrap(),
+            CardType::Unknown
+        );
+    }
+
+    #[test]
+    fn test_enum_strings() {
+        assert_eq!(Intent::Capture.to_string(), "CAPTURE");
+        assert_eq!(Intent::from_str("AUTHORIZE").unwrap(), Intent::Authorize);
+        assert!(Intent::from_str("authorize").is_err());
+        assert_eq!(DisbursementMode::Instant.to_string(), "INSTANT");
+        assert_eq!(CaptureStatus::from_str("COMPLETED").unwrap(), CaptureStatus::Completed);
+        assert_eq!(UpcType::UpcA.to_string(), "UPC-A");
+        // rename_all turned BR_CPF into B_R__C_P_F.
+        assert_eq!(serde_json::to_string(&TaxIdType::BR_CPF).unwrap(), "\"BR_CPF\"");
+        assert_eq!(TaxIdType::from_str("BR_CNPJ").unwrap(), TaxIdType::BR_CNPJ);
+        assert_eq!(
+            crate::common::PatchOp::from_str("replace").unwrap(),
+            crate::common::PatchOp::Replace
+        );
+
+        for intent in [Intent::Capture, Intent::Authorize] {
+            assert_eq!(Intent::from_str(&intent.to_string()).unwrap(), intent);
+        }
+
+        // The string tables agree with the serde names.
+        for upc in [UpcType::UpcA, UpcType::Upc2] {
+            assert_eq!(serde_json::to_value(upc).unwrap(), upc.as_str());
+        }
+        for preference in [ShippingPreference::GetFromFile, ShippingPreference::SetProvidedAddress] {
+            assert_eq!(serde_json::to_value(preference).unwrap(), preference.as_str());
+        }
+        assert_eq!(serde_json::to_value(StoredPaymentType::OneTime).unwrap(), "ONE_TIME");
     }
 
     #[test]
@@ -379,5 +2465,10 @@ mod tests {
         assert_eq!(Country::US.to_string(), "US");
         assert_eq!(Country::ES.to_string(), "ES");
         assert_eq!(Country::ES, Country::from_str("ES").unwrap());
+        assert_eq!(Country::from_str("XK").unwrap(), Country::Other("XK".to_owned()));
+        assert!(Country::from_str("Spain").is_err());
+
+        let address: crate::common::Address = serde_json::from_str(r#"{"country_code":"ES"}"#).unwrap();
+        assert_eq!(address.country_code, Country::ES);
     }
 }
@@ -1,14 +1,178 @@
 //! Common paypal object definitions used by 2 or more APIs
 
-use crate::errors::InvalidCurrencyError;
+use crate::countries::Country;
+#[cfg(any(feature = "orders", feature = "invoicing"))]
+use crate::errors::InvalidPhoneNumberError;
+use crate::errors::{InvalidAmountError, InvalidCurrencyError, MoneyError};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::str::FromStr;
+
+/// Defines an enum of the string values paypal sends, with a fallback variant holding any value this crate doesn't know yet,
+/// so new values added by paypal don't break deserialization. The fallback is named `Unknown` unless another name is given,
+/// as in `enum CardType(Unrecognized) { ... }`.
+///
+/// The enum is serialized as its string value, and implements `as_str`, `Display`, `FromStr` and the conversions from and
+/// to `String`. Responses decoded with [DecodeMode::Strict](crate::errors::DecodeMode::Strict) fail on the fallback instead.
+///
+/// `FromStr` accepts any value unless a validation function and error are given, as in
+/// `enum Currency(Other, is_currency_code => InvalidCurrencyError) { ... }`, then only the fallback values it accepts parse.
+#[allow(unused_macros)]
+macro_rules! string_enum {
+    (@from_str $name:ident, $fallback:ident) => {
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self::from(s.to_owned()))
+            }
+        }
+    };
+    (@from_str $name:ident, $fallback:ident, $valid:path => $err:path) => {
+        impl std::str::FromStr for $name {
+            type Err = $err;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match Self::from(s.to_owned()) {
+                    $name::$fallback(value) if !$valid(&value) => Err($err(value)),
+                    value => Ok(value),
+                }
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        string_enum! {
+            $(#[$meta])*
+            $vis enum $name(Unknown) {
+                $($body)*
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident($fallback:ident $(, $valid:path => $err:path)?) {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal $(| $alias:literal)*,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+        #[non_exhaustive]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            /// A value this version of the crate doesn't know about.
+            $fallback(String),
+        }
+
+        impl $name {
+            /// The value as sent by paypal.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::$fallback(value) => value,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $($value $(| $alias)* => $name::$variant,)*
+                    _ => $name::$fallback(value),
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::$fallback(value) => value,
+                    value => value.as_str().to_owned(),
+                }
+            }
+        }
+
+        string_enum!(@from_str $name, $fallback $(, $valid => $err)?);
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = Self::from(String::deserialize(deserializer)?);
+                match &value {
+                    $name::$fallback(unknown) if crate::errors::is_strict() => {
+                        Err(serde::de::Error::unknown_variant(unknown, &[$($value),*]))
+                    }
+                    _ => Ok(value),
+                }
+            }
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use string_enum;
+
+/// Implements `as_str`, `Display` and `FromStr` for enums without a fallback variant, from the table of the
+/// string values they are serialized to.
+macro_rules! display_from_str {
+    ($name:ident { $($variant:ident = $value:literal),* $(,)? }) => {
+        impl $name {
+            /// The value as sent to or by paypal.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value,)*
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = crate::errors::InvalidVariantError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($value => Ok($name::$variant),)*
+                    _ => Err(crate::errors::InvalidVariantError(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use display_from_str;
 
 /// The phone type.
 ///
 /// https://developer.paypal.com/docs/api/orders/v2/#definition-phone_with_type
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(missing_docs)]
 pub enum PhoneType {
@@ -19,9 +183,174 @@ pub enum PhoneType {
     Pager,
 }
 
+display_from_str!(PhoneType {
+    Fax = "FAX",
+    Home = "HOME",
+    Mobile = "MOBILE",
+    Other = "OTHER",
+    Pager = "PAGER",
+});
+
+/// The kind of a json patch operation.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchOp {
+    /// Adds a value.
+    Add,
+    /// Removes a value.
+    Remove,
+    /// Replaces a value.
+    Replace,
+    /// Moves a value to another path.
+    Move,
+    /// Copies a value to another path.
+    Copy,
+    /// Tests that a value is equal to the given one.
+    Test,
+}
+
+display_from_str!(PatchOp {
+    Add = "add",
+    Remove = "remove",
+    Replace = "replace",
+    Move = "move",
+    Copy = "copy",
+    Test = "test",
+});
+
+/// A json patch operation, used to update resources.
+///
+/// https://datatracker.ietf.org/doc/html/rfc6902
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Patch {
+    /// The operation.
+    pub op: PatchOp,
+    /// The path of the value the operation applies to.
+    pub path: String,
+    /// The value to add, replace or test.
+    pub value: Option<serde_json::Value>,
+    /// The path to move or copy the value from.
+    pub from: Option<String>,
+}
+
+impl Patch {
+    /// Adds the value at the given path.
+    pub fn add<S: Into<String>>(path: S, value: serde_json::Value) -> Self {
+        Patch {
+            op: PatchOp::Add,
+            path: path.into(),
+            value: Some(value),
+            from: None,
+        }
+    }
+
+    /// Replaces the value at the given path.
+    pub fn replace<S: Into<String>>(path: S, value: serde_json::Value) -> Self {
+        Patch {
+            op: PatchOp::Replace,
+            path: path.into(),
+            value: Some(value),
+            from: None,
+        }
+    }
+
+    /// Removes the value at the given path.
+    pub fn remove<S: Into<String>>(path: S) -> Self {
+        Patch {
+            op: PatchOp::Remove,
+            path: path.into(),
+            value: None,
+            from: None,
+        }
+    }
+}
+
+/// The maximum number of digits in an E.164 phone number, country calling code included.
+pub const E164_MAX_DIGITS: usize = 15;
+
+/// Removes the separators people commonly write phone numbers with, returning whether it
+/// started with a `+` and its digits.
+#[cfg(any(feature = "orders", feature = "invoicing"))]
+pub(crate) fn normalize_phone_number(number: &str) -> Result<(bool, String), InvalidPhoneNumberError> {
+    let trimmed = number.trim();
+    let (international, rest) = match trimmed.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+
+    let mut digits = String::with_capacity(rest.len());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => return Err(InvalidPhoneNumberError(number.to_owned())),
+        }
+    }
+
+    if digits.is_empty() || digits.len() > E164_MAX_DIGITS {
+        return Err(InvalidPhoneNumberError(number.to_owned()));
+    }
+    Ok((international, digits))
+}
+
+/// Splits the country calling code off an international number given without its `+`.
+///
+/// Calling codes are prefix free, so the length of the code can be told from its first digits.
+#[cfg(any(feature = "orders", feature = "invoicing"))]
+pub(crate) fn split_calling_code(digits: &str) -> Option<(&str, &str)> {
+    const TWO_DIGITS: &[&str] = &[
+        "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46", "47", "48", "49",
+        "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63", "64", "65", "66", "81", "82", "84",
+        "86", "90", "91", "92", "93", "94", "95", "98",
+    ];
+    const THREE_DIGITS_PREFIXES: &[&str] = &[
+        "21", "22", "23", "24", "25", "26", "29", "35", "37", "38", "42", "50", "59", "67", "68", "69", "80", "85",
+        "87", "88", "96", "97", "99",
+    ];
+
+    let len = match digits.get(..2)? {
+        prefix if prefix.starts_with('1') || prefix.starts_with('7') => 1,
+        prefix if TWO_DIGITS.contains(&prefix) => 2,
+        prefix if THREE_DIGITS_PREFIXES.contains(&prefix) => 3,
+        _ => return None,
+    };
+
+    if digits.len() <= len {
+        return None;
+    }
+    Some(digits.split_at(len))
+}
+
+/// A phone number with its country calling code.
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PhoneWithCountryCode {
+    /// The country calling code (CC), in its canonical international E.164 numbering plan format.
+    pub country_code: String,
+    /// The national number, in its canonical international E.164 numbering plan format.
+    pub national_number: String,
+}
+
+#[cfg(any(feature = "orders", feature = "invoicing"))]
+impl PhoneWithCountryCode {
+    /// Parses an international phone number starting with a `+`, splitting off its country calling code.
+    ///
+    /// Spaces, dashes, dots and parentheses are ignored.
+    pub fn parse(number: &str) -> Result<Self, InvalidPhoneNumberError> {
+        let (international, digits) = normalize_phone_number(number)?;
+        let (country_code, national_number) = split_calling_code(&digits)
+            .filter(|_| international)
+            .ok_or_else(|| InvalidPhoneNumberError(number.to_owned()))?;
+        Ok(PhoneWithCountryCode {
+            country_code: country_code.to_owned(),
+            national_number: national_number.to_owned(),
+        })
+    }
+}
+
 /// The non-portable additional address details
 #[skip_serializing_none]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct AddressDetails {
     /// The street number.
     pub street_number: Option<String>,
@@ -40,7 +369,7 @@ pub struct AddressDetails {
 
 /// The address of the payer.
 #[skip_serializing_none]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Address {
     /// The first line of the address. For example, number or street. For example, 173 Drury Lane.
     /// Required for data entry and compliance and risk checks. Must contain the full address.
@@ -55,7 +384,7 @@ pub struct Address {
     /// The postal code, which is the zip code or equivalent. Typically required for countries with a postal code or an equivalent.
     pub postal_code: Option<String>,
     /// The two-character [ISO 3166-1](https://developer.paypal.com/docs/api/reference/country-codes/) code that identifies the country or region.
-    pub country_code: String,
+    pub country_code: Country,
     /// The non-portable additional address details that are sometimes needed for compliance, risk, or other scenarios where fine-grain address information might be needed.
     pub address_details: Option<AddressDetails>,
 }
@@ -73,6 +402,162 @@ pub struct Money {
     pub value: String,
 }
 
+impl Money {
+    /// No money in the given currency, formatted with its decimal places.
+    pub fn zero(currency_code: Currency) -> Money {
+        Money {
+            value: from_minor_units(&currency_code, 0),
+            currency_code,
+        }
+    }
+
+    /// Compares with money in the same currency, whatever the number of trailing zeros of the values.
+    pub fn checked_cmp(&self, other: &Money) -> Result<std::cmp::Ordering, MoneyError> {
+        if self.currency_code != other.currency_code {
+            return Err(MoneyError::CurrencyMismatch(
+                self.currency_code.clone(),
+                other.currency_code.clone(),
+            ));
+        }
+        let value = to_minor_units(&self.currency_code, &self.value)?;
+        Ok(value.cmp(&to_minor_units(&other.currency_code, &other.value)?))
+    }
+
+    /// Adds money in the same currency.
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        Ok(Money {
+            value: combine_values(
+                (&self.currency_code, &self.value),
+                (&other.currency_code, &other.value),
+                i128::checked_add,
+            )?,
+            currency_code: self.currency_code.clone(),
+        })
+    }
+
+    /// Subtracts money in the same currency, the result can be negative.
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        Ok(Money {
+            value: combine_values(
+                (&self.currency_code, &self.value),
+                (&other.currency_code, &other.value),
+                i128::checked_sub,
+            )?,
+            currency_code: self.currency_code.clone(),
+        })
+    }
+
+    /// Multiplies money by a quantity, like the unit amount of an item.
+    pub fn mul_quantity(&self, quantity: u32) -> Result<Money, MoneyError> {
+        Ok(Money {
+            value: multiply_value(&self.currency_code, &self.value, quantity)?,
+            currency_code: self.currency_code.clone(),
+        })
+    }
+
+    /// Creates money from a decimal, formatted with the number of decimal places the currency uses.
+    ///
+    /// Fails if the value has more significant decimal places than the currency supports.
+    #[cfg(feature = "decimal")]
+    pub fn from_decimal(currency_code: Currency, value: Decimal) -> Result<Self, InvalidAmountError> {
+        Ok(Money {
+            value: format_decimal(&currency_code, value)?,
+            currency_code,
+        })
+    }
+
+    /// The value as a decimal.
+    #[cfg(feature = "decimal")]
+    pub fn decimal(&self) -> Result<Decimal, InvalidAmountError> {
+        parse_decimal(&self.value)
+    }
+}
+
+/// Converts a value to a whole number of the smallest unit of its currency, like cents.
+///
+/// Trailing zeros past the decimal places of the currency are accepted, other digits aren't.
+fn to_minor_units(currency: &Currency, value: &str) -> Result<i128, InvalidAmountError> {
+    let invalid = || InvalidAmountError(value.to_owned());
+    let places = currency.decimal_places() as usize;
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((_, "")) => return Err(invalid()),
+        Some((integer, fraction)) => (integer, fraction),
+        None => (digits, ""),
+    };
+    if integer.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    if fraction.len() > places && fraction[places..].chars().any(|c| c != '0') {
+        return Err(invalid());
+    }
+
+    let fraction = &fraction[..fraction.len().min(places)];
+    let units: i128 = format!("{}{:0<width$}", integer, fraction, width = places)
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok(if negative { -units } else { units })
+}
+
+/// Formats a number of the smallest unit of a currency with exactly the decimal places of the currency.
+fn from_minor_units(currency: &Currency, units: i128) -> String {
+    let places = currency.decimal_places();
+    let sign = if units < 0 { "-" } else { "" };
+    let units = units.unsigned_abs();
+    if places == 0 {
+        return format!("{}{}", sign, units);
+    }
+    let scale = 10u128.pow(places);
+    format!(
+        "{}{}.{:0width$}",
+        sign,
+        units / scale,
+        units % scale,
+        width = places as usize
+    )
+}
+
+/// Combines two values in the same currency with an operation on their smallest units.
+fn combine_values(
+    (currency, value): (&Currency, &str),
+    (other_currency, other_value): (&Currency, &str),
+    op: fn(i128, i128) -> Option<i128>,
+) -> Result<String, MoneyError> {
+    if currency != other_currency {
+        return Err(MoneyError::CurrencyMismatch(currency.clone(), other_currency.clone()));
+    }
+    let (a, b) = (to_minor_units(currency, value)?, to_minor_units(currency, other_value)?);
+    let units = op(a, b).ok_or(MoneyError::Overflow)?;
+    Ok(from_minor_units(currency, units))
+}
+
+/// Multiplies a value by a quantity.
+fn multiply_value(currency: &Currency, value: &str, quantity: u32) -> Result<String, MoneyError> {
+    let units = to_minor_units(currency, value)?
+        .checked_mul(i128::from(quantity))
+        .ok_or(MoneyError::Overflow)?;
+    Ok(from_minor_units(currency, units))
+}
+
+/// Formats a decimal with exactly the number of decimal places of the currency.
+#[cfg(feature = "decimal")]
+pub(crate) fn format_decimal(currency: &Currency, value: Decimal) -> Result<String, InvalidAmountError> {
+    let places = currency.decimal_places();
+    if value.round_dp(places) != value {
+        return Err(InvalidAmountError(value.to_string()));
+    }
+    Ok(format!("{:.*}", places as usize, value))
+}
+
+/// Parses the value of a money object.
+#[cfg(feature = "decimal")]
+pub(crate) fn parse_decimal(value: &str) -> Result<Decimal, InvalidAmountError> {
+    value.parse().map_err(|_| InvalidAmountError(value.to_owned()))
+}
+
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(missing_docs)]
@@ -87,6 +572,57 @@ pub enum LinkMethod {
     Patch,
 }
 
+display_from_str!(LinkMethod {
+    Get = "GET",
+    Post = "POST",
+    Put = "PUT",
+    Delete = "DELETE",
+    Head = "HEAD",
+    Connect = "CONNECT",
+    Options = "OPTIONS",
+    Patch = "PATCH",
+});
+
+impl From<LinkMethod> for reqwest::Method {
+    fn from(method: LinkMethod) -> Self {
+        match method {
+            LinkMethod::Get => reqwest::Method::GET,
+            LinkMethod::Post => reqwest::Method::POST,
+            LinkMethod::Put => reqwest::Method::PUT,
+            LinkMethod::Delete => reqwest::Method::DELETE,
+            LinkMethod::Head => reqwest::Method::HEAD,
+            LinkMethod::Connect => reqwest::Method::CONNECT,
+            LinkMethod::Options => reqwest::Method::OPTIONS,
+            LinkMethod::Patch => reqwest::Method::PATCH,
+        }
+    }
+}
+
+string_enum! {
+    /// The relation of a HATEOAS link to the resource it was returned with.
+    ///
+    /// Deserializing a relation this enum doesn't know about yields [LinkRel::Other] instead of failing, unless the response is decoded
+    /// [strictly](crate::errors::DecodeMode::Strict).
+    pub enum LinkRel(Other) {
+        /// The resource itself.
+        SelfLink = "self",
+        /// Where the buyer is redirected to approve the order.
+        Approve = "approve",
+        /// Captures the payment.
+        Capture = "capture",
+        /// Authorizes the payment.
+        Authorize = "authorize",
+        /// Where the buyer is redirected to complete an action, like 3D Secure authentication.
+        PayerAction = "payer-action",
+        /// The parent resource, like the order of a capture.
+        Up = "up",
+        /// Updates the resource.
+        Update = "update",
+        /// Refunds the payment.
+        Refund = "refund",
+    }
+}
+
 /// A HTOAES link
 #[skip_serializing_none]
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -94,110 +630,374 @@ pub struct LinkDescription {
     /// The complete target URL.
     pub href: String,
     /// The link relation type, which serves as an ID for a link that unambiguously describes the semantics of the link.
-    pub rel: Option<String>,
+    pub rel: Option<LinkRel>,
     /// The HTTP method required to make the related call.
     pub method: Option<LinkMethod>,
 }
 
-/// ISO-4217 currency codes.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
-pub enum Currency {
-    /// Australian dollar
-    AUD,
-    /// Brazilian real, supported for in country paypal accounts only.
-    BRL,
-    /// Canadian dollar
-    CAD,
-    /// Chinese Renmenbi
-    CNY,
-    /// Czech koruna
-    CZK,
-    /// Danish krone
-    DKK,
-    /// Euro
-    EUR,
-    /// Hong Kong dollar
-    HKD,
-    /// Hungarian forint, does not support decimals.
-    HUF,
-    /// Indian rupee, supported for in country paypal india accounts only.
-    INR,
-    /// Israeli new shekel
-    ILS,
-    /// Japanese yen, does not support decimals.
-    JPY,
-    /// Malaysian ringgit
-    MYR,
-    /// Mexican peso
-    MXN,
-    /// New Taiwan dollar, does not support decimals.
-    TWD,
-    /// New Zealand dollar
-    NZD,
-    /// Norwegian krone
-    NOK,
-    /// Philippine peso
-    PHP,
-    /// Polish złoty
-    PLN,
-    /// Pound sterling
-    GBP,
-    /// Russian ruble
-    RUB,
-    /// Singapore dollar
-    SGD,
-    /// Swedish krona
-    SEK,
-    /// Swiss franc
-    CHF,
-    /// Thai baht
-    THB,
-    /// United States dollar
-    USD,
-}
-
-impl Default for Currency {
-    fn default() -> Self {
-        Self::EUR
-    }
-}
-
-impl std::fmt::Display for Currency {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Debug::fmt(&self, f)
-    }
-}
-
-impl FromStr for Currency {
-    type Err = InvalidCurrencyError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "AUD" => Ok(Self::AUD),
-            "BRL" => Ok(Self::BRL),
-            "CAD" => Ok(Self::CAD),
-            "CNY" => Ok(Self::CNY),
-            "CZK" => Ok(Self::CZK),
-            "DKK" => Ok(Self::DKK),
-            "EUR" => Ok(Self::EUR),
-            "HKD" => Ok(Self::HKD),
-            "HUF" => Ok(Self::HUF),
-            "INR" => Ok(Self::INR),
-            "ILS" => Ok(Self::ILS),
-            "JPY" => Ok(Self::JPY),
-            "MYR" => Ok(Self::MYR),
-            "MXN" => Ok(Self::MXN),
-            "NOK" => Ok(Self::NOK),
-            "PHP" => Ok(Self::PHP),
-            "PLN" => Ok(Self::PLN),
-            "GBP" => Ok(Self::GBP),
-            "RUB" => Ok(Self::RUB),
-            "SGD" => Ok(Self::SGD),
-            "SEK" => Ok(Self::SGD),
-            "CHF" => Ok(Self::CHF),
-            "THB" => Ok(Self::THB),
-            "USD" => Ok(Self::USD),
-            cur => Err(InvalidCurrencyError(cur.to_owned())),
+impl LinkDescription {
+    /// Finds the link with the given relation.
+    pub fn find(links: &[LinkDescription], rel: LinkRel) -> Option<&LinkDescription> {
+        links.iter().find(|link| link.rel.as_ref() == Some(&rel))
+    }
+}
+
+string_enum! {
+    /// ISO-4217 currency codes.
+    ///
+    /// Deserializing a currency this enum doesn't know about yields [Currency::Other] instead of failing, unless the response is decoded
+    /// [strictly](crate::errors::DecodeMode::Strict).
+    #[derive(Default)]
+    pub enum Currency(Other, is_currency_code => InvalidCurrencyError) {
+        /// UAE dirham
+        AED = "AED",
+        /// Afghan afghani
+        AFN = "AFN",
+        /// Albanian lek
+        ALL = "ALL",
+        /// Armenian dram
+        AMD = "AMD",
+        /// Netherlands Antillean guilder
+        ANG = "ANG",
+        /// Angolan kwanza
+        AOA = "AOA",
+        /// Argentine peso
+        ARS = "ARS",
+        /// Australian dollar
+        AUD = "AUD",
+        /// Aruban florin
+        AWG = "AWG",
+        /// Azerbaijani manat
+        AZN = "AZN",
+        /// Bosnia and Herzegovina convertible mark
+        BAM = "BAM",
+        /// Barbados dollar
+        BBD = "BBD",
+        /// Bangladeshi taka
+        BDT = "BDT",
+        /// Bulgarian lev
+        BGN = "BGN",
+        /// Bahraini dinar
+        BHD = "BHD",
+        /// Burundian franc
+        BIF = "BIF",
+        /// Bermudian dollar
+        BMD = "BMD",
+        /// Brunei dollar
+        BND = "BND",
+        /// Boliviano
+        BOB = "BOB",
+        /// Brazilian real, supported for in country paypal accounts only.
+        BRL = "BRL",
+        /// Bahamian dollar
+        BSD = "BSD",
+        /// Bhutanese ngultrum
+        BTN = "BTN",
+        /// Botswana pula
+        BWP = "BWP",
+        /// Belarusian ruble
+        BYN = "BYN",
+        /// Belize dollar
+        BZD = "BZD",
+        /// Canadian dollar
+        CAD = "CAD",
+        /// Congolese franc
+        CDF = "CDF",
+        /// Swiss franc
+        CHF = "CHF",
+        /// Chilean peso
+        CLP = "CLP",
+        /// Chinese Renmenbi
+        CNY = "CNY",
+        /// Colombian peso
+        COP = "COP",
+        /// Costa Rican colon
+        CRC = "CRC",
+        /// Cuban peso
+        CUP = "CUP",
+        /// Cape Verdean escudo
+        CVE = "CVE",
+        /// Czech koruna
+        CZK = "CZK",
+        /// Djiboutian franc
+        DJF = "DJF",
+        /// Danish krone
+        DKK = "DKK",
+        /// Dominican peso
+        DOP = "DOP",
+        /// Algerian dinar
+        DZD = "DZD",
+        /// Egyptian pound
+        EGP = "EGP",
+        /// Eritrean nakfa
+        ERN = "ERN",
+        /// Ethiopian birr
+        ETB = "ETB",
+        /// Euro
+        #[default]
+        EUR = "EUR",
+        /// Fiji dollar
+        FJD = "FJD",
+        /// Falkland Islands pound
+        FKP = "FKP",
+        /// Pound sterling
+        GBP = "GBP",
+        /// Georgian lari
+        GEL = "GEL",
+        /// Ghanaian cedi
+        GHS = "GHS",
+        /// Gibraltar pound
+        GIP = "GIP",
+        /// Gambian dalasi
+        GMD = "GMD",
+        /// Guinean franc
+        GNF = "GNF",
+        /// Guatemalan quetzal
+        GTQ = "GTQ",
+        /// Guyanese dollar
+        GYD = "GYD",
+        /// Hong Kong dollar
+        HKD = "HKD",
+        /// Honduran lempira
+        HNL = "HNL",
+        /// Haitian gourde
+        HTG = "HTG",
+        /// Hungarian forint, does not support decimals.
+        HUF = "HUF",
+        /// Indonesian rupiah
+        IDR = "IDR",
+        /// Israeli new shekel
+        ILS = "ILS",
+        /// Indian rupee, supported for in country paypal india accounts only.
+        INR = "INR",
+        /// Iraqi dinar
+        IQD = "IQD",
+        /// Iranian rial
+        IRR = "IRR",
+        /// Icelandic króna
+        ISK = "ISK",
+        /// Jamaican dollar
+        JMD = "JMD",
+        /// Jordanian dinar
+        JOD = "JOD",
+        /// Japanese yen, does not support decimals.
+        JPY = "JPY",
+        /// Kenyan shilling
+        KES = "KES",
+        /// Kyrgyzstani som
+        KGS = "KGS",
+        /// Cambodian riel
+        KHR = "KHR",
+        /// Comoro franc
+        KMF = "KMF",
+        /// North Korean won
+        KPW = "KPW",
+        /// South Korean won
+        KRW = "KRW",
+        /// Kuwaiti dinar
+        KWD = "KWD",
+        /// Cayman Islands dollar
+        KYD = "KYD",
+        /// Kazakhstani tenge
+        KZT = "KZT",
+        /// Lao kip
+        LAK = "LAK",
+        /// Lebanese pound
+        LBP = "LBP",
+        /// Sri Lankan rupee
+        LKR = "LKR",
+        /// Liberian dollar
+        LRD = "LRD",
+        /// Lesotho loti
+        LSL = "LSL",
+        /// Libyan dinar
+        LYD = "LYD",
+        /// Moroccan dirham
+        MAD = "MAD",
+        /// Moldovan leu
+        MDL = "MDL",
+        /// Malagasy ariary
+        MGA = "MGA",
+        /// Macedonian denar
+        MKD = "MKD",
+        /// Myanmar kyat
+        MMK = "MMK",
+        /// Mongolian tögrög
+        MNT = "MNT",
+        /// Macanese pataca
+        MOP = "MOP",
+        /// Mauritanian ouguiya
+        MRU = "MRU",
+        /// Mauritian rupee
+        MUR = "MUR",
+        /// Maldivian rufiyaa
+        MVR = "MVR",
+        /// Malawian kwacha
+        MWK = "MWK",
+        /// Mexican peso
+        MXN = "MXN",
+        /// Malaysian ringgit
+        MYR = "MYR",
+        /// Mozambican metical
+        MZN = "MZN",
+        /// Namibian dollar
+        NAD = "NAD",
+        /// Nigerian naira
+        NGN = "NGN",
+        /// Nicaraguan córdoba
+        NIO = "NIO",
+        /// Norwegian krone
+        NOK = "NOK",
+        /// Nepalese rupee
+        NPR = "NPR",
+        /// New Zealand dollar
+        NZD = "NZD",
+        /// Omani rial
+        OMR = "OMR",
+        /// Panamanian balboa
+        PAB = "PAB",
+        /// Peruvian sol
+        PEN = "PEN",
+        /// Papua New Guinean kina
+        PGK = "PGK",
+        /// Philippine peso
+        PHP = "PHP",
+        /// Pakistani rupee
+        PKR = "PKR",
+        /// Polish złoty
+        PLN = "PLN",
+        /// Paraguayan guaraní
+        PYG = "PYG",
+        /// Qatari riyal
+        QAR = "QAR",
+        /// Romanian leu
+        RON = "RON",
+        /// Serbian dinar
+        RSD = "RSD",
+        /// Russian ruble
+        RUB = "RUB",
+        /// Rwandan franc
+        RWF = "RWF",
+        /// Saudi riyal
+        SAR = "SAR",
+        /// Solomon Islands dollar
+        SBD = "SBD",
+        /// Seychelles rupee
+        SCR = "SCR",
+        /// Sudanese pound
+        SDG = "SDG",
+        /// Swedish krona
+        SEK = "SEK",
+        /// Singapore dollar
+        SGD = "SGD",
+        /// Saint Helena pound
+        SHP = "SHP",
+        /// Sierra Leonean leone
+        SLE = "SLE",
+        /// Somali shilling
+        SOS = "SOS",
+        /// Surinamese dollar
+        SRD = "SRD",
+        /// South Sudanese pound
+        SSP = "SSP",
+        /// São Tomé and Príncipe dobra
+        STN = "STN",
+        /// Salvadoran colón
+        SVC = "SVC",
+        /// Syrian pound
+        SYP = "SYP",
+        /// Swazi lilangeni
+        SZL = "SZL",
+        /// Thai baht
+        THB = "THB",
+        /// Tajikistani somoni
+        TJS = "TJS",
+        /// Turkmenistan manat
+        TMT = "TMT",
+        /// Tunisian dinar
+        TND = "TND",
+        /// Tongan paʻanga
+        TOP = "TOP",
+        /// Turkish lira
+        TRY = "TRY",
+        /// Trinidad and Tobago dollar
+        TTD = "TTD",
+        /// New Taiwan dollar, does not support decimals.
+        TWD = "TWD",
+        /// Tanzanian shilling
+        TZS = "TZS",
+        /// Ukrainian hryvnia
+        UAH = "UAH",
+        /// Ugandan shilling
+        UGX = "UGX",
+        /// United States dollar
+        USD = "USD",
+        /// Uruguayan peso
+        UYU = "UYU",
+        /// Uzbekistan som
+        UZS = "UZS",
+        /// Venezuelan bolívar soberano
+        VES = "VES",
+        /// Vietnamese đồng
+        VND = "VND",
+        /// Vanuatu vatu
+        VUV = "VUV",
+        /// Samoan tala
+        WST = "WST",
+        /// CFA franc BEAC
+        XAF = "XAF",
+        /// East Caribbean dollar
+        XCD = "XCD",
+        /// CFA franc BCEAO
+        XOF = "XOF",
+        /// CFP franc
+        XPF = "XPF",
+        /// Yemeni rial
+        YER = "YER",
+        /// South African rand
+        ZAR = "ZAR",
+        /// Zambian kwacha
+        ZMW = "ZMW",
+        /// Zimbabwean dollar
+        ZWL = "ZWL",
+    }
+}
+
+/// Whether a currency this crate doesn't know about looks like an ISO-4217 code, paypal may add new ones.
+fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+impl Currency {
+    /// The number of decimal places paypal accepts for amounts in this currency.
+    pub fn decimal_places(&self) -> u32 {
+        match self {
+            Currency::BIF
+            | Currency::CLP
+            | Currency::DJF
+            | Currency::GNF
+            | Currency::HUF
+            | Currency::ISK
+            | Currency::JPY
+            | Currency::KMF
+            | Currency::KRW
+            | Currency::PYG
+            | Currency::RWF
+            | Currency::TWD
+            | Currency::UGX
+            | Currency::VND
+            | Currency::VUV
+            | Currency::XAF
+            | Currency::XOF
+            | Currency::XPF => 0,
+            Currency::BHD
+            | Currency::IQD
+            | Currency::JOD
+            | Currency::KWD
+            | Currency::LYD
+            | Currency::OMR
+            | Currency::TND => 3,
+            _ => 2,
         }
     }
 }
@@ -0,0 +1,218 @@
+//! Configuration of a [Client] from the environment or a config file, so examples, command line tools and services
+//! don't each read the credentials their own way.
+//!
+//! [Client::from_env] reads the variables:
+//! - `PAYPAL_CLIENT_ID` and `PAYPAL_SECRET`, the credentials of the app. The client id is read from
+//!   `PAYPAL_CLIENTID` when `PAYPAL_CLIENT_ID` isn't set, that name is deprecated and will stop working.
+//! - `PAYPAL_ENV`, `sandbox` or `live`, the sandbox is used when it's not set.
+//! - `PAYPAL_BN_CODE`, optional, the build notation code sent as PayPal-Partner-Attribution-Id.
+//! - `PAYPAL_WEBHOOK_ID`, optional, the id of the webhook whose deliveries are verified.
+//!
+//! [Client::from_profile] reads the same settings from a profile of a json config file, to switch between apps
+//! or environments by name:
+//!
+//! ```json
+//! {
+//!     "sandbox": { "client_id": "AYSq3RDGsmBLJE", "secret": "EGnHDxD_qRPdaLdZz8" },
+//!     "live": { "client_id": "AeA1QIZXiflr1_", "secret": "ECYYrrSHdKfk_Q0E", "environment": "live" }
+//! }
+//! ```
+//!
+//! Both go through a [ClientConfig], whose [builder](ClientConfig::builder) can configure the client further.
+
+use crate::client::{Client, ClientBuilder, Environment};
+use crate::errors::ConfigError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The former name of the `PAYPAL_CLIENT_ID` variable, still read so existing `.env` files keep working.
+const DEPRECATED_CLIENT_ID_VAR: &str = "PAYPAL_CLIENTID";
+
+/// The settings of a client, read from the environment or a profile.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The client id of the app.
+    pub client_id: String,
+    /// The secret of the app.
+    pub secret: String,
+    /// The environment the client talks to.
+    #[serde(default)]
+    pub environment: Environment,
+    /// The build notation (BN) code sent as PayPal-Partner-Attribution-Id on every request.
+    pub partner_attribution_id: Option<String>,
+    /// The id of the webhook whose deliveries are verified.
+    pub webhook_id: Option<String>,
+}
+
+impl std::fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("client_id", &self.client_id)
+            .field("secret", &"REDACTED")
+            .field("environment", &self.environment)
+            .field("partner_attribution_id", &self.partner_attribution_id)
+            .field("webhook_id", &self.webhook_id)
+            .finish()
+    }
+}
+
+impl ClientConfig {
+    /// Reads the settings from the environment variables, see the [module documentation](self).
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars<F: Fn(&'static str) -> Option<String>>(var: F) -> Result<Self, ConfigError> {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        let required = |name| var(name).ok_or(ConfigError::MissingVariable(name));
+        let client_id = match var(DEPRECATED_CLIENT_ID_VAR) {
+            Some(client_id) if var("PAYPAL_CLIENT_ID").is_none() => {
+                log::warn!(target: "paypal-rs", "{} is deprecated, set PAYPAL_CLIENT_ID instead", DEPRECATED_CLIENT_ID_VAR);
+                client_id
+            }
+            _ => required("PAYPAL_CLIENT_ID")?,
+        };
+        let secret = required("PAYPAL_SECRET")?;
+        let environment = match var("PAYPAL_ENV") {
+            Some(environment) => environment
+                .parse()
+                .map_err(|_| ConfigError::InvalidEnvironment(environment))?,
+            None => Environment::Sandbox,
+        };
+
+        Ok(ClientConfig {
+            client_id,
+            secret,
+            environment,
+            partner_attribution_id: var("PAYPAL_BN_CODE"),
+            webhook_id: var("PAYPAL_WEBHOOK_ID"),
+        })
+    }
+
+    /// Reads the settings from the given profile of a json config file, see the [module documentation](self).
+    pub fn from_profile<P: AsRef<Path>>(path: P, profile: &str) -> Result<Self, ConfigError> {
+        let contents = std::fs::read(path).map_err(ConfigError::Io)?;
+        let mut profiles: HashMap<String, ClientConfig> =
+            serde_json::from_slice(&contents).map_err(ConfigError::InvalidFile)?;
+        profiles
+            .remove(profile)
+            .ok_or_else(|| ConfigError::UnknownProfile(profile.to_owned()))
+    }
+
+    /// Returns a builder configured with these settings.
+    pub fn builder(self) -> ClientBuilder {
+        let mut builder = Client::builder()
+            .client_id(self.client_id)
+            .secret(self.secret)
+            .environment(self.environment);
+        if let Some(partner_attribution_id) = self.partner_attribution_id {
+            builder = builder.partner_attribution_id(partner_attribution_id);
+        }
+        #[cfg(feature = "webhooks")]
+        if let Some(webhook_id) = self.webhook_id {
+            builder = builder.webhook_id(webhook_id);
+        }
+        builder
+    }
+}
+
+impl Client {
+    /// Builds a client configured from the environment variables, see the [config](crate::config) module.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use paypal_rs::Client;
+    ///
+    /// let client = Client::from_env().expect("the paypal credentials are not configured");
+    /// ```
+    pub fn from_env() -> Result<Client, ConfigError> {
+        Ok(ClientConfig::from_env()?.builder().build()?)
+    }
+
+    /// Builds a client configured from a profile of a json config file, see the [config](crate::config) module.
+    pub fn from_profile<P: AsRef<Path>>(path: P, profile: &str) -> Result<Client, ConfigError> {
+        Ok(ClientConfig::from_profile(path, profile)?.builder().build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_vars() {
+        let vars: HashMap<&str, &str> = [
+            ("PAYPAL_CLIENT_ID", "AYSq3RDGsmBLJE"),
+            ("PAYPAL_SECRET", "EGnHDxD_qRPdaLdZz8"),
+            ("PAYPAL_ENV", "Live"),
+            ("PAYPAL_BN_CODE", "FLAVORsb-cz4iu26442098_MP"),
+            ("PAYPAL_WEBHOOK_ID", ""),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let config = ClientConfig::from_vars(|name| vars.get(name).map(|value| value.to_string())).unwrap();
+        assert_eq!(config.client_id, "AYSq3RDGsmBLJE");
+        assert_eq!(config.environment, Environment::Live);
+        assert_eq!(
+            config.partner_attribution_id.as_deref(),
+            Some("FLAVORsb-cz4iu26442098_MP")
+        );
+        assert!(config.webhook_id.is_none());
+        let debug = format!("{:?}", config);
+        assert!(debug.contains(r#"secret: "REDACTED""#) && !debug.contains("EGnHDxD_qRPdaLdZz8"));
+        let client = config.builder().build().unwrap();
+        assert_eq!(client.environment(), Environment::Live);
+
+        let deprecated = ClientConfig::from_vars(|name| match name {
+            "PAYPAL_CLIENTID" => Some("AYSq3RDGsmBLJE".to_owned()),
+            "PAYPAL_SECRET" => Some("EGnHDxD_qRPdaLdZz8".to_owned()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(deprecated.client_id, "AYSq3RDGsmBLJE");
+
+        let missing = ClientConfig::from_vars(|name| Some(name.to_owned()).filter(|name| *name != "PAYPAL_SECRET"));
+        assert!(matches!(missing, Err(ConfigError::MissingVariable("PAYPAL_SECRET"))));
+        let invalid = ClientConfig::from_vars(|name| match name {
+            "PAYPAL_ENV" => Some("production".to_owned()),
+            _ => Some(name.to_owned()),
+        });
+        assert!(matches!(invalid, Err(ConfigError::InvalidEnvironment(_))));
+    }
+
+    #[test]
+    fn test_from_profile() {
+        let path = std::env::temp_dir().join(format!("paypal-rs-profiles-{}.json", std::process::id()));
+        let profiles = r#"{
+            "sandbox": { "client_id": "AYSq3RDGsmBLJE", "secret": "EGnHDxD_qRPdaLdZz8" },
+            "live": { "client_id": "AeA1QIZXiflr1_", "secret": "ECYYrrSHdKfk_Q0E", "environment": "live", "webhook_id": "1JE4291016473214C" }
+        }"#;
+        std::fs::write(&path, profiles).unwrap();
+
+        let sandbox = ClientConfig::from_profile(&path, "sandbox").unwrap();
+        assert_eq!(sandbox.environment, Environment::Sandbox);
+        let client = Client::from_profile(&path, "live").unwrap();
+        assert_eq!(client.environment(), Environment::Live);
+        #[cfg(feature = "webhooks")]
+        assert_eq!(client.webhook_id(), Some("1JE4291016473214C"));
+        assert!(matches!(
+            Client::from_profile(&path, "staging"),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+
+        std::fs::write(
+            &path,
+            r#"{ "sandbox": { "client_id": "AYSq3RDGsmBLJE", "secrets": "" } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            ClientConfig::from_profile(&path, "sandbox"),
+            Err(ConfigError::InvalidFile(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,548 @@
+//! Use the Disputes API to look up the disputes opened by buyers and the documents exchanged during them,
+//! for example to archive the evidence once a dispute is resolved.
+//!
+//! Reference: https://developer.paypal.com/docs/api/customer-disputes/v1/
+
+use crate::client::{Client, HeaderParams};
+use crate::common::*;
+use crate::errors::{decode, ResponseError};
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+string_enum! {
+    /// The reason for the dispute.
+    pub enum DisputeReason {
+        /// The customer did not receive the merchandise or service.
+        MerchandiseOrServiceNotReceived = "MERCHANDISE_OR_SERVICE_NOT_RECEIVED",
+        /// The customer reports that the merchandise or service is not as described.
+        MerchandiseOrServiceNotAsDescribed = "MERCHANDISE_OR_SERVICE_NOT_AS_DESCRIBED",
+        /// The customer did not authorize purchase of the merchandise or service.
+        Unauthorised = "UNAUTHORISED",
+        /// The refund or credit was not processed for the customer.
+        CreditNotProcessed = "CREDIT_NOT_PROCESSED",
+        /// The transaction was a duplicate.
+        DuplicateTransaction = "DUPLICATE_TRANSACTION",
+        /// The customer was charged an incorrect amount.
+        IncorrectAmount = "INCORRECT_AMOUNT",
+        /// The customer paid for the transaction through other means.
+        PaymentByOtherMeans = "PAYMENT_BY_OTHER_MEANS",
+        /// The customer was being charged for a subscription or a recurring transaction that was canceled.
+        CanceledRecurringBilling = "CANCELED_RECURRING_BILLING",
+        /// A problem occurred with the remittance.
+        ProblemWithRemittance = "PROBLEM_WITH_REMITTANCE",
+        /// Other.
+        Other = "OTHER",
+    }
+}
+
+string_enum! {
+    /// The status of the dispute.
+    pub enum DisputeStatus {
+        /// The dispute is open.
+        Open = "OPEN",
+        /// The dispute is waiting for a response from the customer.
+        WaitingForBuyerResponse = "WAITING_FOR_BUYER_RESPONSE",
+        /// The dispute is waiting for a response from the merchant.
+        WaitingForSellerResponse = "WAITING_FOR_SELLER_RESPONSE",
+        /// The dispute is under review with PayPal.
+        UnderReview = "UNDER_REVIEW",
+        /// The dispute is resolved.
+        Resolved = "RESOLVED",
+        /// The default status if the dispute does not have one of the other statuses.
+        Other = "OTHER",
+    }
+}
+
+string_enum! {
+    /// The stage in the dispute lifecycle.
+    pub enum DisputeLifeCycleStage {
+        /// A customer and merchant interact in an attempt to resolve a dispute without escalation to PayPal.
+        Inquiry = "INQUIRY",
+        /// The customer disputed the charge with their card issuer.
+        Chargeback = "CHARGEBACK",
+        /// The first appeal stage for merchants.
+        PreArbitration = "PRE_ARBITRATION",
+        /// The second appeal stage for merchants.
+        Arbitration = "ARBITRATION",
+    }
+}
+
+string_enum! {
+    /// The type of an evidence, only the most common ones are listed.
+    pub enum EvidenceType {
+        /// Proof that the merchandise was shipped.
+        ProofOfFulfillment = "PROOF_OF_FULFILLMENT",
+        /// Proof that the payment was refunded.
+        ProofOfRefund = "PROOF_OF_REFUND",
+        /// Proof of delivery signature.
+        ProofOfDeliverySignature = "PROOF_OF_DELIVERY_SIGNATURE",
+        /// Proof of receipt copy.
+        ProofOfReceiptCopy = "PROOF_OF_RECEIPT_COPY",
+        /// The return policy of the merchant.
+        ReturnPolicy = "RETURN_POLICY",
+        /// The billing agreement.
+        BillingAgreement = "BILLING_AGREEMENT",
+        /// Proof that the merchandise was shipped again.
+        ProofOfReshipment = "PROOF_OF_RESHIPMENT",
+        /// The description of the item.
+        ItemDescription = "ITEM_DESCRIPTION",
+        /// A police report.
+        PoliceReport = "POLICE_REPORT",
+        /// An affidavit.
+        Affidavit = "AFFIDAVIT",
+        /// Proof that the customer paid with another method.
+        PaidWithOtherMethod = "PAID_WITH_OTHER_METHOD",
+        /// A copy of the contract.
+        CopyOfContract = "COPY_OF_CONTRACT",
+        /// A bank statement.
+        BankStatement = "BANK_STATEMENT",
+        /// Proof that the merchandise was returned.
+        ProofOfReturn = "PROOF_OF_RETURN",
+        /// A copy of the customs document.
+        CustomsDocument = "CUSTOMS_DOCUMENT",
+        /// A photo of the shipped item.
+        PhotosOfShippedItem = "PHOTOS_OF_SHIPPED_ITEM",
+        /// Other.
+        Other = "OTHER",
+    }
+}
+
+string_enum! {
+    /// Who posted a message in a dispute.
+    pub enum MessagePostedBy {
+        /// The customer.
+        Buyer = "BUYER",
+        /// The merchant.
+        Seller = "SELLER",
+        /// PayPal.
+        Arbiter = "ARBITER",
+    }
+}
+
+#[cfg(feature = "sandbox-testing")]
+string_enum! {
+    /// The party asked for evidence when moving a sandbox dispute forward.
+    pub enum EvidenceAction {
+        /// Moves the dispute to the WAITING_FOR_BUYER_RESPONSE status.
+        BuyerEvidence = "BUYER_EVIDENCE",
+        /// Moves the dispute to the WAITING_FOR_SELLER_RESPONSE status.
+        SellerEvidence = "SELLER_EVIDENCE",
+    }
+}
+
+#[cfg(feature = "sandbox-testing")]
+string_enum! {
+    /// The outcome of a settled sandbox dispute.
+    pub enum AdjudicationOutcome {
+        /// The dispute is resolved in favor of the customer.
+        BuyerFavor = "BUYER_FAVOR",
+        /// The dispute is resolved in favor of the merchant.
+        SellerFavor = "SELLER_FAVOR",
+    }
+}
+
+/// A document attached to a dispute, download it with [Client::download_dispute_document].
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// The document name.
+    pub name: Option<String>,
+    /// The downloadable URL for the document.
+    pub url: Option<String>,
+}
+
+/// An evidence provided by the customer or the merchant.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Evidence {
+    /// The evidence type.
+    pub evidence_type: Option<EvidenceType>,
+    /// An array of evidence documents.
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    /// Any evidence-related notes.
+    pub notes: Option<String>,
+    /// The source of the evidence, like `SUBMITTED_BY_BUYER`.
+    pub source: Option<String>,
+    /// The date and time when the evidence was received.
+    pub date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Supporting information provided by the customer or the merchant.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupportingInfo {
+    /// Any supporting notes.
+    pub notes: Option<String>,
+    /// An array of supporting documents.
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    /// The source of the information, like `SUBMITTED_BY_BUYER`.
+    pub source: Option<String>,
+    /// The date and time when the information was provided.
+    pub provided_time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A message exchanged in a dispute.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Message {
+    /// Who posted the message.
+    pub posted_by: Option<MessagePostedBy>,
+    /// The date and time when the message was posted.
+    pub time_posted: Option<chrono::DateTime<chrono::Utc>>,
+    /// The message text.
+    pub content: Option<String>,
+    /// An array of the documents attached to the message.
+    #[serde(default)]
+    pub documents: Vec<Document>,
+}
+
+/// A dispute opened by a buyer.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dispute {
+    /// The ID of the dispute.
+    pub dispute_id: String,
+    /// The date and time when the dispute was created.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the dispute was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The reason for the dispute.
+    pub reason: Option<DisputeReason>,
+    /// The status of the dispute.
+    pub status: Option<DisputeStatus>,
+    /// The amount in the transaction that the customer originally disputed.
+    pub dispute_amount: Option<Money>,
+    /// The stage in the dispute lifecycle.
+    pub dispute_life_cycle_stage: Option<DisputeLifeCycleStage>,
+    /// An array of evidence documents.
+    #[serde(default)]
+    pub evidences: Vec<Evidence>,
+    /// An array of supporting information.
+    #[serde(default)]
+    pub supporting_info: Vec<SupportingInfo>,
+    /// An array of customer- or merchant-posted messages for the dispute.
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+impl Dispute {
+    /// Every document attached to the dispute, from the evidences, the supporting information and the messages.
+    pub fn documents(&self) -> impl Iterator<Item = &Document> {
+        let evidences = self.evidences.iter().flat_map(|evidence| &evidence.documents);
+        let supporting_info = self.supporting_info.iter().flat_map(|info| &info.documents);
+        let messages = self.messages.iter().flat_map(|message| &message.documents);
+        evidences.chain(supporting_info).chain(messages)
+    }
+}
+
+impl Client {
+    /// Shows details for a dispute, by ID.
+    pub async fn show_dispute_details(
+        &mut self,
+        dispute_id: &str,
+        header_params: HeaderParams,
+    ) -> Result<Dispute, ResponseError> {
+        let build = self
+            .setup_headers(
+                self.client
+                    .get(format!("{}/v1/customer/disputes/{}", self.endpoint(), dispute_id).as_str()),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build).await?;
+
+        if res.status().is_success() {
+            let dispute = decode::<Dispute>(res, self.decode_mode).await?;
+            Ok(dispute)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Downloads a document attached to a dispute, streaming it to the given writer, and returns its size in bytes.
+    ///
+    /// Documents hosted on the api endpoint of this client are downloaded with the access token, like
+    /// [follow_link](Client::follow_link) does. Documents hosted on paypal.com outside of the api are downloaded
+    /// without it, and other urls are refused.
+    pub async fn download_dispute_document<W: AsyncWrite + Unpin>(
+        &mut self,
+        document: &Document,
+        writer: &mut W,
+    ) -> Result<u64, ResponseError> {
+        let url = document.url.as_deref().unwrap_or_default();
+        let build = if url.starts_with(&format!("{}/", self.endpoint())) {
+            self.setup_headers(self.client.get(url), HeaderParams::default()).await
+        } else if reqwest::Url::parse(url).is_ok_and(|url| is_paypal_hosted(&url)) {
+            self.client.get(url)
+        } else {
+            return Err(ResponseError::InvalidLink(url.to_owned()));
+        };
+        let mut accept = HeaderMap::new();
+        accept.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+
+        let mut res = self.execute(build.headers(accept)).await?;
+
+        if res.status().is_success() {
+            let mut size = 0;
+            while let Some(chunk) = res.chunk().await? {
+                writer.write_all(&chunk).await?;
+                size += chunk.len() as u64;
+            }
+            writer.flush().await?;
+            Ok(size)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+/// Whether the url is served by paypal.com over https.
+fn is_paypal_hosted(url: &reqwest::Url) -> bool {
+    url.scheme() == "https"
+        && url
+            .host_str()
+            .is_some_and(|host| host == "paypal.com" || host.ends_with(".paypal.com"))
+}
+
+#[cfg(feature = "sandbox-testing")]
+#[derive(Debug, Deserialize)]
+struct DisputeActionResponse {
+    #[serde(default)]
+    links: Vec<LinkDescription>,
+}
+
+/// Endpoints only available in the sandbox, to exercise the handling of every stage of a dispute in end-to-end tests.
+///
+/// They fail with [ResponseError::SandboxOnly] without sending anything when the client talks to the live environment.
+#[cfg(feature = "sandbox-testing")]
+impl Client {
+    /// Updates the status of a dispute to wait for evidence from the customer or the merchant, sandbox only.
+    pub async fn require_dispute_evidence(
+        &mut self,
+        dispute_id: &str,
+        action: EvidenceAction,
+        header_params: HeaderParams,
+    ) -> Result<Vec<LinkDescription>, ResponseError> {
+        let path = format!("/v1/customer/disputes/{}/require-evidence", dispute_id);
+        self.dispute_action(&path, &serde_json::json!({ "action": action }), header_params)
+            .await
+    }
+
+    /// Settles a dispute in favor of the customer or the merchant, sandbox only.
+    pub async fn settle_dispute(
+        &mut self,
+        dispute_id: &str,
+        outcome: AdjudicationOutcome,
+        header_params: HeaderParams,
+    ) -> Result<Vec<LinkDescription>, ResponseError> {
+        let path = format!("/v1/customer/disputes/{}/adjudicate", dispute_id);
+        let body = serde_json::json!({ "adjudication_outcome": outcome });
+        self.dispute_action(&path, &body, header_params).await
+    }
+
+    /// Escalates a dispute to a claim reviewed by paypal, with a note explaining why, sandbox only.
+    pub async fn escalate_dispute(
+        &mut self,
+        dispute_id: &str,
+        note: &str,
+        header_params: HeaderParams,
+    ) -> Result<Vec<LinkDescription>, ResponseError> {
+        let path = format!("/v1/customer/disputes/{}/escalate", dispute_id);
+        self.dispute_action(&path, &serde_json::json!({ "note": note }), header_params)
+            .await
+    }
+
+    async fn dispute_action(
+        &mut self,
+        path: &str,
+        body: &serde_json::Value,
+        header_params: HeaderParams,
+    ) -> Result<Vec<LinkDescription>, ResponseError> {
+        if !self.sandbox {
+            return Err(ResponseError::SandboxOnly(path.to_owned()));
+        }
+
+        let build = self
+            .setup_headers(
+                self.client.post(format!("{}{}", self.endpoint(), path).as_str()),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build.json(body)).await?;
+
+        if res.status().is_success() {
+            Ok(decode::<DisputeActionResponse>(res, self.decode_mode).await?.links)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "vcr"))]
+mod tests {
+    use super::*;
+    use crate::middleware::Middleware;
+    use crate::vcr::{interaction, replay_cassette, token_interaction};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the path of every request and whether it carried an access token.
+    #[derive(Debug, Default, Clone)]
+    struct Authorizations(Arc<Mutex<Vec<(String, bool)>>>);
+
+    impl Middleware for Authorizations {
+        fn on_request(&self, request: &mut reqwest::Request) {
+            let authorized = request.headers().contains_key(header::AUTHORIZATION);
+            self.0
+                .lock()
+                .unwrap()
+                .push((request.url().path().to_owned(), authorized));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_documents() {
+        let interactions = [
+            token_interaction(),
+            interaction(
+                "GET",
+                "/v1/customer/disputes/PP-D-27803",
+                b"",
+                200,
+                r#"{
+                    "dispute_id": "PP-D-27803",
+                    "create_time": "2019-04-11T04:18:00.000Z",
+                    "update_time": "2019-04-21T04:19:08.000Z",
+                    "reason": "MERCHANDISE_OR_SERVICE_NOT_RECEIVED",
+                    "status": "RESOLVED",
+                    "dispute_amount": {"currency_code": "USD", "value": "3.00"},
+                    "dispute_life_cycle_stage": "CHARGEBACK",
+                    "evidences": [
+                        {
+                            "evidence_type": "PROOF_OF_FULFILLMENT",
+                            "documents": [
+                                {"name": "tracking.pdf", "url": "https://api-m.sandbox.paypal.com/v1/customer/disputes/PP-D-27803/documents/DOC-1"}
+                            ],
+                            "source": "SUBMITTED_BY_SELLER"
+                        }
+                    ],
+                    "messages": [
+                        {
+                            "posted_by": "BUYER",
+                            "time_posted": "2019-04-12T04:18:00.000Z",
+                            "content": "The package never arrived.",
+                            "documents": [{"name": "photo.png", "url": "https://www.paypal.com/disputes/PP-D-27803/photo.png"}]
+                        }
+                    ],
+                    "links": []
+                }"#,
+            ),
+            interaction(
+                "GET",
+                "/v1/customer/disputes/PP-D-27803/documents/DOC-1",
+                b"",
+                200,
+                "%PDF-1.4",
+            ),
+            interaction("GET", "/disputes/PP-D-27803/photo.png", b"", 200, "PNG"),
+        ];
+
+        let authorizations = Authorizations::default();
+        let mut client = Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .middleware(authorizations.clone())
+            .build()
+            .unwrap();
+        client.set_cassette(replay_cassette(&interactions));
+
+        let dispute = client
+            .show_dispute_details("PP-D-27803", HeaderParams::default())
+            .await
+            .unwrap();
+        assert_eq!(dispute.status, Some(DisputeStatus::Resolved));
+        let documents: Vec<_> = dispute.documents().cloned().collect();
+        assert_eq!(documents.len(), 2);
+
+        let mut pdf = Vec::new();
+        let size = client.download_dispute_document(&documents[0], &mut pdf).await.unwrap();
+        assert_eq!(size, 8);
+        assert_eq!(pdf, b"%PDF-1.4");
+
+        // Documents hosted on paypal.com are downloaded without the access token.
+        let mut png = Vec::new();
+        client.download_dispute_document(&documents[1], &mut png).await.unwrap();
+        assert_eq!(png, b"PNG");
+        let authorizations = authorizations.0.lock().unwrap().clone();
+        assert!(authorizations.contains(&("/v1/customer/disputes/PP-D-27803/documents/DOC-1".to_owned(), true)));
+        assert!(authorizations.contains(&("/disputes/PP-D-27803/photo.png".to_owned(), false)));
+
+        for url in [
+            "https://paypal.com.example.com/photo.png",
+            "http://www.paypal.com/photo.png",
+        ] {
+            let document = Document {
+                name: None,
+                url: Some(url.to_owned()),
+            };
+            assert!(matches!(
+                client.download_dispute_document(&document, &mut Vec::new()).await,
+                Err(ResponseError::InvalidLink(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "sandbox-testing")]
+    #[tokio::test]
+    async fn test_sandbox_dispute_lifecycle() {
+        let links = r#"{"links":[{"href":"https://api-m.sandbox.paypal.com/v1/customer/disputes/PP-D-27803","rel":"self","method":"GET"}]}"#;
+        let action = |path: &str, body: &[u8]| interaction("POST", path, body, 200, links);
+        let interactions = [
+            token_interaction(),
+            action(
+                "/v1/customer/disputes/PP-D-27803/require-evidence",
+                br#"{"action":"SELLER_EVIDENCE"}"#,
+            ),
+            action(
+                "/v1/customer/disputes/PP-D-27803/escalate",
+                br#"{"note":"The buyer didn't answer."}"#,
+            ),
+            action(
+                "/v1/customer/disputes/PP-D-27803/adjudicate",
+                br#"{"adjudication_outcome":"SELLER_FAVOR"}"#,
+            ),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        let links = client
+            .require_dispute_evidence("PP-D-27803", EvidenceAction::SellerEvidence, HeaderParams::default())
+            .await
+            .unwrap();
+        assert_eq!(links[0].rel, Some(LinkRel::SelfLink));
+        client
+            .escalate_dispute("PP-D-27803", "The buyer didn't answer.", HeaderParams::default())
+            .await
+            .unwrap();
+        client
+            .settle_dispute("PP-D-27803", AdjudicationOutcome::SellerFavor, HeaderParams::default())
+            .await
+            .unwrap();
+
+        let mut live = Client::new("id".to_owned(), "secret".to_owned(), false);
+        assert!(matches!(
+            live.settle_dispute("PP-D-27803", AdjudicationOutcome::SellerFavor, HeaderParams::default())
+                .await,
+            Err(ResponseError::SandboxOnly(_))
+        ));
+    }
+}
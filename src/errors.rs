@@ -1,14 +1,37 @@
 //! Errors created by this crate.
-use crate::common::LinkDescription;
-use serde::{Deserialize, Serialize};
+use crate::common::{Currency, LinkDescription};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 
+/// The maximum number of bytes of the body kept in [ResponseError::Decode].
+pub const DECODE_BODY_LIMIT: usize = 4096;
+
+/// Issue codes that the buyer can resolve, for example by choosing another funding source.
+pub const BUYER_ACTIONABLE_ISSUES: &[&str] = &[
+    "INSTRUMENT_DECLINED",
+    "PAYER_ACTION_REQUIRED",
+    "PAYER_CANNOT_PAY",
+    "PAYER_ACCOUNT_RESTRICTED",
+    "PAYER_ACCOUNT_LOCKED_OR_CLOSED",
+    "CARD_EXPIRED",
+    "CARD_CLOSED",
+    "INVALID_SECURITY_CODE",
+    "INVALID_EXPIRATION_DATE",
+    "CARD_TYPE_NOT_SUPPORTED",
+    "REDIRECT_PAYER_FOR_ALTERNATE_FUNDING",
+];
+
 /// A paypal api response error.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaypalError {
+    /// The http status code of the response.
+    #[serde(skip)]
+    pub status: Option<u16>,
     /// The error name.
+    #[serde(default)]
     pub name: String,
     /// The error message.
     pub message: Option<String>,
@@ -20,10 +43,28 @@ pub struct PaypalError {
     pub error: Option<String>,
     /// Only available on Identity errors
     pub error_description: Option<String>,
+    /// A link to more information about the error, on payout item errors.
+    pub information_link: Option<String>,
     /// Links with more information about the error.
+    #[serde(default)]
     pub links: Vec<LinkDescription>,
 }
 
+impl PaypalError {
+    /// The issue codes in the error details, like `INSTRUMENT_DECLINED`.
+    pub fn issues(&self) -> impl Iterator<Item = &str> {
+        self.details
+            .iter()
+            .flatten()
+            .filter_map(|detail| detail.get("issue").map(String::as_str))
+    }
+
+    /// Whether the error name or any of the issues is the given code.
+    pub fn has_issue(&self, code: &str) -> bool {
+        self.name == code || self.issues().any(|issue| issue == code)
+    }
+}
+
 impl fmt::Display for PaypalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:#?}", self)
@@ -39,6 +80,36 @@ pub enum ResponseError {
     ApiError(PaypalError),
     /// A http error.
     HttpError(reqwest::Error),
+    /// The response body could not be deserialized.
+    Decode {
+        /// The http status code of the response.
+        status: u16,
+        /// The raw response body, truncated to [DECODE_BODY_LIMIT] bytes.
+        body: String,
+        /// The deserialization error.
+        source: serde_json::Error,
+    },
+    /// The link can't be followed, because it points outside of the paypal api.
+    InvalidLink(String),
+    /// The response body could not be written out.
+    IoError(std::io::Error),
+    /// The request could not be recorded or replayed.
+    #[cfg(feature = "vcr")]
+    VcrError(String),
+    /// The request body doesn't match the paypal api schema, so the request was not sent.
+    #[cfg(feature = "validation")]
+    InvalidRequest(Vec<crate::validation::SchemaViolation>),
+    /// The circuit breaker is open, so the request was not sent.
+    CircuitOpen,
+    /// The webhook delivery to verify lacks a header or its body isn't json, so it was not sent.
+    #[cfg(feature = "webhooks")]
+    InvalidWebhookRequest(InvalidWebhookRequestError),
+    /// The endpoint only exists in the sandbox and the client talks to the live environment, so the request was not sent.
+    #[cfg(feature = "sandbox-testing")]
+    SandboxOnly(String),
+    /// The idempotency key is too long to be sent as the PayPal-Request-Id, so the request was not sent.
+    #[cfg(feature = "orders")]
+    InvalidIdempotencyKey(String),
 }
 
 impl fmt::Display for ResponseError {
@@ -46,6 +117,34 @@ impl fmt::Display for ResponseError {
         match self {
             ResponseError::ApiError(e) => write!(f, "{}", e),
             ResponseError::HttpError(e) => write!(f, "{}", e),
+            ResponseError::Decode { status, body, source } => {
+                write!(
+                    f,
+                    "error decoding response with status {}: {}, body: {}",
+                    status, source, body
+                )
+            }
+            ResponseError::InvalidLink(href) => write!(f, "can't follow link to {}", href),
+            ResponseError::IoError(e) => write!(f, "{}", e),
+            ResponseError::CircuitOpen => write!(f, "paypal is unavailable, the circuit breaker is open"),
+            #[cfg(feature = "vcr")]
+            ResponseError::VcrError(e) => write!(f, "{}", e),
+            #[cfg(feature = "webhooks")]
+            ResponseError::InvalidWebhookRequest(e) => write!(f, "{}", e),
+            #[cfg(feature = "sandbox-testing")]
+            ResponseError::SandboxOnly(path) => write!(f, "{} is only available in the sandbox", path),
+            #[cfg(feature = "orders")]
+            ResponseError::InvalidIdempotencyKey(key) => write!(
+                f,
+                "the idempotency key {:?} is longer than {} characters",
+                key,
+                crate::idempotency::MAX_KEY_LENGTH
+            ),
+            #[cfg(feature = "validation")]
+            ResponseError::InvalidRequest(violations) => {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                write!(f, "invalid request body: {}", violations.join("; "))
+            }
         }
     }
 }
@@ -55,10 +154,213 @@ impl Error for ResponseError {
         match self {
             ResponseError::ApiError(e) => Some(e),
             ResponseError::HttpError(e) => Some(e),
+            ResponseError::Decode { source, .. } => Some(source),
+            ResponseError::InvalidLink(_) | ResponseError::CircuitOpen => None,
+            ResponseError::IoError(e) => Some(e),
+            #[cfg(feature = "vcr")]
+            ResponseError::VcrError(_) => None,
+            #[cfg(feature = "validation")]
+            ResponseError::InvalidRequest(_) => None,
+            #[cfg(feature = "webhooks")]
+            ResponseError::InvalidWebhookRequest(e) => Some(e),
+            #[cfg(feature = "sandbox-testing")]
+            ResponseError::SandboxOnly(_) => None,
+            #[cfg(feature = "orders")]
+            ResponseError::InvalidIdempotencyKey(_) => None,
+        }
+    }
+}
+
+impl ResponseError {
+    /// Builds the error from a response with an error status.
+    pub(crate) async fn from_response(res: reqwest::Response) -> Self {
+        let status = res.status().as_u16();
+        match decode::<PaypalError>(res, DecodeMode::Lenient).await {
+            Ok(mut e) => {
+                e.status = Some(status);
+                ResponseError::ApiError(e)
+            }
+            Err(e) => e,
+        }
+    }
+
+    /// The http status code of the response, if one was received.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ResponseError::ApiError(e) => e.status,
+            ResponseError::HttpError(e) => e.status().map(|s| s.as_u16()),
+            ResponseError::Decode { status, .. } => Some(*status),
+            ResponseError::InvalidLink(_) | ResponseError::IoError(_) | ResponseError::CircuitOpen => None,
+            #[cfg(feature = "vcr")]
+            ResponseError::VcrError(_) => None,
+            #[cfg(feature = "validation")]
+            ResponseError::InvalidRequest(_) => None,
+            #[cfg(feature = "webhooks")]
+            ResponseError::InvalidWebhookRequest(_) => None,
+            #[cfg(feature = "sandbox-testing")]
+            ResponseError::SandboxOnly(_) => None,
+            #[cfg(feature = "orders")]
+            ResponseError::InvalidIdempotencyKey(_) => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed, because it failed
+    /// due to a network issue, rate limiting or a temporary problem on paypal's side.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_rate_limited() {
+            return true;
+        }
+
+        match self {
+            ResponseError::ApiError(e) => {
+                e.status.is_some_and(|s| s >= 500)
+                    || e.has_issue("INTERNAL_SERVER_ERROR")
+                    || e.has_issue("SERVICE_UNAVAILABLE")
+                    || e.has_issue("PREVIOUS_REQUEST_IN_PROGRESS")
+            }
+            ResponseError::HttpError(e) => {
+                e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error())
+            }
+            // Paypal sometimes answers with an html page when it's having trouble.
+            ResponseError::Decode { status, .. } => *status >= 500,
+            ResponseError::InvalidLink(_) | ResponseError::IoError(_) | ResponseError::CircuitOpen => false,
+            #[cfg(feature = "vcr")]
+            ResponseError::VcrError(_) => false,
+            #[cfg(feature = "validation")]
+            ResponseError::InvalidRequest(_) => false,
+            #[cfg(feature = "webhooks")]
+            ResponseError::InvalidWebhookRequest(_) => false,
+            #[cfg(feature = "sandbox-testing")]
+            ResponseError::SandboxOnly(_) => false,
+            #[cfg(feature = "orders")]
+            ResponseError::InvalidIdempotencyKey(_) => false,
+        }
+    }
+
+    /// Whether paypal rejected the request because too many requests were made.
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            ResponseError::ApiError(e) => e.status == Some(429) || e.has_issue("RATE_LIMIT_REACHED"),
+            _ => self.status() == Some(429),
+        }
+    }
+
+    /// Whether the request failed because the credentials or the access token are invalid,
+    /// or lack the permissions for this request.
+    pub fn is_auth_error(&self) -> bool {
+        match self {
+            ResponseError::ApiError(e) => {
+                matches!(e.status, Some(401) | Some(403))
+                    || e.has_issue("AUTHENTICATION_FAILURE")
+                    || e.has_issue("NOT_AUTHORIZED")
+                    || e.has_issue("PERMISSION_DENIED")
+                    || matches!(e.error.as_deref(), Some("invalid_client") | Some("invalid_token"))
+            }
+            _ => matches!(self.status(), Some(401) | Some(403)),
+        }
+    }
+
+    /// Whether the buyer can fix the error, for example by choosing another card,
+    /// so the checkout should be restarted instead of failing.
+    ///
+    /// See [BUYER_ACTIONABLE_ISSUES].
+    pub fn is_buyer_actionable(&self) -> bool {
+        match self {
+            ResponseError::ApiError(e) => BUYER_ACTIONABLE_ISSUES.iter().any(|code| e.has_issue(code)),
+            _ => false,
+        }
+    }
+}
+
+/// How strictly response bodies are deserialized.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum DecodeMode {
+    /// Unknown fields are ignored and unknown enum values are kept in their `Unknown` variant,
+    /// so changes on paypal's side don't break a running application.
+    #[default]
+    Lenient,
+    /// Unknown fields and unknown enum values are errors, useful in tests to detect when
+    /// paypal's responses no longer match the types of this crate.
+    Strict,
+}
+
+thread_local! {
+    static STRICT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the response being deserialized on this thread must not contain unknown enum values.
+#[allow(dead_code)] // Only called by the string enums of the enabled apis.
+pub(crate) fn is_strict() -> bool {
+    STRICT.with(Cell::get)
+}
+
+/// Deserializes the fields of a response struct this crate doesn't know about, which are only allowed when decoding
+/// leniently. The unknown fields of flattened maps are not reported by [from_slice] otherwise.
+#[cfg(any(feature = "orders", feature = "webhooks"))]
+pub(crate) fn deserialize_extra<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, serde_json::Value>, D::Error> {
+    let extra = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    match extra.keys().next() {
+        Some(field) if is_strict() => Err(serde::de::Error::custom(format!("unknown field `{}`", field))),
+        _ => Ok(extra),
+    }
+}
+
+/// Marks this thread as deserializing strictly until dropped.
+struct StrictGuard(bool);
+
+impl StrictGuard {
+    fn enter() -> Self {
+        StrictGuard(STRICT.with(|strict| strict.replace(true)))
+    }
+}
+
+impl Drop for StrictGuard {
+    fn drop(&mut self) {
+        STRICT.with(|strict| strict.set(self.0));
+    }
+}
+
+/// Deserializes json with the given mode.
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8], mode: DecodeMode) -> Result<T, serde_json::Error> {
+    match mode {
+        DecodeMode::Lenient => serde_json::from_slice(bytes),
+        DecodeMode::Strict => {
+            let _guard = StrictGuard::enter();
+            let mut unknown = Vec::new();
+            let mut de = serde_json::Deserializer::from_slice(bytes);
+            let value = serde_ignored::deserialize(&mut de, |path| unknown.push(path.to_string()))?;
+            de.end()?;
+
+            match unknown.first() {
+                Some(path) => Err(serde::de::Error::custom(format!("unknown field `{}`", path))),
+                None => Ok(value),
+            }
         }
     }
 }
 
+/// Reads the body of a response and deserializes it from json,
+/// keeping the raw body in the error if it can't be deserialized.
+pub(crate) async fn decode<T: DeserializeOwned>(res: reqwest::Response, mode: DecodeMode) -> Result<T, ResponseError> {
+    let status = res.status().as_u16();
+    let bytes = res.bytes().await?;
+
+    from_slice(&bytes, mode).map_err(|source| {
+        let mut end = bytes.len().min(DECODE_BODY_LIMIT);
+        let body = loop {
+            // Don't cut an utf-8 character in half.
+            match std::str::from_utf8(&bytes[..end]) {
+                Ok(body) => break body.to_owned(),
+                Err(e) if e.error_len().is_none() => end = e.valid_up_to(),
+                Err(_) => break String::from_utf8_lossy(&bytes[..end]).into_owned(),
+            }
+        };
+        ResponseError::Decode { status, body, source }
+    })
+}
+
 // Implemented so we can use ? directly on it.
 impl From<PaypalError> for ResponseError {
     fn from(e: PaypalError) -> Self {
@@ -73,6 +375,13 @@ impl From<reqwest::Error> for ResponseError {
     }
 }
 
+// Implemented so we can use ? directly on it.
+impl From<std::io::Error> for ResponseError {
+    fn from(e: std::io::Error) -> Self {
+        ResponseError::IoError(e)
+    }
+}
+
 /// When a currency is invalid.
 #[derive(Debug)]
 pub struct InvalidCurrencyError(pub String);
@@ -85,6 +394,136 @@ impl fmt::Display for InvalidCurrencyError {
 
 impl Error for InvalidCurrencyError {}
 
+/// When an amount is not a valid number or has more decimal places than its currency supports.
+#[derive(Debug)]
+pub struct InvalidAmountError(pub String);
+
+impl fmt::Display for InvalidAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid amount for its currency", self.0)
+    }
+}
+
+impl Error for InvalidAmountError {}
+
+/// When money can't be computed with, see [Money::checked_add](crate::common::Money::checked_add).
+#[derive(Debug)]
+pub enum MoneyError {
+    /// The two amounts are in different currencies.
+    CurrencyMismatch(Currency, Currency),
+    /// A value is not a valid amount for its currency.
+    InvalidAmount(InvalidAmountError),
+    /// An item quantity is not a whole number.
+    InvalidQuantity(String),
+    /// Only one of the amounts has a breakdown, so the result couldn't have a consistent one.
+    MissingBreakdown,
+    /// The result doesn't fit in a money value.
+    Overflow,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch(a, b) => write!(f, "can't combine amounts in {} and {}", a, b),
+            MoneyError::InvalidAmount(e) => write!(f, "{}", e),
+            MoneyError::InvalidQuantity(quantity) => write!(f, "{:?} is not a valid quantity", quantity),
+            MoneyError::MissingBreakdown => write!(f, "can't combine an amount with a breakdown and one without"),
+            MoneyError::Overflow => write!(f, "the amount is too large"),
+        }
+    }
+}
+
+impl Error for MoneyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MoneyError::InvalidAmount(e) => Some(e),
+            MoneyError::CurrencyMismatch(..)
+            | MoneyError::InvalidQuantity(_)
+            | MoneyError::MissingBreakdown
+            | MoneyError::Overflow => None,
+        }
+    }
+}
+
+impl From<InvalidAmountError> for MoneyError {
+    fn from(e: InvalidAmountError) -> Self {
+        MoneyError::InvalidAmount(e)
+    }
+}
+
+/// When a webhook delivery is rejected as a replay, see [ReplayGuard](crate::replay::ReplayGuard).
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The transmission time is not a valid Internet date and time.
+    InvalidTransmissionTime(String),
+    /// The transmission time is too far from now.
+    OutsideWindow {
+        /// The transmission time of the delivery.
+        transmission_time: chrono::DateTime<chrono::Utc>,
+        /// How far the transmission time is from now.
+        skew: chrono::Duration,
+    },
+    /// The transmission id was already seen.
+    DuplicateTransmission(String),
+    /// The event id was already seen.
+    DuplicateEvent(String),
+    /// The signature of the delivery wasn't verified successfully, so its ids are not recorded.
+    Unverified,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::InvalidTransmissionTime(time) => write!(f, "{:?} is not a valid transmission time", time),
+            ReplayError::OutsideWindow {
+                transmission_time,
+                skew,
+            } => write!(
+                f,
+                "the transmission time {} is {}s away from now",
+                transmission_time,
+                skew.num_seconds()
+            ),
+            ReplayError::DuplicateTransmission(id) => write!(f, "the transmission {} was already received", id),
+            ReplayError::DuplicateEvent(id) => write!(f, "the event {} was already received", id),
+            ReplayError::Unverified => write!(f, "the signature of the delivery was not verified"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/// When a webhook delivery can't be verified, see
+/// [WebhookVerificationPayload::from_request](crate::webhooks::WebhookVerificationPayload::from_request).
+#[cfg(feature = "webhooks")]
+#[derive(Debug)]
+pub enum InvalidWebhookRequestError {
+    /// The given header is missing or not a string.
+    MissingHeader(&'static str),
+    /// The body is not valid json.
+    InvalidBody(serde_json::Error),
+}
+
+#[cfg(feature = "webhooks")]
+impl fmt::Display for InvalidWebhookRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidWebhookRequestError::MissingHeader(header) => write!(f, "the {} header is missing", header),
+            InvalidWebhookRequestError::InvalidBody(e) => write!(f, "the webhook body is not valid json: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "webhooks")]
+impl Error for InvalidWebhookRequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InvalidWebhookRequestError::MissingHeader(_) => None,
+            InvalidWebhookRequestError::InvalidBody(e) => Some(e),
+        }
+    }
+}
+
 /// When a country is invalid.
 #[derive(Debug)]
 pub struct InvalidCountryError(pub String);
@@ -96,3 +535,296 @@ impl fmt::Display for InvalidCountryError {
 }
 
 impl Error for InvalidCountryError {}
+
+/// When a phone number is not in a valid E.164 format.
+#[derive(Debug)]
+pub struct InvalidPhoneNumberError(pub String);
+
+impl fmt::Display for InvalidPhoneNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid phone number", self.0)
+    }
+}
+
+impl Error for InvalidPhoneNumberError {}
+
+/// When the billing cycles or pricing schemes of a subscription plan break the api's rules.
+#[derive(Debug)]
+pub struct InvalidPlanError(pub String);
+
+impl fmt::Display for InvalidPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid plan: {}", self.0)
+    }
+}
+
+impl Error for InvalidPlanError {}
+
+/// When the purchase units of an order break the api's rules.
+#[derive(Debug)]
+pub struct InvalidOrderError(pub String);
+
+impl fmt::Display for InvalidOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid order: {}", self.0)
+    }
+}
+
+impl Error for InvalidOrderError {}
+
+/// When a string is not one of the values of an enum.
+#[derive(Debug)]
+pub struct InvalidVariantError(pub String);
+
+impl fmt::Display for InvalidVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid variant", self.0)
+    }
+}
+
+impl Error for InvalidVariantError {}
+
+/// When a [Client](crate::client::Client) can't be built from the given configuration.
+#[derive(Debug)]
+pub enum ClientBuildError {
+    /// A required field was not set or is empty.
+    MissingField(&'static str),
+    /// The value of the given header is not a valid header value.
+    InvalidHeader(&'static str),
+    /// The name or value of the given default header is not valid.
+    InvalidDefaultHeader(String),
+    /// The given timeout is zero.
+    InvalidTimeout(&'static str),
+    /// The http client could not be created.
+    HttpError(reqwest::Error),
+    /// A root certificate contains no valid PEM encoded certificate.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    InvalidRootCertificate,
+}
+
+impl fmt::Display for ClientBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientBuildError::MissingField(field) => write!(f, "{} is required", field),
+            ClientBuildError::InvalidHeader(header) => write!(f, "invalid value for the {} header", header),
+            ClientBuildError::InvalidDefaultHeader(header) => write!(f, "invalid default header {}", header),
+            ClientBuildError::InvalidTimeout(timeout) => write!(f, "{} must be greater than zero", timeout),
+            ClientBuildError::HttpError(e) => write!(f, "{}", e),
+            #[cfg(any(feature = "rustls", feature = "native-tls"))]
+            ClientBuildError::InvalidRootCertificate => write!(f, "invalid root certificate"),
+        }
+    }
+}
+
+impl Error for ClientBuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientBuildError::HttpError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientBuildError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientBuildError::HttpError(e)
+    }
+}
+
+/// When a [Client](crate::client::Client) can't be configured from the environment or a profile.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The given environment variable is not set.
+    MissingVariable(&'static str),
+    /// The environment is neither `sandbox` nor `live`.
+    InvalidEnvironment(String),
+    /// The config file could not be read.
+    Io(std::io::Error),
+    /// The config file is not valid json, or a profile doesn't have the expected fields.
+    InvalidFile(serde_json::Error),
+    /// The config file has no profile with the given name.
+    UnknownProfile(String),
+    /// The client could not be built from the configuration.
+    Build(ClientBuildError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingVariable(name) => write!(f, "the {} environment variable is not set", name),
+            ConfigError::InvalidEnvironment(environment) => {
+                write!(f, "unknown environment {:?}, use sandbox or live", environment)
+            }
+            ConfigError::Io(e) => write!(f, "can't read the config file: {}", e),
+            ConfigError::InvalidFile(e) => write!(f, "invalid config file: {}", e),
+            ConfigError::UnknownProfile(profile) => write!(f, "no profile named {} in the config file", profile),
+            ConfigError::Build(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::InvalidFile(e) => Some(e),
+            ConfigError::Build(e) => Some(e),
+            ConfigError::MissingVariable(_) | ConfigError::InvalidEnvironment(_) | ConfigError::UnknownProfile(_) => {
+                None
+            }
+        }
+    }
+}
+
+impl From<ClientBuildError> for ConfigError {
+    fn from(e: ClientBuildError) -> Self {
+        ConfigError::Build(e)
+    }
+}
+
+/// When a [ClientPool](crate::pool::ClientPool) can't give the client of a merchant.
+#[derive(Debug)]
+pub enum PoolError {
+    /// There are no credentials for the given merchant.
+    UnknownMerchant(String),
+    /// The client of the merchant could not be built.
+    Build(ClientBuildError),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::UnknownMerchant(merchant_id) => write!(f, "no credentials for merchant {}", merchant_id),
+            PoolError::Build(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for PoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PoolError::Build(e) => Some(e),
+            PoolError::UnknownMerchant(_) => None,
+        }
+    }
+}
+
+impl From<ClientBuildError> for PoolError {
+    fn from(e: ClientBuildError) -> Self {
+        PoolError::Build(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: u16, json: &str) -> ResponseError {
+        let mut e: PaypalError = serde_json::from_str(json).unwrap();
+        e.status = Some(status);
+        ResponseError::ApiError(e)
+    }
+
+    #[test]
+    fn test_classification() {
+        let declined = api_error(
+            422,
+            r#"{"name":"UNPROCESSABLE_ENTITY","details":[{"issue":"INSTRUMENT_DECLINED","description":"The instrument presented was either declined by the processor or bank."}],"links":[]}"#,
+        );
+        assert!(declined.is_buyer_actionable());
+        assert!(!declined.is_retryable());
+        assert!(!declined.is_auth_error());
+
+        let auth = api_error(
+            401,
+            r#"{"error":"invalid_client","error_description":"Client Authentication failed"}"#,
+        );
+        assert!(auth.is_auth_error());
+        assert!(!auth.is_retryable());
+
+        let limited = api_error(429, r#"{"name":"RATE_LIMIT_REACHED","links":[]}"#);
+        assert!(limited.is_rate_limited());
+        assert!(limited.is_retryable());
+
+        let internal = api_error(500, r#"{"name":"INTERNAL_SERVER_ERROR","links":[]}"#);
+        assert!(internal.is_retryable());
+        assert!(!internal.is_buyer_actionable());
+    }
+
+    #[cfg(feature = "orders")]
+    #[test]
+    fn test_decode_mode() {
+        use crate::orders::{Order, OrderStatus};
+
+        let order: Order = from_slice(
+            br#"{"id":"5O190127TN364715T","status":"COMPLETED","links":[]}"#,
+            DecodeMode::Strict,
+        )
+        .unwrap();
+        assert_eq!(order.status, OrderStatus::Completed);
+
+        let new_field = br#"{"id":"5O190127TN364715T","status":"COMPLETED","links":[],"new_field":true}"#;
+        let order: Order = from_slice(new_field, DecodeMode::Lenient).unwrap();
+        assert_eq!(order.extra["new_field"], serde_json::Value::Bool(true));
+        assert!(serde_json::to_string(&order).unwrap().contains(r#""new_field":true"#));
+        let e = from_slice::<Order>(new_field, DecodeMode::Strict).unwrap_err();
+        assert!(e.to_string().contains("new_field"));
+
+        let new_status = br#"{"id":"5O190127TN364715T","status":"SOMETHING_NEW","links":[]}"#;
+        let order: Order = from_slice(new_status, DecodeMode::Lenient).unwrap();
+        assert_eq!(order.status, OrderStatus::Unknown("SOMETHING_NEW".to_owned()));
+        assert!(from_slice::<Order>(new_status, DecodeMode::Strict).is_err());
+        assert!(!is_strict());
+    }
+
+    #[test]
+    fn test_decode_mode_fallbacks() {
+        use crate::common::{Address, Currency, LinkDescription, LinkRel, Money};
+        use crate::countries::Country;
+
+        let money = br#"{"currency_code":"XYZ","value":"1.00"}"#;
+        let lenient: Money = from_slice(money, DecodeMode::Lenient).unwrap();
+        assert_eq!(lenient.currency_code, Currency::Other("XYZ".to_owned()));
+        let e = from_slice::<Money>(money, DecodeMode::Strict).unwrap_err();
+        assert!(e.to_string().contains("unknown variant `XYZ`"));
+        assert_eq!("XYZ".parse::<Currency>().unwrap(), Currency::Other("XYZ".to_owned()));
+        assert!("xyz".parse::<Currency>().is_err());
+
+        let address = br#"{"country_code":"XK"}"#;
+        let lenient: Address = from_slice(address, DecodeMode::Lenient).unwrap();
+        assert_eq!(lenient.country_code, Country::Other("XK".to_owned()));
+        assert!(from_slice::<Address>(address, DecodeMode::Strict).is_err());
+        assert!("XKX".parse::<Country>().is_err());
+
+        let link = br#"{"href":"https://api-m.paypal.com/v1/notifications/webhooks","rel":"webhooks"}"#;
+        let lenient: LinkDescription = from_slice(link, DecodeMode::Lenient).unwrap();
+        assert_eq!(lenient.rel, Some(LinkRel::Other("webhooks".to_owned())));
+        assert!(from_slice::<LinkDescription>(link, DecodeMode::Strict).is_err());
+
+        #[cfg(feature = "webhooks")]
+        {
+            use crate::webhooks::EventType;
+
+            let event_type = br#""CUSTOMER.DISPUTE.CREATED""#;
+            let lenient: EventType = from_slice(event_type, DecodeMode::Lenient).unwrap();
+            assert_eq!(lenient, EventType::Other("CUSTOMER.DISPUTE.CREATED".to_owned()));
+            let e = from_slice::<EventType>(event_type, DecodeMode::Strict).unwrap_err();
+            assert!(e.to_string().contains("unknown variant `CUSTOMER.DISPUTE.CREATED`"));
+        }
+        assert!(!is_strict());
+    }
+
+    #[tokio::test]
+    async fn test_decode_keeps_body() {
+        let mut res = http::Response::new("<html>Bad Gateway</html>");
+        *res.status_mut() = reqwest::StatusCode::BAD_GATEWAY;
+
+        match ResponseError::from_response(res.into()).await {
+            ResponseError::Decode { status, body, .. } => {
+                assert_eq!(status, 502);
+                assert_eq!(body, "<html>Bad Gateway</html>");
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Persistence of idempotency keys, so a request retried later doesn't create a second resource.
+//!
+//! PayPal only remembers a `PayPal-Request-Id` for a few hours, and a process that crashed between sending a request
+//! and saving its result doesn't know it was sent at all. An [OrderKeyStore] keeps the order created for each key
+//! on the application side, see [Client::create_order_idempotent](crate::client::Client::create_order_idempotent).
+
+use futures_util::future::BoxFuture;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// The longest idempotency key paypal accepts as a `PayPal-Request-Id`.
+pub const MAX_KEY_LENGTH: usize = 108;
+
+/// Stores the ID of the order created for each idempotency key.
+///
+/// Use a store that outlives the process, like a table of the application's database, to be safe across restarts.
+/// A failing store should behave as if it had no order for the key, the request id still avoids duplicates then.
+pub trait OrderKeyStore: Debug + Send + Sync {
+    /// Returns the ID of the order created for the given key.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<String>>;
+
+    /// Stores the ID of the order created for the given key.
+    fn put<'a>(&'a self, key: &'a str, order_id: &'a str) -> BoxFuture<'a, ()>;
+}
+
+impl<T: OrderKeyStore + ?Sized> OrderKeyStore for Arc<T> {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<String>> {
+        (**self).get(key)
+    }
+
+    fn put<'a>(&'a self, key: &'a str, order_id: &'a str) -> BoxFuture<'a, ()> {
+        (**self).put(key, order_id)
+    }
+}
+
+/// Keeps the order IDs in memory, it only protects against duplicates within the process.
+#[derive(Debug, Default)]
+pub struct MemoryOrderKeyStore {
+    orders: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryOrderKeyStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl OrderKeyStore for MemoryOrderKeyStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<String>> {
+        let order_id = self.orders.lock().unwrap().get(key).cloned();
+        Box::pin(async move { order_id })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, order_id: &'a str) -> BoxFuture<'a, ()> {
+        self.orders.lock().unwrap().insert(key.to_owned(), order_id.to_owned());
+        Box::pin(async {})
+    }
+}
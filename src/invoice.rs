@@ -7,7 +7,7 @@
 //! Reference: https://developer.paypal.com/docs/api/invoicing/v2/
 
 use crate::common::*;
-use crate::errors::{PaypalError, ResponseError};
+use crate::errors::{decode, InvalidPhoneNumberError, ResponseError};
 use crate::client::HeaderParams;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
@@ -54,6 +54,18 @@ pub enum PaymentTermType {
     NoDueDate,
 }
 
+display_from_str!(PaymentTermType {
+    DueOnReceipt = "DUE_ON_RECEIPT",
+    DueOnDateSpecified = "DUE_ON_DATE_SPECIFIED",
+    Net10 = "NET10",
+    Net15 = "NET15",
+    Net30 = "NET30",
+    Net45 = "NET45",
+    Net60 = "NET60",
+    Net90 = "NET90",
+    NoDueDate = "NO_DUE_DATE",
+});
+
 /// The payment due date for the invoice.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaymentTerm {
@@ -76,6 +88,12 @@ pub enum FlowType {
     RegularSingle,
 }
 
+display_from_str!(FlowType {
+    MultipleRecipientsGroup = "MULTIPLE_RECIPIENTS_GROUP",
+    Batch = "BATCH",
+    RegularSingle = "REGULAR_SINGLE",
+});
+
 /// Metadata about a resource
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -132,6 +150,13 @@ pub struct InvoiceDetail {
     pub metadata: Option<Metadata>,
 }
 
+impl InvoiceDetail {
+    /// Attaches an uploaded file to the invoice, see [Client::upload_invoice_attachment].
+    pub fn attach(&mut self, file: FileReference) {
+        self.attachments.get_or_insert_with(Vec::new).push(file);
+    }
+}
+
 /// A name to be used as recipient, etc.
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -159,16 +184,26 @@ pub struct Name {
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct PhoneDetail {
-    /// The country calling code (CC), in its canonical international E.164 numbering plan format.
-    pub country_code: String,
-    /// The national number, in its canonical international E.164 numbering plan format.
-    pub national_number: String,
+    /// The phone number.
+    #[serde(flatten)]
+    pub phone: PhoneWithCountryCode,
     /// The extension number.
     pub extension_number: Option<String>,
     /// The phone type.
     pub phone_type: Option<PhoneType>,
 }
 
+impl PhoneDetail {
+    /// Parses an international phone number starting with a `+`, splitting off its country calling code.
+    pub fn parse(phone_type: Option<PhoneType>, number: &str) -> Result<Self, InvalidPhoneNumberError> {
+        Ok(PhoneDetail {
+            phone: PhoneWithCountryCode::parse(number)?,
+            extension_number: None,
+            phone_type,
+        })
+    }
+}
+
 /// The invoicer information.
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -267,6 +302,12 @@ pub enum UnitOfMeasure {
     Amount,
 }
 
+display_from_str!(UnitOfMeasure {
+    Quantity = "QUANTITY",
+    Hours = "HOURS",
+    Amount = "AMOUNT",
+});
+
 /// Item information
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
@@ -389,6 +430,27 @@ impl Amount {
             breakdown: None,
         }
     }
+
+    /// Creates a new amount from a decimal, formatted with the number of decimal places the currency uses.
+    ///
+    /// Fails if the value has more significant decimal places than the currency supports.
+    #[cfg(feature = "decimal")]
+    pub fn from_decimal(
+        currency_code: Currency,
+        value: rust_decimal::Decimal,
+    ) -> Result<Self, crate::errors::InvalidAmountError> {
+        Ok(Amount {
+            value: format_decimal(&currency_code, value)?,
+            currency_code,
+            breakdown: None,
+        })
+    }
+
+    /// The value as a decimal.
+    #[cfg(feature = "decimal")]
+    pub fn decimal(&self) -> Result<rust_decimal::Decimal, crate::errors::InvalidAmountError> {
+        parse_decimal(&self.value)
+    }
 }
 
 /// The payment type in an invoicing flow
@@ -401,8 +463,13 @@ pub enum PaymentType {
     External,
 }
 
+display_from_str!(PaymentType {
+    Paypal = "PAYPAL",
+    External = "EXTERNAL",
+});
+
 /// The payment mode or method through which the invoicer can accept the payment.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PaymentMethod {
     /// Payments can be received through bank transfers.
@@ -416,6 +483,7 @@ pub enum PaymentMethod {
     /// Payments can be received through debit card payments.
     DebitCard,
     /// Payments can be received through paypal payments.
+    #[default]
     Paypal,
     /// Payments can be received through wire transfer.
     WireTransfer,
@@ -423,11 +491,16 @@ pub enum PaymentMethod {
     Other,
 }
 
-impl Default for PaymentMethod {
-    fn default() -> Self {
-        PaymentMethod::Paypal
-    }
-}
+display_from_str!(PaymentMethod {
+    BankTransfer = "BANK_TRANSFER",
+    Cash = "CASH",
+    Check = "CHECK",
+    CreditCard = "CREDIT_CARD",
+    DebitCard = "DEBIT_CARD",
+    Paypal = "PAYPAL",
+    WireTransfer = "WIRE_TRANSFER",
+    Other = "OTHER",
+});
 
 /// Payment detail
 #[skip_serializing_none]
@@ -489,34 +562,34 @@ pub struct Refunds {
     pub transactions: Option<Vec<RefundDetail>>,
 }
 
-/// The status of the invoice
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum Status {
-    ///  The invoice is in draft state. It is not yet sent to the payer.
-    Draft,
-    /// The invoice has been sent to the payer. The payment is awaited from the payer.
-    Sent,
-    /// The invoice is scheduled on a future date. It is not yet sent to the payer.
-    Scheduled,
-    /// The payer has paid for the invoice.
-    Paid,
-    /// The invoice is marked as paid by the invoicer.
-    MarkedAsPaid,
-    /// The invoice has been cancelled by the invoicer.
-    Cancelled,
-    /// The invoice has been refunded by the invoicer.
-    Refunded,
-    /// The payer has partially paid for the invoice.
-    PartiallyPaid,
-    /// The invoice has been partially refunded by the invoicer.
-    PartiallyRefunded,
-    /// The invoice is marked as refunded by the invoicer.
-    MarkedAsRefunded,
-    /// The invoicer is yet to receive the payment from the payer for the invoice.
-    Unpaid,
-    /// The invoicer is yet to receive the payment for the invoice. It is under pending review.
-    PaymentPending,
+string_enum! {
+    /// The status of the invoice
+    pub enum Status {
+        ///  The invoice is in draft state. It is not yet sent to the payer.
+        Draft = "DRAFT",
+        /// The invoice has been sent to the payer. The payment is awaited from the payer.
+        Sent = "SENT",
+        /// The invoice is scheduled on a future date. It is not yet sent to the payer.
+        Scheduled = "SCHEDULED",
+        /// The payer has paid for the invoice.
+        Paid = "PAID",
+        /// The invoice is marked as paid by the invoicer.
+        MarkedAsPaid = "MARKED_AS_PAID",
+        /// The invoice has been cancelled by the invoicer.
+        Cancelled = "CANCELLED",
+        /// The invoice has been refunded by the invoicer.
+        Refunded = "REFUNDED",
+        /// The payer has partially paid for the invoice.
+        PartiallyPaid = "PARTIALLY_PAID",
+        /// The invoice has been partially refunded by the invoicer.
+        PartiallyRefunded = "PARTIALLY_REFUNDED",
+        /// The invoice is marked as refunded by the invoicer.
+        MarkedAsRefunded = "MARKED_AS_REFUNDED",
+        /// The invoicer is yet to receive the payment from the payer for the invoice.
+        Unpaid = "UNPAID",
+        /// The invoicer is yet to receive the payment for the invoice. It is under pending review.
+        PaymentPending = "PAYMENT_PENDING",
+    }
 }
 
 /// An invoice payload
@@ -662,13 +735,13 @@ impl Client {
             )
             .await;
 
-        let res = build.send().await?;
+        let res = self.execute(build).await?;
 
         if res.status().is_success() {
-            let x = res.json::<HashMap<String, String>>().await?;
+            let x = decode::<HashMap<String, String>>(res, self.decode_mode).await?;
             Ok(x.get("invoice_number").expect("to have a invoice number").clone())
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -687,14 +760,14 @@ impl Client {
             )
             .await;
 
-        let res = build.json(&invoice).send().await?;
+        let res = self.execute(build.json(&invoice)).await?;
 
         if res.status().is_success() {
             //println!("{:#?}", res.text().await?);
-            let inv = res.json::<Invoice>().await?;
+            let inv = decode::<Invoice>(res, self.decode_mode).await?;
             Ok(inv)
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -712,13 +785,13 @@ impl Client {
             )
             .await;
 
-        let res = build.send().await?;
+        let res = self.execute(build).await?;
 
         if res.status().is_success() {
-            let x = res.json::<Invoice>().await?;
+            let x = decode::<Invoice>(res, self.decode_mode).await?;
             Ok(x)
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -745,13 +818,13 @@ impl Client {
             )
             .await;
 
-        let res = build.send().await?;
+        let res = self.execute(build).await?;
 
         if res.status().is_success() {
-            let x = res.json::<InvoiceList>().await?;
+            let x = decode::<InvoiceList>(res, self.decode_mode).await?;
             Ok(x)
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -765,12 +838,12 @@ impl Client {
             )
             .await;
 
-        let res = build.send().await?;
+        let res = self.execute(build).await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -798,12 +871,12 @@ impl Client {
             )
             .await;
 
-        let res = build.send().await?;
+        let res = self.execute(build).await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -822,12 +895,12 @@ impl Client {
             )
             .await;
 
-        let res = build.json(&reason).send().await?;
+        let res = self.execute(build.json(&reason)).await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -852,13 +925,13 @@ impl Client {
             )
             .await;
 
-        let res = build.json(&params).send().await?;
+        let res = self.execute(build.json(&params)).await?;
 
         if res.status().is_success() {
             let b = res.bytes().await?;
             Ok(b)
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -877,13 +950,46 @@ impl Client {
             )
             .await;
 
-        let res = build.json(&payload).send().await?;
+        let res = self.execute(build.json(&payload)).await?;
 
         if res.status().is_success() {
-            let x = res.json::<HashMap<String, String>>().await?;
+            let x = decode::<HashMap<String, String>>(res, self.decode_mode).await?;
             Ok(x.get("payment_id").unwrap().to_owned())
         } else {
-            Err(res.json::<PaypalError>().await?.into())
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Uploads a file, like the PDF of a contract or a receipt, to attach to invoices.
+    ///
+    /// Add the returned reference to the invoice detail with [InvoiceDetail::attach] before creating or updating it.
+    pub async fn upload_invoice_attachment(
+        &mut self,
+        file_name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        header_params: HeaderParams,
+    ) -> Result<FileReference, ResponseError> {
+        let part = reqwest::multipart::Part::bytes(data)
+            .file_name(file_name.to_owned())
+            .mime_str(content_type)?;
+        let form = reqwest::multipart::Form::new().part("file", part);
+
+        let build = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v1/invoicing/files", self.endpoint()).as_str()),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build.multipart(form)).await?;
+
+        if res.status().is_success() {
+            let file = decode::<FileReference>(res, self.decode_mode).await?;
+            Ok(file)
+        } else {
+            Err(ResponseError::from_response(res).await)
         }
     }
 
@@ -892,16 +998,14 @@ impl Client {
 
 #[cfg(test)]
 mod tests {
-    use crate::{invoice::*, client::Client, client::HeaderParams};
+    use crate::{client::Client, client::HeaderParams};
 
     async fn create_client() -> Client {
         dotenv::dotenv().ok();
         let clientid = std::env::var("PAYPAL_CLIENTID").unwrap();
         let secret = std::env::var("PAYPAL_SECRET").unwrap();
 
-        let client = Client::new(clientid, secret, true);
-
-        client
+        Client::new(clientid, secret, true)
     }
 
     #[tokio::test]
@@ -912,4 +1016,35 @@ mod tests {
 
         println!("{:#?}", list);
     }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_upload_attachment() {
+        use super::InvoiceDetail;
+        use crate::vcr::{interaction, replay_cassette, token_interaction};
+
+        let interactions = [
+            token_interaction(),
+            // The multipart body is streamed, so it's hashed as empty.
+            interaction("POST", "/v1/invoicing/files", b"", 201, r#"{"id":"FA-7BL79389MD960621S","reference_url":"https://www.sandbox.paypal.com/invoice/payerView/attachments/RkEtN0JMNzkzODlNRDk2MDYyMVM=","content_type":"application/pdf","create_time":"2022-08-23T18:29:50Z","size":"1024"}"#),
+        ];
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(replay_cassette(&interactions));
+
+        let file = client
+            .upload_invoice_attachment(
+                "contract.pdf",
+                "application/pdf",
+                b"%PDF-1.4".to_vec(),
+                HeaderParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(file.id, "FA-7BL79389MD960621S");
+
+        let mut detail = InvoiceDetail::default();
+        detail.attach(file);
+        assert_eq!(detail.attachments.unwrap().len(), 1);
+    }
 }
@@ -19,6 +19,7 @@
 //! ```rust
 //! use paypal_rs::{
 //!     Client,
+//!     Environment,
 //!     HeaderParams,
 //!     Prefer,
 //!     orders::{OrderPayload, Intent, PurchaseUnit, Amount},
@@ -31,7 +32,12 @@
 //!     let clientid = std::env::var("PAYPAL_CLIENTID").unwrap();
 //!     let secret = std::env::var("PAYPAL_SECRET").unwrap();
 //!
-//!     let mut client = Client::new(clientid, secret, true);
+//!     let mut client = Client::builder()
+//!         .client_id(clientid)
+//!         .secret(secret)
+//!         .environment(Environment::Sandbox)
+//!         .build()
+//!         .unwrap();
 //!
 //!     client.get_access_token().await.unwrap();
 //!
@@ -50,8 +56,31 @@
 //! }
 //! ```
 //!
+//! [Client::from_env] and [Client::from_profile] build a client from the `PAYPAL_CLIENT_ID`, `PAYPAL_SECRET` and
+//! `PAYPAL_ENV` environment variables or a profile of a config file instead, see the [config] module.
+//!
+//! ## Features
+//!
+//! Each api has its own feature, all enabled by default. Integrations only using some of them can disable the
+//! default features to compile less code:
+//!
+//! ```toml
+//! paypal-rs = { version = "*", default-features = false, features = ["orders", "webhooks"] }
+//! ```
+//!
+//! The features are `orders`, `payments`, `subscriptions`, `invoicing`, `payouts`, `disputes`, `webhooks`,
+//! `billing-agreements` and `transaction-search`.
+//!
+//! The `gzip` and `brotli` features, disabled by default, request compressed responses. The `sandbox-testing`
+//! feature adds the sandbox only endpoints moving disputes through their lifecycle, for end-to-end tests.
+//!
+//! Connections use rustls with the bundled Mozilla root certificates through the default `rustls` feature, which
+//! works in distroless containers. Enable `native-tls` to use the platform's TLS library and certificate store
+//! instead. Without either feature the client can't connect to paypal.
+//!
 //! ## Testing
 //! You need the enviroment variables PAYPAL_CLIENTID and PAYPAL_SECRET to be set.
+//! PAYPAL_CLIENTID is the deprecated name of PAYPAL_CLIENT_ID, which [Client::from_env] reads first.
 //!
 //! `cargo test`
 //!
@@ -65,17 +94,27 @@
 //! - - [x] Capture payment for order
 //! - [ ] Invoicing API - 0.2.0
 //! - [ ] Payments API - 0.3.0
+//! - - [x] Billing agreements
 //! - [ ] Tracking API - 0.4.0
 //! - [ ] Subscriptions API - 0.5.0
+//! - - [x] Create plan
 //! - [ ] Identity API - 0.6.0
 //! - [ ] Disputes API - 0.7.0
+//! - - [x] Show dispute details
+//! - - [x] Download dispute documents
 //! - [ ] Catalog Products API - 0.8.0
 //! - [ ] Partner Referrals API - 0.9.0
 //! - [ ] Payouts API - 0.10.0
+//! - - [x] Create batch payout
+//! - - [x] Show payout batch details
 //! - [ ] Transaction Search API - 0.11.0
+//! - - [x] List transactions
 //! - [ ] Referenced Payouts API - 0.12.0
 //! - [ ] Vault API - 0.13.0
+//! - - [x] Payment token and credit card webhook resources
 //! - [ ] Webhooks Management API - 0.14.0
+//! - - [x] List webhooks
+//! - - [x] Simulate webhook event
 //! - [ ] Payment Experience Web Profiles API - 1.0.0
 
 #![deny(missing_docs)]
@@ -83,8 +122,51 @@
 pub mod common;
 pub mod countries;
 pub mod client;
+pub mod config;
 pub mod errors;
+#[cfg(feature = "invoicing")]
 pub mod invoice;
+pub mod middleware;
+#[cfg(feature = "orders")]
 pub mod orders;
+pub mod retry;
+pub mod rate_limit;
+pub mod circuit_breaker;
+#[cfg(feature = "webhooks")]
 pub mod webhooks;
-pub mod capture;
\ No newline at end of file
+#[cfg(feature = "webhooks")]
+pub mod replay;
+#[cfg(feature = "payments")]
+pub mod capture;
+#[cfg(feature = "billing-agreements")]
+pub mod billing_agreements;
+#[cfg(feature = "payments")]
+pub mod batch;
+pub mod token_store;
+#[cfg(feature = "orders")]
+pub mod idempotency;
+pub mod pool;
+#[cfg(feature = "orders")]
+pub mod checkout;
+#[cfg(feature = "orders")]
+pub mod marketplace;
+#[cfg(feature = "disputes")]
+pub mod disputes;
+#[cfg(feature = "payouts")]
+pub mod payouts;
+#[cfg(feature = "transaction-search")]
+pub mod transaction_search;
+#[cfg(feature = "subscriptions")]
+pub mod subscriptions;
+#[cfg(feature = "orders")]
+pub mod vault;
+#[cfg(feature = "vcr")]
+pub mod vcr;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "testutils")]
+pub mod testutils;
+#[cfg(feature = "validation")]
+pub mod validation;
+
+pub use client::{Client, ClientBuilder, Environment, HeaderParams, Prefer, Query};
@@ -0,0 +1,397 @@
+//! Orders paying several sellers at once, for PayPal Commerce Platform marketplaces.
+//!
+//! A [MarketplaceOrder] turns a [SellerUnit] per seller into a purchase unit paying that seller, with the amount and
+//! its breakdown computed from the items, and the platform fee the marketplace keeps. The purchase units are checked
+//! with [validate_purchase_units] before the order is built, so an inconsistent order fails right away instead of
+//! being rejected by paypal with an `UNPROCESSABLE_ENTITY` error.
+//!
+//! Later operations on the payments of a seller, like refunding a capture, are made on behalf of the seller with the
+//! headers from [on_behalf_of].
+//!
+//! ```
+//! use paypal_rs::common::{Currency, Money};
+//! use paypal_rs::marketplace::{MarketplaceOrder, SellerUnit};
+//! use paypal_rs::orders::{Intent, Item, Payee};
+//!
+//! let eur = |value: &str| Money { currency_code: Currency::EUR, value: value.to_owned() };
+//! let seller = |merchant_id: &str| Payee { merchant_id: Some(merchant_id.to_owned()), email_address: None };
+//!
+//! let order = MarketplaceOrder::new(Intent::Capture)
+//!     .seller(SellerUnit::new("mugs", seller("7KNGBPH2U58GQ"), vec![Item::new("Mug", eur("12.50"), 2)]).platform_fee(eur("2.50")))
+//!     .seller(SellerUnit::new("tea", seller("2FLXRJ5TQ9V8S"), vec![Item::new("Green tea", eur("8.00"), 1)]).shipping_cost(eur("4.90")))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(order.purchase_units[0].amount.value, "25.00");
+//! assert_eq!(order.purchase_units[1].amount.value, "12.90");
+//! ```
+
+use crate::common::{Currency, Money};
+use crate::errors::InvalidOrderError;
+use crate::orders::{
+    Amount, Breakdown, DisbursementMode, Intent, Item, OrderPayload, Payee, PaymentInstruction, PlatformFee,
+    PurchaseUnit,
+};
+use crate::HeaderParams;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// The part of a marketplace order paid to one seller.
+#[derive(Debug)]
+pub struct SellerUnit {
+    /// The id of the purchase unit, unique within the order.
+    pub reference_id: String,
+    /// The seller receiving the payment.
+    pub payee: Payee,
+    /// The items bought from the seller, all in the currency of the order.
+    pub items: Vec<Item>,
+    /// The shipping cost charged by the seller.
+    pub shipping_cost: Option<Money>,
+    /// The fee kept by the marketplace out of the payment to the seller.
+    pub platform_fee: Option<Money>,
+    /// The purchase description.
+    pub description: Option<String>,
+    /// The API caller-provided external ID, appearing in transaction and settlement reports.
+    pub custom_id: Option<String>,
+    /// The API caller-provided external invoice ID for this order.
+    pub invoice_id: Option<String>,
+}
+
+impl SellerUnit {
+    /// Creates a seller unit with the required properties.
+    pub fn new<S: Into<String>>(reference_id: S, payee: Payee, items: Vec<Item>) -> Self {
+        SellerUnit {
+            reference_id: reference_id.into(),
+            payee,
+            items,
+            shipping_cost: None,
+            platform_fee: None,
+            description: None,
+            custom_id: None,
+            invoice_id: None,
+        }
+    }
+
+    /// Sets the shipping cost charged by the seller.
+    pub fn shipping_cost(mut self, shipping_cost: Money) -> Self {
+        self.shipping_cost = Some(shipping_cost);
+        self
+    }
+
+    /// Sets the fee kept by the marketplace.
+    pub fn platform_fee(mut self, platform_fee: Money) -> Self {
+        self.platform_fee = Some(platform_fee);
+        self
+    }
+
+    /// Builds the purchase unit, computing its amount from the items, their tax and the shipping cost.
+    pub fn into_purchase_unit(
+        self,
+        disbursement_mode: Option<DisbursementMode>,
+    ) -> Result<PurchaseUnit, InvalidOrderError> {
+        let invalid = |message: String| InvalidOrderError(format!("purchase unit {}: {}", self.reference_id, message));
+        if self.items.is_empty() {
+            return Err(invalid("a seller unit needs at least one item".to_owned()));
+        }
+        let mut breakdown = Breakdown::from_items(&self.items).map_err(|e| invalid(e.to_string()))?;
+        breakdown.shipping = self.shipping_cost.clone();
+        let total = match breakdown.total().map_err(|e| invalid(e.to_string()))? {
+            Some(total) => total,
+            None => return Err(invalid("the amount has no breakdown".to_owned())),
+        };
+
+        let payment_instruction = match (self.platform_fee, disbursement_mode) {
+            (None, None) => None,
+            (platform_fee, disbursement_mode) => Some(PaymentInstruction {
+                platform_fees: platform_fee.map(|amount| vec![PlatformFee { amount, payee: None }]),
+                disbursement_mode,
+            }),
+        };
+
+        Ok(PurchaseUnit {
+            reference_id: Some(self.reference_id),
+            amount: Amount {
+                currency_code: total.currency_code,
+                value: total.value,
+                breakdown: Some(breakdown),
+            },
+            payee: Some(self.payee),
+            payment_instruction,
+            description: self.description,
+            custom_id: self.custom_id,
+            invoice_id: self.invoice_id,
+            items: Some(self.items),
+            ..Default::default()
+        })
+    }
+}
+
+/// Builds an order paying several sellers, see the [module documentation](self).
+#[derive(Debug)]
+pub struct MarketplaceOrder {
+    intent: Intent,
+    sellers: Vec<SellerUnit>,
+    disbursement_mode: Option<DisbursementMode>,
+}
+
+impl MarketplaceOrder {
+    /// Creates an order without sellers.
+    pub fn new(intent: Intent) -> Self {
+        MarketplaceOrder {
+            intent,
+            sellers: Vec::new(),
+            disbursement_mode: None,
+        }
+    }
+
+    /// Adds the purchase unit of a seller.
+    pub fn seller(mut self, seller: SellerUnit) -> Self {
+        self.sellers.push(seller);
+        self
+    }
+
+    /// Sets when the sellers get their funds, for every purchase unit.
+    pub fn disbursement_mode(mut self, disbursement_mode: DisbursementMode) -> Self {
+        self.disbursement_mode = Some(disbursement_mode);
+        self
+    }
+
+    /// Builds the order payload, after checking its purchase units with [validate_purchase_units].
+    pub fn build(self) -> Result<OrderPayload, InvalidOrderError> {
+        let disbursement_mode = self.disbursement_mode;
+        let purchase_units = self
+            .sellers
+            .into_iter()
+            .map(|seller| seller.into_purchase_unit(disbursement_mode.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        validate_purchase_units(&purchase_units)?;
+        Ok(OrderPayload::new(self.intent, purchase_units))
+    }
+}
+
+/// Checks the purchase units of an order against the rules of the api.
+///
+/// Every purchase unit must be in the same currency, with a positive amount equal to the sum of its breakdown,
+/// an item total and tax total matching its items, and platform fees not above the amount. When there are several
+/// purchase units, each one needs its own reference id and a payee.
+pub fn validate_purchase_units(purchase_units: &[PurchaseUnit]) -> Result<(), InvalidOrderError> {
+    let first = purchase_units
+        .first()
+        .ok_or_else(|| InvalidOrderError("an order needs at least one purchase unit".to_owned()))?;
+    let currency = &first.amount.currency_code;
+    let mut reference_ids = HashSet::new();
+    for unit in purchase_units {
+        let reference_id = unit.reference_id.as_deref().unwrap_or("default");
+        let invalid = |message: String| InvalidOrderError(format!("purchase unit {}: {}", reference_id, message));
+        if purchase_units.len() > 1 {
+            match unit.reference_id.as_deref() {
+                Some(id) if !id.is_empty() => {
+                    if !reference_ids.insert(id) {
+                        return Err(invalid("the reference id is used by another purchase unit".to_owned()));
+                    }
+                }
+                _ => {
+                    return Err(InvalidOrderError(
+                        "every purchase unit of a multi-seller order needs a reference id".to_owned(),
+                    ))
+                }
+            }
+            let has_payee = unit
+                .payee
+                .as_ref()
+                .is_some_and(|payee| payee.merchant_id.is_some() || payee.email_address.is_some());
+            if !has_payee {
+                return Err(invalid(
+                    "a multi-seller order needs a payee on every purchase unit".to_owned(),
+                ));
+            }
+        }
+        validate_amounts(unit, currency).map_err(invalid)?;
+    }
+    Ok(())
+}
+
+fn validate_amounts(unit: &PurchaseUnit, currency: &Currency) -> Result<(), String> {
+    if unit.amount.currency_code != *currency {
+        return Err(format!(
+            "the amount is in {} instead of {}",
+            unit.amount.currency_code, currency
+        ));
+    }
+    let amount = Money {
+        currency_code: unit.amount.currency_code.clone(),
+        value: unit.amount.value.clone(),
+    };
+    let compare = |money: &Money, other: &Money| money.checked_cmp(other).map_err(|e| e.to_string());
+    let same = |money: &Option<Money>, other: &Option<Money>| -> Result<bool, String> {
+        match (money, other) {
+            (Some(money), Some(other)) => Ok(compare(money, other)? == Ordering::Equal),
+            (money, other) => Ok(money.is_none() && other.is_none()),
+        }
+    };
+
+    let zero = Money::zero(currency.clone());
+    if compare(&amount, &zero)? != Ordering::Greater {
+        return Err(format!("the amount {} is not positive", amount.value));
+    }
+
+    let breakdown = unit.amount.breakdown.as_ref();
+    if let Some(breakdown) = breakdown {
+        if let Some(total) = breakdown.total().map_err(|e| e.to_string())? {
+            if compare(&total, &amount)? != Ordering::Equal {
+                return Err(format!("the amount {} doesn't match its breakdown", amount.value));
+            }
+        }
+    }
+
+    if let Some(items) = &unit.items {
+        let expected = Breakdown::from_items(items).map_err(|e| e.to_string())?;
+        let breakdown = breakdown.ok_or_else(|| "purchase units with items need an amount breakdown".to_owned())?;
+        if !same(&breakdown.item_total, &expected.item_total)? {
+            return Err("the item total doesn't match the items".to_owned());
+        }
+        if expected.tax_total.is_some() && !same(&breakdown.tax_total, &expected.tax_total)? {
+            return Err("the tax total doesn't match the tax of the items".to_owned());
+        }
+    }
+
+    let platform_fees = unit
+        .payment_instruction
+        .as_ref()
+        .and_then(|instruction| instruction.platform_fees.as_ref());
+    if let Some(platform_fees) = platform_fees {
+        let mut total = zero.clone();
+        for fee in platform_fees {
+            if compare(&fee.amount, &zero)? != Ordering::Greater {
+                return Err("platform fees must be positive".to_owned());
+            }
+            total = total.checked_add(&fee.amount).map_err(|e| e.to_string())?;
+        }
+        if compare(&total, &amount)? == Ordering::Greater {
+            return Err("the platform fees are above the amount".to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// The headers to act on behalf of a seller, like when refunding or capturing its payments.
+///
+/// The seller is identified by its merchant id in the `PayPal-Auth-Assertion` header.
+pub fn on_behalf_of(payee: &Payee) -> Result<HeaderParams, InvalidOrderError> {
+    let merchant_id = payee
+        .merchant_id
+        .clone()
+        .ok_or_else(|| InvalidOrderError("acting on behalf of a seller needs its merchant id".to_owned()))?;
+    Ok(HeaderParams {
+        merchant_payer_id: Some(merchant_id),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eur(value: &str) -> Money {
+        Money {
+            currency_code: Currency::EUR,
+            value: value.to_owned(),
+        }
+    }
+
+    fn seller(merchant_id: &str) -> Payee {
+        Payee {
+            merchant_id: Some(merchant_id.to_owned()),
+            email_address: None,
+        }
+    }
+
+    #[test]
+    fn test_marketplace_order() {
+        let order = MarketplaceOrder::new(Intent::Capture)
+            .seller(
+                SellerUnit::new(
+                    "mugs",
+                    seller("7KNGBPH2U58GQ"),
+                    vec![Item::new("Mug", eur("12.5"), 2).tax(eur("1.25"))],
+                )
+                .shipping_cost(eur("4.90"))
+                .platform_fee(eur("2.50")),
+            )
+            .seller(SellerUnit::new(
+                "tea",
+                seller("2FLXRJ5TQ9V8S"),
+                vec![Item::new("Green tea", eur("8.00"), 3)],
+            ))
+            .disbursement_mode(DisbursementMode::Delayed)
+            .build()
+            .unwrap();
+
+        let mugs = &order.purchase_units[0];
+        assert_eq!(mugs.amount.value, "32.40");
+        let breakdown = mugs.amount.breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.item_total, Some(eur("25.00")));
+        assert_eq!(breakdown.tax_total, Some(eur("2.50")));
+        let instruction = mugs.payment_instruction.as_ref().unwrap();
+        assert_eq!(instruction.platform_fees.as_ref().unwrap()[0].amount, eur("2.50"));
+        assert_eq!(instruction.disbursement_mode, Some(DisbursementMode::Delayed));
+
+        let tea = &order.purchase_units[1];
+        assert_eq!(tea.amount.value, "24.00");
+        assert!(tea.amount.breakdown.as_ref().unwrap().tax_total.is_none());
+        assert!(tea.payment_instruction.as_ref().unwrap().platform_fees.is_none());
+
+        let headers = on_behalf_of(tea.payee.as_ref().unwrap()).unwrap();
+        assert_eq!(headers.merchant_payer_id.as_deref(), Some("2FLXRJ5TQ9V8S"));
+        assert!(on_behalf_of(&Payee::default()).is_err());
+    }
+
+    #[test]
+    fn test_validate_purchase_units() {
+        let unit = |reference_id: &str, payee: Option<Payee>| PurchaseUnit {
+            reference_id: Some(reference_id.to_owned()),
+            payee,
+            ..PurchaseUnit::new(Amount::new(Currency::EUR, "10.00"))
+        };
+        assert!(validate_purchase_units(&[]).is_err());
+        // A single purchase unit pays the merchant of the api caller.
+        validate_purchase_units(&[PurchaseUnit::new(Amount::new(Currency::EUR, "10"))]).unwrap();
+        validate_purchase_units(&[unit("a", Some(seller("A"))), unit("b", Some(seller("B")))]).unwrap();
+        assert!(validate_purchase_units(&[unit("a", Some(seller("A"))), unit("a", Some(seller("B")))]).is_err());
+        assert!(validate_purchase_units(&[unit("a", Some(seller("A"))), unit("b", None)]).is_err());
+
+        let mut other_currency = unit("b", Some(seller("B")));
+        other_currency.amount.currency_code = Currency::USD;
+        assert!(validate_purchase_units(&[unit("a", Some(seller("A"))), other_currency]).is_err());
+
+        let mut wrong_breakdown = unit("a", None);
+        wrong_breakdown.amount.breakdown = Some(Breakdown {
+            item_total: Some(eur("8.00")),
+            shipping: Some(eur("3.00")),
+            discount: Some(eur("0.50")),
+            ..Default::default()
+        });
+        let error = validate_purchase_units(&[wrong_breakdown]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid order: purchase unit a: the amount 10.00 doesn't match its breakdown"
+        );
+
+        let mut wrong_items = unit("a", None);
+        wrong_items.amount.breakdown = Some(Breakdown {
+            item_total: Some(eur("10.00")),
+            ..Default::default()
+        });
+        wrong_items.items = Some(vec![Item::new("Mug", eur("4.00"), 2)]);
+        assert!(validate_purchase_units(&[wrong_items]).is_err());
+
+        let mut high_fee = unit("a", None);
+        high_fee.payment_instruction = Some(PaymentInstruction {
+            platform_fees: Some(vec![PlatformFee {
+                amount: eur("10.01"),
+                payee: None,
+            }]),
+            disbursement_mode: None,
+        });
+        assert!(validate_purchase_units(&[high_fee]).is_err());
+    }
+}
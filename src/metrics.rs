@@ -0,0 +1,193 @@
+//! Metrics about the requests made to paypal, so their health can be graphed along the rest of a service.
+//!
+//! Install a [MetricsRecorder] with [ClientBuilder::metrics](crate::client::ClientBuilder::metrics), either [Facade]
+//! to emit them through the [metrics](https://docs.rs/metrics) crate, or a [Callback] to handle them yourself.
+//!
+//! Requires the `metrics` feature.
+//!
+//! ```
+//! use paypal_rs::metrics::{Callback, MetricEvent};
+//! use paypal_rs::Client;
+//!
+//! let client = Client::builder()
+//!     .client_id("client_id")
+//!     .secret("secret")
+//!     .metrics(Callback(|event: &MetricEvent<'_>| {
+//!         if let MetricEvent::Request { endpoint, latency, .. } = event {
+//!             println!("{} took {:?}", endpoint, latency);
+//!         }
+//!     }))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::fmt::{self, Debug};
+use std::time::Duration;
+
+/// Something worth measuring that happened in a client.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MetricEvent<'a> {
+    /// A request was sent, once per attempt.
+    Request {
+        /// The http method of the request.
+        method: &'a str,
+        /// The path of the request, with the resource ids replaced by `{id}`.
+        endpoint: &'a str,
+        /// The status of the response, not set when no response was received.
+        status: Option<u16>,
+        /// How long it took to receive the response.
+        latency: Duration,
+    },
+    /// A failed request is going to be sent again.
+    Retry {
+        /// The http method of the request.
+        method: &'a str,
+        /// The path of the request, with the resource ids replaced by `{id}`.
+        endpoint: &'a str,
+        /// The attempt that failed, starting at 1.
+        attempt: u32,
+    },
+    /// A new access token was requested.
+    TokenRefresh {
+        /// Whether paypal gave a token.
+        success: bool,
+    },
+}
+
+/// Receives the metric events of a client.
+pub trait MetricsRecorder: Debug + Send + Sync {
+    /// Called for every event, it should return quickly since requests wait for it.
+    fn record(&self, event: &MetricEvent<'_>);
+}
+
+/// Calls the given function with every event.
+pub struct Callback<F>(pub F);
+
+impl<F> Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+impl<F: Fn(&MetricEvent<'_>) + Send + Sync> MetricsRecorder for Callback<F> {
+    fn record(&self, event: &MetricEvent<'_>) {
+        (self.0)(event)
+    }
+}
+
+/// Emits the events through the [metrics](https://docs.rs/metrics) crate, to the recorder installed by the application:
+///
+/// - `paypal_requests_total`, a counter labeled with `method`, `endpoint` and `status`.
+/// - `paypal_request_duration_seconds`, a histogram labeled with `method` and `endpoint`.
+/// - `paypal_retries_total`, a counter labeled with `method` and `endpoint`.
+/// - `paypal_token_refreshes_total`, a counter labeled with `result`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Facade;
+
+impl MetricsRecorder for Facade {
+    fn record(&self, event: &MetricEvent<'_>) {
+        match *event {
+            MetricEvent::Request {
+                method,
+                endpoint,
+                status,
+                latency,
+            } => {
+                let status = status.map_or_else(|| "error".to_owned(), |status| status.to_string());
+                ::metrics::counter!(
+                    "paypal_requests_total",
+                    "method" => method.to_owned(),
+                    "endpoint" => endpoint.to_owned(),
+                    "status" => status
+                )
+                .increment(1);
+                ::metrics::histogram!(
+                    "paypal_request_duration_seconds",
+                    "method" => method.to_owned(),
+                    "endpoint" => endpoint.to_owned()
+                )
+                .record(latency.as_secs_f64());
+            }
+            MetricEvent::Retry { method, endpoint, .. } => {
+                ::metrics::counter!(
+                    "paypal_retries_total",
+                    "method" => method.to_owned(),
+                    "endpoint" => endpoint.to_owned()
+                )
+                .increment(1);
+            }
+            MetricEvent::TokenRefresh { success } => {
+                let result = if success { "success" } else { "error" };
+                ::metrics::counter!("paypal_token_refreshes_total", "result" => result).increment(1);
+            }
+        }
+    }
+}
+
+/// Replaces the resource ids in a path with `{id}`, so every order or invoice is reported under the same endpoint.
+///
+/// Paypal ids are long and contain digits, unlike the other segments of the api paths.
+pub(crate) fn endpoint(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.len() >= 10 && segment.bytes().any(|b| b.is_ascii_digit()) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(
+            endpoint("/v2/checkout/orders/5O190127TN364715T/capture"),
+            "/v2/checkout/orders/{id}/capture"
+        );
+        assert_eq!(endpoint("/v1/oauth2/token"), "/v1/oauth2/token");
+        assert_eq!(
+            endpoint("/v2/invoicing/invoices/INV2-Z56S-5LLA-Q52L-CPZ5/send"),
+            "/v2/invoicing/invoices/{id}/send"
+        );
+        assert_eq!(endpoint("/v2/invoicing/invoices"), "/v2/invoicing/invoices");
+    }
+
+    #[cfg(feature = "vcr")]
+    #[tokio::test]
+    async fn test_callback() {
+        use crate::client::Client;
+        use crate::vcr::{replay_cassette, token_interaction, Interaction};
+        use std::sync::{Arc, Mutex};
+
+        let interactions = [Interaction {
+            status: 401,
+            body: r#"{"error":"invalid_client","error_description":"Client Authentication failed"}"#.to_owned(),
+            ..token_interaction()
+        }];
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut client = Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .cassette(replay_cassette(&interactions))
+            .metrics(Callback(move |event: &MetricEvent<'_>| {
+                recorded.lock().unwrap().push(format!("{:?}", event));
+            }))
+            .build()
+            .unwrap();
+        assert!(client.get_access_token().await.is_err());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].starts_with(r#"Request { method: "POST", endpoint: "/v1/oauth2/token", status: Some(401)"#));
+        assert_eq!(events[1], "TokenRefresh { success: false }");
+    }
+}
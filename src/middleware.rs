@@ -0,0 +1,18 @@
+//! Hooks that run around every request the client sends.
+//!
+//! Middlewares are installed with [ClientBuilder::middleware](crate::client::ClientBuilder::middleware)
+//! and run in the order they were added, once per attempt, so retried requests go through them again.
+
+use std::fmt::Debug;
+
+/// A hook run around every request sent by the client.
+pub trait Middleware: Debug + Send + Sync {
+    /// Called right before the request is sent, it can be modified here.
+    fn on_request(&self, _request: &mut reqwest::Request) {}
+
+    /// Called once a response is received, before its body is read.
+    fn on_response(&self, _response: &reqwest::Response) {}
+
+    /// Called when the request could not be sent or the response could not be received.
+    fn on_error(&self, _error: &reqwest::Error) {}
+}
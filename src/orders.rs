@@ -5,16 +5,19 @@
 //! Reference: https://developer.paypal.com/docs/api/orders/v2/
 
 use crate::common::*;
-use crate::errors::{PaypalError, ResponseError};
+use crate::errors::{decode, InvalidPhoneNumberError, MoneyError, ResponseError};
 use crate::client::{HeaderParams, Client};
+use crate::countries::Country;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
+use std::collections::HashMap;
 
 /// The intent to either capture payment immediately or authorize a payment for an order after order creation.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Intent {
     /// The merchant intends to capture payment immediately after the customer makes a payment.
+    #[default]
     Capture,
     /// The merchant intends to authorize a payment and place funds on hold after the customer makes a payment.
     /// Authorized payments are guaranteed for up to three days but are available to capture for up to 29 days.
@@ -24,11 +27,10 @@ pub enum Intent {
     Authorize,
 }
 
-impl Default for Intent {
-    fn default() -> Self {
-        Intent::Capture
-    }
-}
+display_from_str!(Intent {
+    Capture = "CAPTURE",
+    Authorize = "AUTHORIZE",
+});
 
 /// Represents a payer name.
 ///
@@ -43,7 +45,7 @@ pub struct PayerName {
 }
 
 /// The phone number, in its canonical international E.164 numbering plan format.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct PhoneNumber {
     /// The national number, in its canonical international E.164 numbering plan format.
     /// The combined length of the country calling code (CC) and the national number must not be greater than 15 digits.
@@ -51,6 +53,44 @@ pub struct PhoneNumber {
     pub national_number: String,
 }
 
+impl PhoneNumber {
+    /// Parses and validates a phone number, ignoring spaces, dashes, dots and parentheses.
+    ///
+    /// If the number starts with a `+` the country calling code is dropped, use [PhoneNumber::parse_international] to keep it.
+    pub fn parse(number: &str) -> Result<Self, InvalidPhoneNumberError> {
+        let (international, digits) = normalize_phone_number(number)?;
+        if international {
+            return Self::parse_international(number).map(|(_, number)| number);
+        }
+
+        if digits.len() >= E164_MAX_DIGITS {
+            return Err(InvalidPhoneNumberError(number.to_owned()));
+        }
+        Ok(PhoneNumber {
+            national_number: digits,
+        })
+    }
+
+    /// Parses an international phone number starting with a `+`, returning its country calling code and national number.
+    pub fn parse_international(number: &str) -> Result<(String, Self), InvalidPhoneNumberError> {
+        let phone = PhoneWithCountryCode::parse(number)?;
+        Ok((
+            phone.country_code,
+            PhoneNumber {
+                national_number: phone.national_number,
+            },
+        ))
+    }
+}
+
+impl std::str::FromStr for PhoneNumber {
+    type Err = InvalidPhoneNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 /// The phone number of the customer. Available only when you enable the
 /// Contact Telephone Number option in the Profile & Settings for the merchant's PayPal account.
 #[skip_serializing_none]
@@ -62,17 +102,33 @@ pub struct Phone {
     pub phone_number: PhoneNumber,
 }
 
+impl Phone {
+    /// Creates a phone from a number, see [PhoneNumber::parse].
+    pub fn parse(phone_type: Option<PhoneType>, number: &str) -> Result<Self, InvalidPhoneNumberError> {
+        Ok(Phone {
+            phone_type,
+            phone_number: PhoneNumber::parse(number)?,
+        })
+    }
+}
+
 /// The customer's tax ID type. Supported for the PayPal payment method only.
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(non_camel_case_types)]
 pub enum TaxIdType {
     /// The individual tax ID type.
+    #[serde(rename = "BR_CPF")]
     BR_CPF,
     /// The business tax ID type.
+    #[serde(rename = "BR_CNPJ")]
     BR_CNPJ,
 }
 
+display_from_str!(TaxIdType {
+    BR_CPF = "BR_CPF",
+    BR_CNPJ = "BR_CNPJ",
+});
+
 /// The tax information of the payer.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaxInfo {
@@ -152,11 +208,83 @@ impl Amount {
             breakdown: None,
         }
     }
+
+    /// Adds an amount in the same currency, along with its breakdown.
+    ///
+    /// Fails if only one of the amounts has a breakdown, the result couldn't have a consistent one.
+    pub fn checked_add(&self, other: &Amount) -> Result<Amount, MoneyError> {
+        self.combine(other, Money::checked_add)
+    }
+
+    /// Subtracts an amount in the same currency, along with its breakdown. The result can be negative.
+    ///
+    /// Fails if only one of the amounts has a breakdown, the result couldn't have a consistent one.
+    pub fn checked_sub(&self, other: &Amount) -> Result<Amount, MoneyError> {
+        self.combine(other, Money::checked_sub)
+    }
+
+    /// Multiplies the amount and its breakdown by a quantity.
+    pub fn mul_quantity(&self, quantity: u32) -> Result<Amount, MoneyError> {
+        Ok(Amount {
+            currency_code: self.currency_code.clone(),
+            value: self.money().mul_quantity(quantity)?.value,
+            breakdown: self
+                .breakdown
+                .as_ref()
+                .map(|breakdown| breakdown.mul_quantity(quantity))
+                .transpose()?,
+        })
+    }
+
+    fn money(&self) -> Money {
+        Money {
+            currency_code: self.currency_code.clone(),
+            value: self.value.clone(),
+        }
+    }
+
+    fn combine(
+        &self,
+        other: &Amount,
+        op: fn(&Money, &Money) -> Result<Money, MoneyError>,
+    ) -> Result<Amount, MoneyError> {
+        let breakdown = match (&self.breakdown, &other.breakdown) {
+            (None, None) => None,
+            (Some(breakdown), Some(other)) => Some(breakdown.combine(other, op)?),
+            _ => return Err(MoneyError::MissingBreakdown),
+        };
+        Ok(Amount {
+            currency_code: self.currency_code.clone(),
+            value: op(&self.money(), &other.money())?.value,
+            breakdown,
+        })
+    }
+
+    /// Creates a new amount from a decimal, formatted with the number of decimal places the currency uses.
+    ///
+    /// Fails if the value has more significant decimal places than the currency supports.
+    #[cfg(feature = "decimal")]
+    pub fn from_decimal(
+        currency_code: Currency,
+        value: rust_decimal::Decimal,
+    ) -> Result<Self, crate::errors::InvalidAmountError> {
+        Ok(Amount {
+            value: format_decimal(&currency_code, value)?,
+            currency_code,
+            breakdown: None,
+        })
+    }
+
+    /// The value as a decimal.
+    #[cfg(feature = "decimal")]
+    pub fn decimal(&self) -> Result<rust_decimal::Decimal, crate::errors::InvalidAmountError> {
+        parse_decimal(&self.value)
+    }
 }
 
 /// The merchant who receives payment for this transaction.
 #[skip_serializing_none]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Payee {
     /// The email address of merchant.
     pub email_address: Option<String>,
@@ -166,7 +294,7 @@ pub struct Payee {
 
 /// Fees, commissions, tips, or donations
 #[skip_serializing_none]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PlatformFee {
     /// The fee for this transaction.
     pub amount: Money,
@@ -175,20 +303,17 @@ pub struct PlatformFee {
     pub payee: Option<Payee>,
 }
 
-/// The funds that are held on behalf of the merchant
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-pub enum DisbursementMode {
-    /// The funds are released to the merchant immediately.
-    Instant,
-    /// The funds are held for a finite number of days. The actual duration depends on the region and type of integration.
-    /// You can release the funds through a referenced payout.
-    /// Otherwise, the funds disbursed automatically after the specified duration.
-    Delayed,
-}
-
-impl Default for DisbursementMode {
-    fn default() -> Self {
-        DisbursementMode::Instant
+string_enum! {
+    /// The funds that are held on behalf of the merchant
+    #[derive(Default)]
+    pub enum DisbursementMode {
+        /// The funds are released to the merchant immediately.
+        #[default]
+        Instant = "INSTANT",
+        /// The funds are held for a finite number of days. The actual duration depends on the region and type of integration.
+        /// You can release the funds through a referenced payout.
+        /// Otherwise, the funds disbursed automatically after the specified duration.
+        Delayed = "DELAYED",
     }
 }
 
@@ -202,21 +327,142 @@ pub struct PaymentInstruction {
     pub disbursement_mode: Option<DisbursementMode>,
 }
 
+/// The payload to capture payment for an order.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OrderCapturePayload {
+    /// The platform fees and disbursement mode of the capture, for PayPal Commerce Platform partners.
+    pub payment_instruction: Option<PaymentInstruction>,
+}
+
 /// The item category type.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ItemCategoryType {
     /// Goods that are stored, delivered, and used in their electronic format.
     /// This value is not currently supported for API callers that leverage
     /// the [PayPal for Commerce Platform](https://www.paypal.com/us/webapps/mpp/commerce-platform) product.
+    #[default]
     Digital,
     /// A tangible item that can be shipped with proof of delivery.
     Physical,
+    /// A contribution or gift for which no good or service is exchanged, usually to a not for profit organization.
+    Donation,
 }
 
-impl Default for ItemCategoryType {
-    fn default() -> Self {
-        ItemCategoryType::Digital
+display_from_str!(ItemCategoryType {
+    Digital = "DIGITAL",
+    Physical = "PHYSICAL",
+    Donation = "DONATION",
+});
+
+/// The Universal Product Code type.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum UpcType {
+    /// UPC-A
+    #[serde(rename = "UPC-A")]
+    UpcA,
+    /// UPC-B
+    #[serde(rename = "UPC-B")]
+    UpcB,
+    /// UPC-C
+    #[serde(rename = "UPC-C")]
+    UpcC,
+    /// UPC-D
+    #[serde(rename = "UPC-D")]
+    UpcD,
+    /// UPC-E
+    #[serde(rename = "UPC-E")]
+    UpcE,
+    /// UPC-2
+    #[serde(rename = "UPC-2")]
+    Upc2,
+    /// UPC-5
+    #[serde(rename = "UPC-5")]
+    Upc5,
+}
+
+display_from_str!(UpcType {
+    UpcA = "UPC-A",
+    UpcB = "UPC-B",
+    UpcC = "UPC-C",
+    UpcD = "UPC-D",
+    UpcE = "UPC-E",
+    Upc2 = "UPC-2",
+    Upc5 = "UPC-5",
+});
+
+/// The Universal Product Code of an item.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Upc {
+    /// The Universal Product Code type.
+    #[serde(rename = "type")]
+    pub type_: UpcType,
+    /// The UPC product code of the item, 6 to 17 digits.
+    pub code: String,
+}
+
+/// How the items are delivered to the payer.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ShippingType {
+    /// The items are shipped to the payer's address.
+    Shipping,
+    /// The payer picks the items up from the merchant's store.
+    PickupInStore,
+    /// The payer picks the items up from a pickup location.
+    PickupFromPerson,
+}
+
+display_from_str!(ShippingType {
+    Shipping = "SHIPPING",
+    PickupInStore = "PICKUP_IN_STORE",
+    PickupFromPerson = "PICKUP_FROM_PERSON",
+});
+
+/// A shipping method the payer can choose from on the paypal review page.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShippingOption {
+    /// A unique ID that identifies a payer-selected shipping option.
+    pub id: String,
+    /// A description that the payer sees, which helps them choose an appropriate shipping option.
+    pub label: String,
+    /// Whether this option is selected. Only one option can be selected.
+    pub selected: bool,
+    /// The classification for the method of purchase fulfillment.
+    #[serde(rename = "type")]
+    pub type_: Option<ShippingType>,
+    /// The shipping cost for the selected option.
+    pub amount: Option<Money>,
+}
+
+impl ShippingOption {
+    /// Creates a new, unselected, shipping option.
+    pub fn new(id: impl Into<String>, label: impl Into<String>, type_: ShippingType, amount: Money) -> Self {
+        ShippingOption {
+            id: id.into(),
+            label: label.into(),
+            selected: false,
+            type_: Some(type_),
+            amount: Some(amount),
+        }
+    }
+}
+
+/// The name of the person to whom to ship the items.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ShippingName {
+    /// The full name of the person.
+    pub full_name: Option<String>,
+}
+
+impl<S: Into<String>> From<S> for ShippingName {
+    fn from(full_name: S) -> Self {
+        ShippingName {
+            full_name: Some(full_name.into()),
+        }
     }
 }
 
@@ -225,11 +471,105 @@ impl Default for ItemCategoryType {
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ShippingDetail {
     /// The name of the person to whom to ship the items. Supports only the full_name property.
-    pub name: Option<String>,
+    ///
+    /// It used to be a plain string, which paypal rejects: build it from the string with `Some(name.into())`
+    /// and read it back with [full_name](ShippingDetail::full_name).
+    pub name: Option<ShippingName>,
+    /// The method by which the payer wants to get their items.
+    #[serde(rename = "type")]
+    pub type_: Option<ShippingType>,
+    /// The shipping options the payer can choose from.
+    pub options: Option<Vec<ShippingOption>>,
     /// The address of the person to whom to ship the items.
     pub address: Option<Address>,
 }
 
+impl ShippingDetail {
+    /// The full name of the person to whom to ship the items.
+    pub fn full_name(&self) -> Option<&str> {
+        self.name.as_ref()?.full_name.as_deref()
+    }
+
+    /// Marks the option with the given id as the only selected one, returns false if there is no such option.
+    pub fn select_option(&mut self, id: &str) -> bool {
+        let options = match &mut self.options {
+            Some(options) if options.iter().any(|option| option.id == id) => options,
+            _ => return false,
+        };
+        for option in options {
+            option.selected = option.id == id;
+        }
+        true
+    }
+}
+
+/// The path of a field of the purchase unit with the given reference id, to use in a [Patch].
+pub fn purchase_unit_path(reference_id: &str, field: &str) -> String {
+    format!("/purchase_units/@reference_id=='{}'/{}", reference_id, field)
+}
+
+impl Patch {
+    /// Replaces the shipping options of a purchase unit, used to update the selected option
+    /// once the payer changed it on the review page.
+    pub fn shipping_options(reference_id: &str, options: &[ShippingOption]) -> Self {
+        Patch::replace(
+            purchase_unit_path(reference_id, "shipping/options"),
+            serde_json::to_value(options).expect("error serializing shipping options"),
+        )
+    }
+
+    /// Replaces the shipping details of a purchase unit, one patch per field that is set,
+    /// since paypal only accepts patches of the shipping name, type, options and address.
+    pub fn shipping(reference_id: &str, shipping: &ShippingDetail) -> Vec<Self> {
+        let fields = vec![
+            ("shipping/name", shipping.name.as_ref().map(serde_json::to_value)),
+            ("shipping/type", shipping.type_.as_ref().map(serde_json::to_value)),
+            ("shipping/options", shipping.options.as_ref().map(serde_json::to_value)),
+            ("shipping/address", shipping.address.as_ref().map(serde_json::to_value)),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(field, value)| {
+                let value = value?.expect("error serializing shipping details");
+                Some(Patch::replace(purchase_unit_path(reference_id, field), value))
+            })
+            .collect()
+    }
+
+    /// Replaces the intent of an order, it can only be changed from Authorize to Capture.
+    pub fn intent(intent: Intent) -> Self {
+        Patch::replace(
+            "/intent",
+            serde_json::to_value(intent).expect("error serializing intent"),
+        )
+    }
+
+    /// Replaces the purchase unit with the same reference id, "default" if it has none.
+    pub fn purchase_unit(unit: &PurchaseUnit) -> Self {
+        Patch::replace(
+            format!(
+                "/purchase_units/@reference_id=='{}'",
+                unit.reference_id.as_deref().unwrap_or("default")
+            ),
+            serde_json::to_value(unit).expect("error serializing purchase unit"),
+        )
+    }
+
+    /// Replaces the amount of a purchase unit, its breakdown must match the items.
+    pub fn amount(reference_id: &str, amount: &Amount) -> Self {
+        Patch::replace(
+            purchase_unit_path(reference_id, "amount"),
+            serde_json::to_value(amount).expect("error serializing amount"),
+        )
+    }
+
+    /// Sets the invoice id of a purchase unit, whether it had one or not.
+    pub fn invoice_id(reference_id: &str, invoice_id: &str) -> Self {
+        Patch::add(purchase_unit_path(reference_id, "invoice_id"), invoice_id.into())
+    }
+}
+
 /// Represents an item.
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
@@ -249,36 +589,219 @@ pub struct Item {
     pub sku: Option<String>,
     /// The item category type
     pub category: Option<ItemCategoryType>,
+    /// The URL to the item being purchased. Visible to buyer and used in buyer experiences.
+    pub url: Option<String>,
+    /// The URL of the item's image. File type and size restrictions apply, an image that violates these restrictions will not be honored.
+    pub image_url: Option<String>,
+    /// The Universal Product Code of the item.
+    pub upc: Option<Upc>,
 }
 
-/// The status of the payment authorization.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum AuthorizationStatus {
-    /// The authorized payment is created. No captured payments have been made for this authorized payment.
-    Created,
-    /// The authorized payment has one or more captures against it. The sum of these captured payments is greater than the amount of the original authorized payment.
-    Captured,
-    /// PayPal cannot authorize funds for this authorized payment.
-    Denied,
-    /// The authorized payment has expired.
-    Expired,
-    /// A captured payment was made for the authorized payment for an amount that is less than the amount of the original authorized payment.
-    PartiallyExpired,
-    /// The payment which was authorized for an amount that is less than the originally requested amount.
-    PartiallyCaptured,
-    /// The authorized payment was voided. No more captured payments can be made against this authorized payment.
-    Voided,
-    /// The created authorization is in pending state. For more information, see status.details.
-    Pending,
-}
-
-/// Authorization status reason.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum AuthorizationStatusDetailsReason {
-    /// Authorization is pending manual review.
-    PendingReview,
+impl Item {
+    /// Creates a new item with the required properties.
+    pub fn new<S: Into<String>>(name: S, unit_amount: Money, quantity: u32) -> Self {
+        Item {
+            name: name.into(),
+            unit_amount,
+            tax: None,
+            quantity: quantity.to_string(),
+            description: None,
+            sku: None,
+            category: None,
+            url: None,
+            image_url: None,
+            upc: None,
+        }
+    }
+
+    /// Sets the tax for each unit.
+    pub fn tax(mut self, tax: Money) -> Self {
+        self.tax = Some(tax);
+        self
+    }
+
+    /// Sets the detailed item description.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the stock keeping unit (SKU).
+    pub fn sku<S: Into<String>>(mut self, sku: S) -> Self {
+        self.sku = Some(sku.into());
+        self
+    }
+
+    /// Sets the item category type.
+    pub fn category(mut self, category: ItemCategoryType) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Sets the URL to the item being purchased.
+    pub fn url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Sets the URL of the item's image.
+    pub fn image_url<S: Into<String>>(mut self, image_url: S) -> Self {
+        self.image_url = Some(image_url.into());
+        self
+    }
+
+    /// Sets the Universal Product Code of the item.
+    pub fn upc<S: Into<String>>(mut self, type_: UpcType, code: S) -> Self {
+        self.upc = Some(Upc {
+            type_,
+            code: code.into(),
+        });
+        self
+    }
+
+    /// The item quantity as a number, None if it isn't a valid whole number.
+    pub fn quantity_count(&self) -> Option<u32> {
+        self.quantity.parse().ok()
+    }
+
+    /// The price of all the units, unit_amount * quantity.
+    pub fn total(&self) -> Result<Money, MoneyError> {
+        self.unit_amount.mul_quantity(self.checked_quantity()?)
+    }
+
+    /// The tax of all the units, tax * quantity.
+    pub fn tax_total(&self) -> Result<Option<Money>, MoneyError> {
+        let quantity = self.checked_quantity()?;
+        self.tax.as_ref().map(|tax| tax.mul_quantity(quantity)).transpose()
+    }
+
+    fn checked_quantity(&self) -> Result<u32, MoneyError> {
+        self.quantity_count()
+            .ok_or_else(|| MoneyError::InvalidQuantity(self.quantity.clone()))
+    }
+}
+
+impl Breakdown {
+    /// Creates a breakdown with the item_total and tax_total matching the given items.
+    ///
+    /// Fails if the items are not all in the same currency.
+    pub fn from_items(items: &[Item]) -> Result<Self, MoneyError> {
+        let currency = match items.first() {
+            Some(item) => item.unit_amount.currency_code.clone(),
+            None => return Ok(Breakdown::default()),
+        };
+
+        let mut item_total = Money::zero(currency.clone());
+        let mut tax_total: Option<Money> = None;
+        for item in items {
+            item_total = item_total.checked_add(&item.total()?)?;
+            if let Some(tax) = item.tax_total()? {
+                let total = tax_total.unwrap_or_else(|| Money::zero(currency.clone()));
+                tax_total = Some(total.checked_add(&tax)?);
+            }
+        }
+
+        Ok(Breakdown {
+            item_total: Some(item_total),
+            tax_total,
+            ..Default::default()
+        })
+    }
+
+    /// The amount the breakdown adds up to: the item total, tax, shipping, handling and insurance,
+    /// minus the discounts. None if it has no field at all.
+    pub fn total(&self) -> Result<Option<Money>, MoneyError> {
+        let mut total: Option<Money> = None;
+        let added = [
+            &self.item_total,
+            &self.tax_total,
+            &self.shipping,
+            &self.handling,
+            &self.insurance,
+        ];
+        let subtracted = [&self.shipping_discount, &self.discount];
+        let signed = added
+            .iter()
+            .map(|money| (money, false))
+            .chain(subtracted.iter().map(|money| (money, true)));
+        for (money, negative) in signed {
+            if let Some(money) = money {
+                let current = total.unwrap_or_else(|| Money::zero(money.currency_code.clone()));
+                total = Some(if negative {
+                    current.checked_sub(money)?
+                } else {
+                    current.checked_add(money)?
+                });
+            }
+        }
+        Ok(total)
+    }
+
+    /// Combines each field with the same field of another breakdown, a missing field counting as zero.
+    fn combine(
+        &self,
+        other: &Breakdown,
+        op: fn(&Money, &Money) -> Result<Money, MoneyError>,
+    ) -> Result<Breakdown, MoneyError> {
+        let field = |money: &Option<Money>, other: &Option<Money>| match (money, other) {
+            (None, None) => Ok(None),
+            (Some(money), Some(other)) => op(money, other).map(Some),
+            (Some(money), None) => op(money, &Money::zero(money.currency_code.clone())).map(Some),
+            (None, Some(other)) => op(&Money::zero(other.currency_code.clone()), other).map(Some),
+        };
+        Ok(Breakdown {
+            item_total: field(&self.item_total, &other.item_total)?,
+            shipping: field(&self.shipping, &other.shipping)?,
+            handling: field(&self.handling, &other.handling)?,
+            tax_total: field(&self.tax_total, &other.tax_total)?,
+            insurance: field(&self.insurance, &other.insurance)?,
+            shipping_discount: field(&self.shipping_discount, &other.shipping_discount)?,
+            discount: field(&self.discount, &other.discount)?,
+        })
+    }
+
+    fn mul_quantity(&self, quantity: u32) -> Result<Breakdown, MoneyError> {
+        let field = |money: &Option<Money>| money.as_ref().map(|money| money.mul_quantity(quantity)).transpose();
+        Ok(Breakdown {
+            item_total: field(&self.item_total)?,
+            shipping: field(&self.shipping)?,
+            handling: field(&self.handling)?,
+            tax_total: field(&self.tax_total)?,
+            insurance: field(&self.insurance)?,
+            shipping_discount: field(&self.shipping_discount)?,
+            discount: field(&self.discount)?,
+        })
+    }
+}
+
+string_enum! {
+    /// The status of the payment authorization.
+    pub enum AuthorizationStatus {
+        /// The authorized payment is created. No captured payments have been made for this authorized payment.
+        Created = "CREATED",
+        /// The authorized payment has one or more captures against it. The sum of these captured payments is greater than the amount of the original authorized payment.
+        Captured = "CAPTURED",
+        /// PayPal cannot authorize funds for this authorized payment.
+        Denied = "DENIED",
+        /// The authorized payment has expired.
+        Expired = "EXPIRED",
+        /// A captured payment was made for the authorized payment for an amount that is less than the amount of the original authorized payment.
+        PartiallyExpired = "PARTIALLY_EXPIRED",
+        /// The payment which was authorized for an amount that is less than the originally requested amount.
+        PartiallyCaptured = "PARTIALLY_CAPTURED",
+        /// The authorized payment was voided. No more captured payments can be made against this authorized payment.
+        Voided = "VOIDED",
+        /// The created authorization is in pending state. For more information, see status.details.
+        Pending = "PENDING",
+    }
+}
+
+string_enum! {
+    /// Authorization status reason.
+    pub enum AuthorizationStatusDetailsReason {
+        /// Authorization is pending manual review.
+        PendingReview = "PENDING_REVIEW",
+    }
 }
 
 /// Details about the status of the authorization.
@@ -288,61 +811,95 @@ pub struct AuthorizationStatusDetails {
     pub reason: AuthorizationStatusDetailsReason,
 }
 
+/// The processor response for a card payment.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ProcessorResponse {
+    /// The address verification code for Visa, Discover, Mastercard, or American Express transactions.
+    pub avs_code: Option<String>,
+    /// The card verification value code for Visa, Discover, Mastercard, or American Express.
+    pub cvv_code: Option<String>,
+    /// The processor response code for the non-PayPal payment processor errors.
+    pub response_code: Option<String>,
+    /// The declined payment transactions might have payment advice codes.
+    /// The card networks, like Visa and Mastercard, return payment advice codes.
+    pub payment_advice_code: Option<String>,
+}
+
+impl ProcessorResponse {
+    /// Whether the processor approved the payment, response code 0000.
+    pub fn is_approved(&self) -> bool {
+        self.response_code.as_deref() == Some("0000")
+    }
+
+    /// Whether the card network advises against retrying the payment with the same card.
+    ///
+    /// Payment advice code 21 means the card holder cancelled all recurring payments, 03 and 04 mean the card is unusable.
+    pub fn should_not_retry(&self) -> bool {
+        matches!(self.payment_advice_code.as_deref(), Some("03") | Some("04") | Some("21"))
+    }
+}
+
 /// A payment authorization.
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct AuthorizationWithData {
     /// The status for the authorized payment.
     pub status: AuthorizationStatus,
     /// The details of the authorized order pending status.
-    pub status_details: AuthorizationStatusDetails,
+    pub status_details: Option<AuthorizationStatusDetails>,
+    /// The processor response for card payments.
+    pub processor_response: Option<ProcessorResponse>,
+    /// The reference to the transaction assigned by the card network, store it to make later merchant initiated payments.
+    pub network_transaction_reference: Option<NetworkTransactionReference>,
 }
 
-/// The capture status.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum CaptureStatus {
-    /// The funds for this captured payment were credited to the payee's PayPal account.
-    Completed,
-    ///  The funds could not be captured.
-    Declined,
-    /// An amount less than this captured payment's amount was partially refunded to the payer.
-    PartiallyRefunded,
-    /// The funds for this captured payment was not yet credited to the payee's PayPal account. For more information, see status.details.
-    Pending,
-    /// An amount greater than or equal to this captured payment's amount was refunded to the payer.
-    Refunded,
-}
-
-/// Capture status reason.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum CaptureStatusDetailsReason {
-    /// The payer initiated a dispute for this captured payment with PayPal.
-    BuyerComplaint,
-    /// The captured funds were reversed in response to the payer disputing this captured payment with
-    /// the issuer of the financial instrument used to pay for this captured payment.
-    Chargeback,
-    /// The payer paid by an eCheck that has not yet cleared.
-    Echeck,
-    /// Visit your online account. In your **Account Overview**, accept and deny this payment.
-    InternationalWithdrawal,
-    /// No additional specific reason can be provided. For more information about this captured payment, visit your account online or contact PayPal.
-    Other,
-    /// The captured payment is pending manual review.
-    PendingReview,
-    /// The payee has not yet set up appropriate receiving preferences for their account.
-    /// For more information about how to accept or deny this payment, visit your account online.
-    /// This reason is typically offered in scenarios such as when the currency of the captured
-    /// payment is different from the primary holding currency of the payee.
-    ReceivingPreferenceMandatesManualAction,
-    /// The captured funds were refunded.
-    Refunded,
-    /// The payer must send the funds for this captured payment. This code generally appears for manual EFTs.
-    TransactionApprovedAwaitingFunding,
-    /// The payee does not have a PayPal account.
-    Unilateral,
-    /// The payee's PayPal account is not verified.
-    VerificationRequired,
+string_enum! {
+    /// The capture status.
+    pub enum CaptureStatus {
+        /// The funds for this captured payment were credited to the payee's PayPal account.
+        Completed = "COMPLETED",
+        ///  The funds could not be captured.
+        Declined = "DECLINED",
+        /// An amount less than this captured payment's amount was partially refunded to the payer.
+        PartiallyRefunded = "PARTIALLY_REFUNDED",
+        /// The funds for this captured payment was not yet credited to the payee's PayPal account. For more information, see status.details.
+        Pending = "PENDING",
+        /// An amount greater than or equal to this captured payment's amount was refunded to the payer.
+        Refunded = "REFUNDED",
+    }
+}
+
+string_enum! {
+    /// Capture status reason.
+    pub enum CaptureStatusDetailsReason {
+        /// The payer initiated a dispute for this captured payment with PayPal.
+        BuyerComplaint = "BUYER_COMPLAINT",
+        /// The captured funds were reversed in response to the payer disputing this captured payment with
+        /// the issuer of the financial instrument used to pay for this captured payment.
+        Chargeback = "CHARGEBACK",
+        /// The payer paid by an eCheck that has not yet cleared.
+        Echeck = "ECHECK",
+        /// Visit your online account. In your **Account Overview**, accept and deny this payment.
+        InternationalWithdrawal = "INTERNATIONAL_WITHDRAWAL",
+        /// No additional specific reason can be provided. For more information about this captured payment, visit your account online or contact PayPal.
+        Other = "OTHER",
+        /// The captured payment is pending manual review.
+        PendingReview = "PENDING_REVIEW",
+        /// The payee has not yet set up appropriate receiving preferences for their account.
+        /// For more information about how to accept or deny this payment, visit your account online.
+        /// This reason is typically offered in scenarios such as when the currency of the captured
+        /// payment is different from the primary holding currency of the payee.
+        ReceivingPreferenceMandatesManualAction = "RECEIVING_PREFERENCE_MANDATES_MANUAL_ACTION",
+        /// The captured funds were refunded.
+        Refunded = "REFUNDED",
+        /// The payer must send the funds for this captured payment. This code generally appears for manual EFTs.
+        TransactionApprovedAwaitingFunding = "TRANSACTION_APPROVED_AWAITING_FUNDING",
+        /// The payee does not have a PayPal account.
+        Unilateral = "UNILATERAL",
+        /// The payee's PayPal account is not verified.
+        VerificationRequired = "VERIFICATION_REQUIRED",
+    }
 }
 
 /// Details about the captured payment status.
@@ -356,30 +913,47 @@ pub struct CaptureStatusDetails {
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Capture {
+    /// The ID of the captured payment.
+    pub id: Option<String>,
     /// The status of the captured payment.
     pub status: CaptureStatus,
+    /// The amount of the captured payment.
+    pub amount: Option<Money>,
+    /// Whether no more captures can be made against the authorization.
+    pub final_capture: Option<bool>,
+    /// The date and time when the payment was captured.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the captured payment was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
     /// The details of the captured payment status.
     pub status_details: Option<CaptureStatusDetails>,
+    /// The processor response for card payments.
+    pub processor_response: Option<ProcessorResponse>,
+    /// The reference to the transaction assigned by the card network, store it to make later merchant initiated payments.
+    pub network_transaction_reference: Option<NetworkTransactionReference>,
+    /// The fields paypal sent that this crate doesn't know about yet, as they were received.
+    #[serde(flatten, deserialize_with = "crate::errors::deserialize_extra")]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
-/// The status of the refund
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum RefundStatus {
-    /// The refund was cancelled.
-    Cancelled,
-    /// The refund is pending. For more information, see status_details.reason.
-    Pending,
-    /// The funds for this transaction were debited to the customer's account.
-    Completed,
+string_enum! {
+    /// The status of the refund
+    pub enum RefundStatus {
+        /// The refund was cancelled.
+        Cancelled = "CANCELLED",
+        /// The refund is pending. For more information, see status_details.reason.
+        Pending = "PENDING",
+        /// The funds for this transaction were debited to the customer's account.
+        Completed = "COMPLETED",
+    }
 }
 
-/// Refund status reason.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum RefundStatusDetailsReason {
-    /// The customer's account is funded through an eCheck, which has not yet cleared.
-    Echeck,
+string_enum! {
+    /// Refund status reason.
+    pub enum RefundStatusDetailsReason {
+        /// The customer's account is funded through an eCheck, which has not yet cleared.
+        Echeck = "ECHECK",
+    }
 }
 
 /// Details about the status of the refund.
@@ -390,12 +964,40 @@ pub struct RefundStatusDetails {
 }
 
 /// A refund
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Refund {
+    /// The ID of the refund.
+    pub id: Option<String>,
     /// The status of the refund.
     pub status: RefundStatus,
+    /// The amount refunded to the payer.
+    pub amount: Option<Money>,
+    /// The date and time when the refund was created.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the refund was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
     /// The details of the refund status.
-    pub status_details: RefundStatusDetails,
+    pub status_details: Option<RefundStatusDetails>,
+    /// How the refund is taken from the payee, with the fees returned to them.
+    pub seller_payable_breakdown: Option<SellerPayableBreakdown>,
+}
+
+/// The breakdown of a refund on the payee side.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SellerPayableBreakdown {
+    /// The amount refunded to the payer.
+    pub gross_amount: Option<Money>,
+    /// The part of the PayPal fee returned to the payee.
+    pub paypal_fee: Option<Money>,
+    /// The platform fees returned to the platform, on PayPal Commerce Platform.
+    #[serde(default)]
+    pub platform_fees: Vec<PlatformFee>,
+    /// The amount debited from the payee's account.
+    pub net_amount: Option<Money>,
+    /// The total amount refunded from the captured payment, this refund included.
+    pub total_refunded_amount: Option<Money>,
 }
 
 /// The comprehensive history of payments for the purchase unit.
@@ -412,6 +1014,87 @@ pub struct PaymentCollection {
     pub refunds: Vec<Refund>,
 }
 
+impl PaymentCollection {
+    /// The most recent capture, whatever its status.
+    pub fn latest_capture(&self) -> Option<&Capture> {
+        self.captures.iter().max_by_key(|capture| capture.create_time)
+    }
+
+    /// The amount captured, per currency.
+    ///
+    /// Declined and pending captures are not counted, refunds are not subtracted.
+    pub fn total_captured(&self) -> Result<Vec<Money>, MoneyError> {
+        sum_per_currency(self.captured_amounts(), std::iter::empty())
+    }
+
+    /// The amount refunded to the payer, per currency. Only completed refunds are counted.
+    pub fn total_refunded(&self) -> Result<Vec<Money>, MoneyError> {
+        sum_per_currency(self.refunded_amounts(&[RefundStatus::Completed]), std::iter::empty())
+    }
+
+    /// The amount that can still be refunded, per currency: the captured amount minus the completed and pending
+    /// refunds.
+    pub fn refundable_remaining(&self) -> Result<Vec<Money>, MoneyError> {
+        let refunded = self.refunded_amounts(&[RefundStatus::Completed, RefundStatus::Pending]);
+        let mut remaining = sum_per_currency(self.captured_amounts(), refunded)?;
+        for money in &mut remaining {
+            let zero = Money::zero(money.currency_code.clone());
+            if money.checked_cmp(&zero)? == std::cmp::Ordering::Less {
+                *money = zero;
+            }
+        }
+        Ok(remaining)
+    }
+
+    fn captured_amounts(&self) -> impl Iterator<Item = &Money> {
+        self.captures
+            .iter()
+            .filter(|capture| {
+                matches!(
+                    capture.status,
+                    CaptureStatus::Completed | CaptureStatus::PartiallyRefunded | CaptureStatus::Refunded
+                )
+            })
+            .filter_map(|capture| capture.amount.as_ref())
+    }
+
+    fn refunded_amounts<'a>(&'a self, statuses: &'a [RefundStatus]) -> impl Iterator<Item = &'a Money> {
+        self.refunds
+            .iter()
+            .filter(move |refund| statuses.contains(&refund.status))
+            .filter_map(|refund| refund.amount.as_ref())
+    }
+}
+
+/// Sums the added amounts minus the subtracted ones, per currency, in the order the currencies first appear.
+fn sum_per_currency<'a>(
+    added: impl Iterator<Item = &'a Money>,
+    subtracted: impl Iterator<Item = &'a Money>,
+) -> Result<Vec<Money>, MoneyError> {
+    let mut totals: Vec<Money> = Vec::new();
+    let signed = added
+        .map(|money| (money, false))
+        .chain(subtracted.map(|money| (money, true)));
+    for (money, negative) in signed {
+        let position = totals
+            .iter()
+            .position(|total| total.currency_code == money.currency_code);
+        let index = match position {
+            Some(index) => index,
+            None => {
+                totals.push(Money::zero(money.currency_code.clone()));
+                totals.len() - 1
+            }
+        };
+        totals[index] = if negative {
+            totals[index].checked_sub(money)?
+        } else {
+            totals[index].checked_add(money)?
+        };
+    }
+    Ok(totals)
+}
+
 /// Represents either a full or partial order that the payer intends to purchase from the payee.
 #[skip_serializing_none]
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -456,6 +1139,9 @@ pub struct PurchaseUnit {
     pub shipping: Option<ShippingDetail>,
     /// The comprehensive history of payments for the purchase unit.
     pub payments: Option<PaymentCollection>,
+    /// The fields paypal sent that this crate doesn't know about yet, as they were received.
+    #[serde(flatten, deserialize_with = "crate::errors::deserialize_extra")]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 impl PurchaseUnit {
@@ -469,7 +1155,7 @@ impl PurchaseUnit {
 }
 
 /// The type of landing page to show on the PayPal site for customer checkout.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LandingPage {
     /// When the customer clicks PayPal Checkout, the customer is redirected to a page to log in to PayPal and approve the payment.
@@ -480,20 +1166,22 @@ pub enum LandingPage {
     /// When the customer clicks PayPal Checkout, the customer is redirected to either a page to log in to PayPal and approve
     /// the payment or to a page to enter credit or debit card and other relevant billing information required to complete the purchase,
     /// depending on their previous interaction with PayPal.
+    #[default]
     NoPreference,
 }
 
-impl Default for LandingPage {
-    fn default() -> Self {
-        LandingPage::NoPreference
-    }
-}
+display_from_str!(LandingPage {
+    Login = "LOGIN",
+    Billing = "BILLING",
+    NoPreference = "NO_PREFERENCE",
+});
 
 /// The shipping preference
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShippingPreference {
     /// Use the customer-provided shipping address on the PayPal site.
+    #[default]
     GetFromFile,
     /// Redact the shipping address from the PayPal site. Recommended for digital goods.
     NoShipping,
@@ -501,19 +1189,20 @@ pub enum ShippingPreference {
     SetProvidedAddress,
 }
 
-impl Default for ShippingPreference {
-    fn default() -> Self {
-        ShippingPreference::GetFromFile
-    }
-}
+display_from_str!(ShippingPreference {
+    GetFromFile = "GET_FROM_FILE",
+    NoShipping = "NO_SHIPPING",
+    SetProvidedAddress = "SET_PROVIDED_ADDRESS",
+});
 
 /// Configures a Continue or Pay Now checkout flow.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum UserAction {
     /// After you redirect the customer to the PayPal payment page, a Continue button appears. Use this option when
     /// the final amount is not known when the checkout flow is initiated and you want to redirect the customer
     /// to the merchant page without processing the payment.
+    #[default]
     Continue,
     /// After you redirect the customer to the PayPal payment page, a Pay Now button appears.
     /// Use this option when the final amount is known when the checkout is initiated and you want to
@@ -521,17 +1210,17 @@ pub enum UserAction {
     PayNow,
 }
 
-impl Default for UserAction {
-    fn default() -> Self {
-        UserAction::Continue
-    }
-}
+display_from_str!(UserAction {
+    Continue = "CONTINUE",
+    PayNow = "PAY_NOW",
+});
 
 /// The merchant-preferred payment sources.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PayeePreferred {
     /// Accepts any type of payment from the customer.
+    #[default]
     Unrestricted,
     /// Accepts only immediate payment from the customer.
     /// For example, credit card, PayPal balance, or instant ACH.
@@ -539,11 +1228,10 @@ pub enum PayeePreferred {
     ImmediatePaymentRequired,
 }
 
-impl Default for PayeePreferred {
-    fn default() -> Self {
-        PayeePreferred::Unrestricted
-    }
-}
+display_from_str!(PayeePreferred {
+    Unrestricted = "UNRESTRICTED",
+    ImmediatePaymentRequired = "IMMEDIATE_PAYMENT_REQUIRED",
+});
 
 /// A payment method.
 #[skip_serializing_none]
@@ -579,6 +1267,23 @@ pub struct ApplicationContext {
     pub cancel_url: Option<String>,
 }
 
+/// The instruction to process an order.
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProcessingInstruction {
+    /// Captures or authorizes the order as soon as the payer approves it, required by some alternative payment methods
+    /// where the payer approves asynchronously.
+    OrderCompleteOnPaymentApproval,
+    /// The order is not completed automatically, the merchant must capture or authorize it.
+    #[default]
+    NoInstruction,
+}
+
+display_from_str!(ProcessingInstruction {
+    OrderCompleteOnPaymentApproval = "ORDER_COMPLETE_ON_PAYMENT_APPROVAL",
+    NoInstruction = "NO_INSTRUCTION",
+});
+
 /// A order payload to be used when creating an order.
 #[skip_serializing_none]
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -591,7 +1296,13 @@ pub struct OrderPayload {
     /// Each purchase unit represents either a full or partial order that the payer intends to purchase from the payee.
     pub purchase_units: Vec<PurchaseUnit>,
     /// Customize the payer experience during the approval process for the payment with PayPal.
+    ///
+    /// Deprecated by paypal in favor of the experience context of the payment source, see [ExperienceContext].
     pub application_context: Option<ApplicationContext>,
+    /// The payment source definition.
+    pub payment_source: Option<PaymentSource>,
+    /// The instruction to process an order.
+    pub processing_instruction: Option<ProcessingInstruction>,
 }
 
 impl OrderPayload {
@@ -605,52 +1316,490 @@ impl OrderPayload {
     }
 }
 
-/// The card brand or network.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+/// Who initiated a card payment.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum CardBrand {
-    /// Visa card.
-    Visa,
-    /// Mastecard card.
-    Mastercard,
-    /// Discover card.
-    Discover,
-    /// American Express card.
-    Amex,
-    /// Solo debit card.
-    Solo,
-    /// Japan Credit Bureau card.
-    JCB,
-    /// Military Star card.
-    Star,
-    /// Delta Airlines card.
-    Delta,
-    /// Switch credit card.
-    Switch,
-    /// Maestro credit card.
-    Maestro,
-    /// Carte Bancaire (CB) credit card.
-    CbNationale,
-    /// Configoga credit card.
-    Configoga,
-    /// Confidis credit card.
-    Confidis,
-    /// Visa Electron credit card.
-    Electron,
-    /// Cetelem credit card.
-    Cetelem,
-    /// China union pay credit card.
-    ChinaUnionPay,
+pub enum PaymentInitiator {
+    /// The payment is initiated by the card holder (CIT).
+    Customer,
+    /// The payment is initiated by the merchant, without the card holder being present (MIT).
+    Merchant,
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+display_from_str!(PaymentInitiator {
+    Customer = "CUSTOMER",
+    Merchant = "MERCHANT",
+});
+
+/// The kind of payment made with a stored card.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-#[allow(missing_docs)]
-pub enum CardType {
-    Credit,
-    Debit,
-    Prepaid,
-    Unknown,
+pub enum StoredPaymentType {
+    /// A single payment, like a purchase with a card on file.
+    OneTime,
+    /// A payment on a fixed schedule, like a subscription.
+    Recurring,
+    /// A payment made when needed, like an account top up.
+    Unscheduled,
+}
+
+display_from_str!(StoredPaymentType {
+    OneTime = "ONE_TIME",
+    Recurring = "RECURRING",
+    Unscheduled = "UNSCHEDULED",
+});
+
+/// Whether the card is being stored or was stored previously.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StoredCredentialUsage {
+    /// The first payment with the card, which is stored for later use.
+    First,
+    /// A later payment with a card stored previously.
+    Subsequent,
+    /// Paypal decides based on the card and its previous payments.
+    Derived,
+}
+
+display_from_str!(StoredCredentialUsage {
+    First = "FIRST",
+    Subsequent = "SUBSEQUENT",
+    Derived = "DERIVED",
+});
+
+/// A reference to a previous card payment, as assigned by the card network.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct NetworkTransactionReference {
+    /// The transaction ID assigned by the card network.
+    pub id: String,
+    /// The date the transaction was authorized by the card network, in MMDD format.
+    pub date: Option<String>,
+    /// The card network.
+    pub network: Option<CardBrand>,
+    /// The reference number assigned by the acquirer, used to trace the transaction.
+    pub acquirer_reference_number: Option<String>,
+}
+
+/// Information about a card stored for later payments, required by the card networks
+/// for customer and merchant initiated payments.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoredCredential {
+    /// Who initiated the payment.
+    pub payment_initiator: PaymentInitiator,
+    /// The kind of payment.
+    pub payment_type: StoredPaymentType,
+    /// Whether the card is being stored or was stored previously.
+    pub usage: Option<StoredCredentialUsage>,
+    /// The reference to the first payment made with the card, required for merchant initiated payments.
+    pub previous_network_transaction_reference: Option<NetworkTransactionReference>,
+}
+
+/// A payment card to fund a payment with.
+///
+/// Its debug output hides the card number and security code, so they don't end up in logs.
+#[skip_serializing_none]
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct Card {
+    /// The card holder's name as it appears on the card.
+    pub name: Option<String>,
+    /// The primary account number of the card.
+    pub number: Option<String>,
+    /// The card expiration year and month, in YYYY-MM format.
+    pub expiry: Option<String>,
+    /// The three- or four-digit security code of the card.
+    pub security_code: Option<String>,
+    /// The billing address for the card.
+    pub billing_address: Option<Address>,
+    /// The ID of a card stored in the vault.
+    pub vault_id: Option<String>,
+    /// Information about a card stored for later payments.
+    pub stored_credential: Option<StoredCredential>,
+    /// Customizes the payer experience, the return and cancel urls are used for 3D Secure authentication.
+    pub experience_context: Option<ExperienceContext>,
+}
+
+impl std::fmt::Debug for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "REDACTED");
+        f.debug_struct("Card")
+            .field("name", &self.name)
+            .field("number", &redacted(&self.number))
+            .field("expiry", &self.expiry)
+            .field("security_code", &redacted(&self.security_code))
+            .field("billing_address", &self.billing_address)
+            .field("vault_id", &self.vault_id)
+            .field("stored_credential", &self.stored_credential)
+            .field("experience_context", &self.experience_context)
+            .finish()
+    }
+}
+
+/// Customizes the payer experience for pay upon invoice.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PayUponInvoiceExperienceContext {
+    /// The BCP 47-formatted locale of the invoice and emails sent to the payer, for example de-DE.
+    pub locale: Option<String>,
+    /// The label that overrides the business name in the PayPal account.
+    pub brand_name: Option<String>,
+    /// The URL of the logo shown on the invoice.
+    pub logo_url: Option<String>,
+    /// How the payer can reach the merchant's customer service, shown on the invoice. Required.
+    pub customer_service_instructions: Vec<String>,
+}
+
+/// Pay upon invoice lets payers in Germany pay for their purchase within 30 days, by bank transfer to Ratepay.
+///
+/// The request must include the PayPal-Client-Metadata-Id and PayPal-Request-Id headers,
+/// see [HeaderParams](crate::client::HeaderParams).
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayUponInvoice {
+    /// The name of the payer.
+    pub name: PayerName,
+    /// The email address of the payer.
+    pub email: String,
+    /// The birth date of the payer in YYYY-MM-DD format.
+    pub birth_date: chrono::NaiveDate,
+    /// The phone number of the payer.
+    pub phone: PhoneWithCountryCode,
+    /// The billing address of the payer.
+    pub billing_address: Address,
+    /// Customizes the payer experience.
+    pub experience_context: PayUponInvoiceExperienceContext,
+}
+
+/// The bank account the payer must transfer the funds to.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct DepositBankDetails {
+    /// The business identifier code of the bank.
+    pub bic: String,
+    /// The name of the bank.
+    pub bank_name: String,
+    /// The international bank account number.
+    pub iban: String,
+    /// The holder of the account, usually Ratepay.
+    pub account_holder_name: String,
+}
+
+/// The pay upon invoice details returned with the order.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayUponInvoiceResponse {
+    /// The name of the payer.
+    pub name: Option<PayerName>,
+    /// The email address of the payer.
+    pub email: Option<String>,
+    /// The birth date of the payer.
+    pub birth_date: Option<chrono::NaiveDate>,
+    /// The phone number of the payer.
+    pub phone: Option<PhoneWithCountryCode>,
+    /// The billing address of the payer.
+    pub billing_address: Option<Address>,
+    /// The reference the payer must use in the bank transfer.
+    pub payment_reference: Option<String>,
+    /// The bank account the payer must transfer the funds to.
+    pub deposit_bank_details: Option<DepositBankDetails>,
+}
+
+/// Customizes the payer experience during the approval process for a payment source.
+///
+/// It replaces the [ApplicationContext] of the order, which converts into it.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ExperienceContext {
+    /// The label that overrides the business name in the PayPal account on the payment pages.
+    pub brand_name: Option<String>,
+    /// The BCP 47-formatted locale of the payment pages.
+    pub locale: Option<String>,
+    /// The type of landing page to show on the PayPal site, only used by the paypal payment source.
+    pub landing_page: Option<LandingPage>,
+    /// The shipping preference.
+    pub shipping_preference: Option<ShippingPreference>,
+    /// Configures a Continue or Pay Now checkout flow, only used by the paypal payment source.
+    pub user_action: Option<UserAction>,
+    /// The URL where the payer is redirected after approving the payment.
+    pub return_url: Option<String>,
+    /// The URL where the payer is redirected after cancelling the payment.
+    pub cancel_url: Option<String>,
+}
+
+impl From<ApplicationContext> for ExperienceContext {
+    /// Keeps everything but the payment method, which has no equivalent in the experience context.
+    fn from(context: ApplicationContext) -> Self {
+        ExperienceContext {
+            brand_name: context.brand_name,
+            locale: context.locale,
+            landing_page: context.landing_page,
+            shipping_preference: context.shipping_preference,
+            user_action: context.user_action,
+            return_url: context.return_url,
+            cancel_url: context.cancel_url,
+        }
+    }
+}
+
+/// A paypal account to fund a payment with.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PaypalWallet {
+    /// The email address of the payer, prefilled on the login page.
+    pub email_address: Option<String>,
+    /// The ID of a paypal account stored in the vault.
+    pub vault_id: Option<String>,
+    /// Customizes the payer experience.
+    pub experience_context: Option<ExperienceContext>,
+}
+
+/// The BLIK code the payer generated in their banking app, for a payment without redirection.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlikLevel0 {
+    /// The 6 digit code.
+    pub auth_code: String,
+}
+
+/// A BLIK one click payment, where the payer doesn't need to enter a code after the first payment.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct BlikOneClick {
+    /// The 6 digit code, required for the first payment.
+    pub auth_code: Option<String>,
+    /// The merchant generated, unique reference of the payer.
+    pub consumer_reference: String,
+    /// A label of the merchant shown in the payer's banking app, required for the first payment.
+    pub alias_label: Option<String>,
+    /// The alias of the payer's bank app, required when they have several.
+    pub alias_key: Option<String>,
+}
+
+/// BLIK, a polish mobile payment method.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Blik {
+    /// The full name of the payer.
+    pub name: String,
+    /// The country of the payer, must be PL.
+    pub country_code: Country,
+    /// The email address of the payer.
+    pub email: Option<String>,
+    /// Customizes the payer experience.
+    pub experience_context: Option<ExperienceContext>,
+    /// Pay with a code, without redirecting the payer.
+    pub level_0: Option<BlikLevel0>,
+    /// Pay without a code after the first payment.
+    pub one_click: Option<BlikOneClick>,
+}
+
+impl Blik {
+    /// Creates a BLIK payment source where the payer is redirected to enter their code.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Blik {
+            name: name.into(),
+            country_code: Country::PL,
+            email: None,
+            experience_context: None,
+            level_0: None,
+            one_click: None,
+        }
+    }
+}
+
+/// The BLIK one click details returned with the order.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlikOneClickResponse {
+    /// The merchant generated, unique reference of the payer.
+    pub consumer_reference: Option<String>,
+}
+
+/// The BLIK details returned with the order.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlikResponse {
+    /// The full name of the payer.
+    pub name: Option<String>,
+    /// The country of the payer.
+    pub country_code: Option<Country>,
+    /// The email address of the payer.
+    pub email: Option<String>,
+    /// The one click details.
+    pub one_click: Option<BlikOneClickResponse>,
+}
+
+/// Przelewy24, a polish bank transfer payment method.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct P24 {
+    /// The full name of the payer.
+    pub name: String,
+    /// The email address of the payer.
+    pub email: String,
+    /// The country of the payer, must be PL.
+    pub country_code: Country,
+    /// Customizes the payer experience.
+    pub experience_context: Option<ExperienceContext>,
+}
+
+impl P24 {
+    /// Creates a Przelewy24 payment source.
+    pub fn new(name: impl Into<String>, email: impl Into<String>) -> Self {
+        P24 {
+            name: name.into(),
+            email: email.into(),
+            country_code: Country::PL,
+            experience_context: None,
+        }
+    }
+}
+
+/// The Przelewy24 details returned with the order.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct P24Response {
+    /// The full name of the payer.
+    pub name: Option<String>,
+    /// The email address of the payer.
+    pub email: Option<String>,
+    /// The country of the payer.
+    pub country_code: Option<Country>,
+    /// The reference shown on the payer's bank statement.
+    pub payment_descriptor: Option<String>,
+    /// The ID of the bank the payer chose.
+    pub method_id: Option<String>,
+    /// The name of the bank the payer chose.
+    pub method_description: Option<String>,
+}
+
+/// A payment method where the payer is redirected to their bank to approve the payment,
+/// shared by Bancontact, eps, MyBank and Trustly.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankPaymentSource {
+    /// The full name of the payer.
+    pub name: String,
+    /// The country of the payer.
+    pub country_code: Country,
+    /// The email address of the payer, only used by Trustly.
+    pub email: Option<String>,
+    /// Customizes the payer experience.
+    pub experience_context: Option<ExperienceContext>,
+}
+
+impl BankPaymentSource {
+    /// Creates a payment source for the given payer.
+    pub fn new<S: Into<String>>(name: S, country_code: Country) -> Self {
+        BankPaymentSource {
+            name: name.into(),
+            country_code,
+            email: None,
+            experience_context: None,
+        }
+    }
+
+    /// Creates a Bancontact payment source, only available in Belgium.
+    pub fn bancontact<S: Into<String>>(name: S) -> Self {
+        Self::new(name, Country::BE)
+    }
+
+    /// Creates an eps payment source, only available in Austria.
+    pub fn eps<S: Into<String>>(name: S) -> Self {
+        Self::new(name, Country::AT)
+    }
+
+    /// Creates a MyBank payment source, only available in Italy.
+    pub fn mybank<S: Into<String>>(name: S) -> Self {
+        Self::new(name, Country::IT)
+    }
+}
+
+/// The bank payment details returned with the order.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankPaymentSourceResponse {
+    /// The full name of the payer.
+    pub name: Option<String>,
+    /// The country of the payer.
+    pub country_code: Option<Country>,
+    /// The business identifier code of the payer's bank.
+    pub bic: Option<String>,
+    /// The last characters of the payer's IBAN.
+    pub iban_last_chars: Option<String>,
+    /// The last digits of the card used, only returned by Bancontact.
+    pub card_last_digits: Option<String>,
+}
+
+/// The payment source definition.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PaymentSource {
+    /// The paypal account to use to fund a payment.
+    pub paypal: Option<PaypalWallet>,
+    /// The payment card to use to fund a payment.
+    pub card: Option<Card>,
+    /// Pay upon invoice, only available in Germany.
+    pub pay_upon_invoice: Option<PayUponInvoice>,
+    /// BLIK, only available in Poland.
+    pub blik: Option<Blik>,
+    /// Przelewy24, only available in Poland.
+    pub p24: Option<P24>,
+    /// Bancontact, only available in Belgium.
+    pub bancontact: Option<BankPaymentSource>,
+    /// eps, only available in Austria.
+    pub eps: Option<BankPaymentSource>,
+    /// MyBank, only available in Italy.
+    pub mybank: Option<BankPaymentSource>,
+    /// Trustly, available in several european countries.
+    pub trustly: Option<BankPaymentSource>,
+}
+
+string_enum! {
+    /// The card brand or network.
+    pub enum CardBrand {
+        /// Visa card.
+        Visa = "VISA",
+        /// Mastecard card.
+        Mastercard = "MASTERCARD",
+        /// Discover card.
+        Discover = "DISCOVER",
+        /// American Express card.
+        Amex = "AMEX",
+        /// Solo debit card.
+        Solo = "SOLO",
+        /// Japan Credit Bureau card.
+        JCB = "JCB",
+        /// Military Star card.
+        Star = "STAR",
+        /// Delta Airlines card.
+        Delta = "DELTA",
+        /// Switch credit card.
+        Switch = "SWITCH",
+        /// Maestro credit card.
+        Maestro = "MAESTRO",
+        /// Carte Bancaire (CB) credit card.
+        CbNationale = "CB_NATIONALE",
+        /// Configoga credit card.
+        Configoga = "CONFIGOGA",
+        /// Confidis credit card.
+        Confidis = "CONFIDIS",
+        /// Visa Electron credit card.
+        Electron = "ELECTRON",
+        /// Cetelem credit card.
+        Cetelem = "CETELEM",
+        /// China union pay credit card.
+        ChinaUnionPay = "CHINA_UNION_PAY",
+    }
+}
+
+string_enum! {
+    #[allow(missing_docs)]
+    pub enum CardType(Unrecognized) {
+        Credit = "CREDIT",
+        Debit = "DEBIT",
+        Prepaid = "PREPAID",
+        Unknown = "UNKNOWN",
+    }
 }
 
 /// The payment card to use to fund a payment.
@@ -663,6 +1812,40 @@ pub struct CardResponse {
     /// The payment card type.
     #[serde(rename = "type")]
     pub card_type: CardType,
+    /// The vault details of the card, when it was saved during the payment.
+    pub attributes: Option<PaymentSourceAttributes>,
+}
+
+string_enum! {
+    /// The status of a payment source saved during a payment.
+    pub enum VaultStatus {
+        /// The payment source is saved, its ID can be used right away.
+        Vaulted = "VAULTED",
+        /// The payment source will be saved once the payment completes.
+        Created = "CREATED",
+        /// The buyer approved saving the payment source.
+        Approved = "APPROVED",
+    }
+}
+
+/// A payment source saved during a payment.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultResponse {
+    /// The ID of the saved payment source, used as the vault id of later payments.
+    pub id: Option<String>,
+    /// The status of the saved payment source.
+    pub status: Option<VaultStatus>,
+    /// The customer the payment source is saved for.
+    pub customer: Option<crate::vault::VaultCustomer>,
+}
+
+/// Additional attributes of a payment source in a response.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentSourceAttributes {
+    /// The vault details, when the payment source was saved.
+    pub vault: Option<VaultResponse>,
 }
 
 /// The customer's wallet used to fund the transaction.
@@ -672,30 +1855,107 @@ pub struct WalletResponse {
     pub apple_pay: CardResponse,
 }
 
+string_enum! {
+    /// The status of a paypal account.
+    pub enum AccountStatus {
+        /// The account owner's identity was verified by paypal.
+        Verified = "VERIFIED",
+        /// The account owner's identity was not verified.
+        Unverified = "UNVERIFIED",
+    }
+}
+
+/// The paypal account used to fund the payment.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaypalWalletResponse {
+    /// The email address of the paypal account holder.
+    pub email_address: Option<String>,
+    /// The PayPal-assigned ID for the paypal account holder.
+    pub account_id: Option<String>,
+    /// The status of the paypal account.
+    pub account_status: Option<AccountStatus>,
+    /// The name of the paypal account holder.
+    pub name: Option<PayerName>,
+    /// The address of the paypal account holder.
+    pub address: Option<Address>,
+    /// The vault details of the paypal account, when it was saved during the payment.
+    pub attributes: Option<PaymentSourceAttributes>,
+}
+
 /// The payment source used to fund the payment.
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaymentSourceResponse {
+    /// The paypal account used to fund the payment.
+    pub paypal: Option<PaypalWalletResponse>,
     /// The payment card to use to fund a payment. Card can be a credit or debit card
     pub card: Option<CardResponse>,
     /// The customer's wallet used to fund the transaction.
     pub wallet: Option<WalletResponse>,
+    /// The pay upon invoice details, with the bank account the payer must pay to.
+    pub pay_upon_invoice: Option<PayUponInvoiceResponse>,
+    /// The BLIK details.
+    pub blik: Option<BlikResponse>,
+    /// The Przelewy24 details.
+    pub p24: Option<P24Response>,
+    /// The Bancontact details.
+    pub bancontact: Option<BankPaymentSourceResponse>,
+    /// The eps details.
+    pub eps: Option<BankPaymentSourceResponse>,
+    /// The MyBank details.
+    pub mybank: Option<BankPaymentSourceResponse>,
+    /// The Trustly details.
+    pub trustly: Option<BankPaymentSourceResponse>,
 }
 
-/// The status of an order.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum OrderStatus {
-    /// The order was created with the specified context.
-    Created,
-    /// The order was saved and persisted. The order status continues to be in progress until a capture
-    /// is made with final_capture = true for all purchase units within the order.
-    Saved,
-    /// The customer approved the payment through the PayPal wallet or another form of guest or unbranded payment. For example, a card, bank account, or so on.
-    Approved,
-    /// All purchase units in the order are voided.
-    Voided,
-    /// The payment was authorized or the authorized payment was captured for the order.
-    Completed,
+impl PaymentSourceResponse {
+    /// The ID of the card or paypal account saved during the payment, if it was saved.
+    ///
+    /// Orders only carry it when shown with [OrderField::PaymentSource].
+    pub fn vault_id(&self) -> Option<&str> {
+        let card = self.card.as_ref().and_then(|card| card.attributes.as_ref());
+        let paypal = self.paypal.as_ref().and_then(|paypal| paypal.attributes.as_ref());
+        card.or(paypal)
+            .and_then(|attributes| attributes.vault.as_ref())
+            .and_then(|vault| vault.id.as_deref())
+    }
+}
+
+string_enum! {
+    /// A group of fields of an order left out of the responses unless asked for.
+    pub enum OrderField {
+        /// The payment source, with the vault details of a payment source saved during checkout.
+        PaymentSource = "payment_source",
+    }
+}
+
+string_enum! {
+    /// The status of an order.
+    pub enum OrderStatus {
+        /// The order was created with the specified context.
+        Created = "CREATED",
+        /// The order was saved and persisted. The order status continues to be in progress until a capture
+        /// is made with final_capture = true for all purchase units within the order.
+        Saved = "SAVED",
+        /// The customer approved the payment through the PayPal wallet or another form of guest or unbranded payment. For example, a card, bank account, or so on.
+        Approved = "APPROVED",
+        /// All purchase units in the order are voided.
+        Voided = "VOIDED",
+        /// The payment was authorized or the authorized payment was captured for the order.
+        Completed = "COMPLETED",
+        /// The order requires an action from the payer, like 3D Secure authentication or approving a bank payment.
+        PayerActionRequired = "PAYER_ACTION_REQUIRED",
+        /// The order is waiting for approval, as with pay upon invoice until Ratepay accepts the payer.
+        PendingApproval = "PENDING_APPROVAL",
+    }
+}
+
+impl OrderStatus {
+    /// Whether the order can't change status anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Completed | OrderStatus::Voided)
+    }
 }
 
 /// An order represents a payment between two or more parties.
@@ -721,6 +1981,30 @@ pub struct Order {
     pub status: OrderStatus,
     /// An array of request-related HATEOAS links. To complete payer approval, use the approve link to redirect the payer.
     pub links: Vec<LinkDescription>,
+    /// The fields paypal sent that this crate doesn't know about yet, as they were received.
+    #[serde(flatten, deserialize_with = "crate::errors::deserialize_extra")]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// How [Client::wait_for_order_status] polls an order.
+#[derive(Debug, Clone)]
+pub struct PollConfig {
+    /// The delay before the first poll after the initial one, doubled after every poll.
+    pub initial_delay: std::time::Duration,
+    /// The maximum delay between two polls.
+    pub max_delay: std::time::Duration,
+    /// Stop polling once this much time has passed since the first poll.
+    pub timeout: std::time::Duration,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig {
+            initial_delay: std::time::Duration::from_secs(1),
+            max_delay: std::time::Duration::from_secs(10),
+            timeout: std::time::Duration::from_secs(120),
+        }
+    }
 }
 
 impl Client {
@@ -732,18 +2016,78 @@ impl Client {
     ) -> Result<Order, ResponseError> {
         let builder = {
             self.setup_headers(
-                self.client.post(&format!("{}/v2/checkout/orders", self.endpoint())),
+                self.client.post(format!("{}/v2/checkout/orders", self.endpoint())),
                 header_params,
             )
             .await
         };
-        let res = builder.json(&order).send().await?;
+        let res = self.execute(builder.json(&order)).await?;
 
         if res.status().is_success() {
-            let order = res.json::<Order>().await?;
+            let order = decode::<Order>(res, self.decode_mode).await?;
             Ok(order)
         } else {
-            Err(ResponseError::ApiError(res.json::<PaypalError>().await?))
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Creates an order at most once for the given idempotency key, like an id of the cart or of the job.
+    ///
+    /// The key is sent as the `PayPal-Request-Id`, so paypal answers a retry with the order it already created, and
+    /// the ID of the order is saved in the store. A call with a key found in the store returns the current details
+    /// of that order instead of sending the payload again, even after the request id expired at paypal.
+    ///
+    /// The key replaces the request id of the header params, and can't be longer than
+    /// [MAX_KEY_LENGTH](crate::idempotency::MAX_KEY_LENGTH) characters.
+    pub async fn create_order_idempotent(
+        &mut self,
+        order: OrderPayload,
+        key: &str,
+        store: &dyn crate::idempotency::OrderKeyStore,
+        header_params: HeaderParams,
+    ) -> Result<Order, ResponseError> {
+        if key.chars().count() > crate::idempotency::MAX_KEY_LENGTH {
+            return Err(ResponseError::InvalidIdempotencyKey(key.to_owned()));
+        }
+        if let Some(order_id) = store.get(key).await {
+            return self.show_order_details(&order_id).await;
+        }
+
+        let header_params = HeaderParams {
+            request_id: Some(key.to_owned()),
+            ..header_params
+        };
+        let order = self.create_order(order, header_params).await?;
+        store.put(key, &order.id).await;
+        Ok(order)
+    }
+
+    /// Polls the order until it has the target status, like an order paid with an alternative payment method
+    /// waiting for the payer's approval, without relying on the webhooks which may be delayed.
+    ///
+    /// Returns the last order fetched: its status is the target one unless the order reached a terminal status
+    /// first, or the timeout passed.
+    pub async fn wait_for_order_status(
+        &mut self,
+        order_id: &str,
+        target_status: OrderStatus,
+        config: PollConfig,
+    ) -> Result<Order, ResponseError> {
+        let start = std::time::Instant::now();
+        let mut delay = config.initial_delay;
+        loop {
+            let order = self.show_order_details(order_id).await?;
+            if order.status == target_status || order.status.is_terminal() {
+                return Ok(order);
+            }
+
+            let remaining = config.timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                return Ok(order);
+            }
+            log::debug!(target: "paypal-rs", "order {} is {}, polling again in {:?}", order_id, order.status, delay);
+            tokio::time::sleep(delay.min(remaining)).await;
+            delay = (delay * 2).min(config.max_delay);
         }
     }
 
@@ -767,20 +2111,21 @@ impl Client {
             )
             .await;
 
-        let res = builder.send().await?;
+        let res = self.execute(builder).await?;
 
         if res.status().is_success() {
-            let order = res.json::<Order>().await?;
+            let order = decode::<Order>(res, self.decode_mode).await?;
             Ok(order)
         } else {
-            Err(ResponseError::ApiError(res.json::<PaypalError>().await?))
+            Err(ResponseError::from_response(res).await)
         }
     }
 
     /// Updates an order with the CREATED or APPROVED status.
     /// You cannot update an order with the COMPLETED status.
     ///
-    /// Only replacing the existing purchase units and intent is supported right now.
+    /// Replaces the intent and the purchase units with the same reference id, see [patch_order](Client::patch_order)
+    /// for other changes.
     ///
     /// Note: You can only update the intent from Authorize to Capture
     ///
@@ -791,87 +2136,87 @@ impl Client {
         intent: Option<Intent>,
         purchase_units: Option<Vec<PurchaseUnit>>,
     ) -> Result<(), ResponseError> {
-        let mut intent_json = String::new();
-        let units_json = String::new();
-
-        if let Some(p_units) = purchase_units {
-            let mut units_json = String::new();
-
-            for (i, unit) in p_units.iter().enumerate() {
-                let unit_str = serde_json::to_string(&unit).expect("error serializing purchase unit");
-                let mut unit_json = format!(
-                    r#"
-                {{
-                    "op": "replace",
-                    "path": "/purchase_units/@reference_id='{reference_id}'",
-                    "value": {unit}
-                }}
-                "#,
-                    reference_id = unit.reference_id.clone().unwrap_or_else(|| String::from("default")),
-                    unit = unit_str
-                );
-
-                if i < p_units.len() - 1 {
-                    unit_json += ",";
-                }
-
-                units_json.push_str(&unit_json);
-            }
+        let mut patches = Vec::new();
+        if let Some(intent) = intent {
+            patches.push(Patch::intent(intent));
         }
-
-        if let Some(x) = intent {
-            let intent_str = match x {
-                Intent::Authorize => String::from("AUTHORIZE"),
-                Intent::Capture => String::from("CAPTURE"),
-            };
-
-            intent_json = format!(
-                r#"
-                {{
-                    "op": "replace",
-                    "path": "/intent",
-                    "value": "{intent}"
-                }}
-                "#,
-                intent = intent_str
-            );
+        for unit in purchase_units.iter().flatten() {
+            patches.push(Patch::purchase_unit(unit));
         }
+        self.patch_order(id, &patches).await
+    }
 
-        let final_json = {
-            if !intent_json.is_empty() && !units_json.is_empty() {
-                format!("[{},{}]", intent_json, units_json)
-            } else {
-                format!("[{}{}]", intent_json, units_json)
-            }
-        };
-
-        let builder = {
-            self.setup_headers(
+    /// Updates an order with the given patch operations.
+    ///
+    /// See [Patch] and [purchase_unit_path] to build them.
+    pub async fn patch_order(&mut self, order_id: &str, patches: &[Patch]) -> Result<(), ResponseError> {
+        let builder = self
+            .setup_headers(
                 self.client
-                    .patch(&format!("{}/v2/checkout/orders/{}", self.endpoint(), id)),
-                crate::client::HeaderParams {
+                    .patch(format!("{}/v2/checkout/orders/{}", self.endpoint(), order_id)),
+                HeaderParams {
                     content_type: Some(String::from("application/json")),
                     ..Default::default()
                 },
             )
-            .await
-        };
+            .await;
 
-        let res = builder.body(final_json.clone()).send().await?;
+        let res = self.execute(builder.json(patches)).await?;
 
         if res.status().is_success() {
             Ok(())
         } else {
-            Err(ResponseError::ApiError(res.json::<PaypalError>().await?))
+            Err(ResponseError::from_response(res).await)
         }
     }
 
+    /// Replaces the shipping details of the default purchase unit of an order, see [Patch::shipping].
+    pub async fn set_order_shipping(&mut self, order_id: &str, shipping: &ShippingDetail) -> Result<(), ResponseError> {
+        self.patch_order(order_id, &Patch::shipping("default", shipping)).await
+    }
+
+    /// Replaces the amount of the default purchase unit of an order.
+    pub async fn set_order_amount(&mut self, order_id: &str, amount: &Amount) -> Result<(), ResponseError> {
+        self.patch_order(order_id, &[Patch::amount("default", amount)]).await
+    }
+
+    /// Sets the invoice id of the default purchase unit of an order.
+    pub async fn set_order_invoice_id(&mut self, order_id: &str, invoice_id: &str) -> Result<(), ResponseError> {
+        self.patch_order(order_id, &[Patch::invoice_id("default", invoice_id)])
+            .await
+    }
+
     /// Shows details for an order, by ID.
     pub async fn show_order_details(&mut self, order_id: &str) -> Result<Order, ResponseError> {
         self.build_endpoint_order(order_id, "", false, HeaderParams::default())
             .await
     }
 
+    /// Shows details for an order, by ID, along with the given fields left out by default,
+    /// like the payment source holding the vault id of a card saved during checkout.
+    pub async fn show_order_details_with_fields(
+        &mut self,
+        order_id: &str,
+        fields: &[OrderField],
+    ) -> Result<Order, ResponseError> {
+        let mut builder = self
+            .client
+            .get(format!("{}/v2/checkout/orders/{}", self.endpoint(), order_id));
+        if !fields.is_empty() {
+            let fields: Vec<&str> = fields.iter().map(OrderField::as_str).collect();
+            builder = builder.query(&[("fields", fields.join(","))]);
+        }
+        let builder = self.setup_headers(builder, HeaderParams::default()).await;
+
+        let res = self.execute(builder).await?;
+
+        if res.status().is_success() {
+            decode::<Order>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
     /// Captures payment for an order. To successfully capture payment for an order,
     /// the buyer must first approve the order or a valid payment_source must be provided in the request.
     /// A buyer can approve the order upon being redirected to the rel:approve URL that was returned in the HATEOAS links in the create order response.
@@ -884,6 +2229,30 @@ impl Client {
             .await
     }
 
+    /// Captures payment for an order with a body, like the platform fees to take on PayPal Commerce Platform.
+    pub async fn capture_order_with(
+        &mut self,
+        order_id: &str,
+        payload: &OrderCapturePayload,
+        header_params: HeaderParams,
+    ) -> Result<Order, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v2/checkout/orders/{}/capture", self.endpoint(), order_id)),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(builder.json(payload)).await?;
+
+        if res.status().is_success() {
+            decode::<Order>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
     /// Authorizes payment for an order. To successfully authorize payment for an order,
     /// the buyer must first approve the order or a valid payment_source must be provided in the request.
     /// A buyer can approve the order upon being redirected to the rel:approve URL that was returned in the HATEOAS links in the create order response.
@@ -0,0 +1,372 @@
+//! Use the Payouts API to make payments to multiple PayPal or Venmo recipients.
+//! The Payouts API is a fast, convenient way to send commissions, rebates, rewards, and general disbursements.
+//!
+//! Reference: https://developer.paypal.com/docs/api/payments.payouts-batch/v1/
+
+use crate::client::{Client, HeaderParams};
+use crate::common::*;
+use crate::errors::{decode, PaypalError, ResponseError};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+string_enum! {
+    /// How the receiver of a payout item is identified.
+    pub enum RecipientType {
+        /// The unencrypted email address of the receiver.
+        Email = "EMAIL",
+        /// The mobile number of the receiver, required for Venmo recipients without a handle.
+        Phone = "PHONE",
+        /// The encrypted PayPal account number of the receiver.
+        PaypalId = "PAYPAL_ID",
+    }
+}
+
+string_enum! {
+    /// The wallet a payout item is sent to.
+    pub enum RecipientWallet {
+        /// A PayPal account.
+        Paypal = "PAYPAL",
+        /// A Venmo account.
+        Venmo = "VENMO",
+    }
+}
+
+string_enum! {
+    /// The status of a payout batch.
+    pub enum BatchStatus {
+        /// The payouts were denied.
+        Denied = "DENIED",
+        /// The payouts are pending.
+        Pending = "PENDING",
+        /// The payouts are being processed.
+        Processing = "PROCESSING",
+        /// The payouts were processed, the items may still have failed individually.
+        Success = "SUCCESS",
+        /// The payouts were canceled.
+        Canceled = "CANCELED",
+    }
+}
+
+string_enum! {
+    /// The status of a payout item.
+    pub enum PayoutItemStatus {
+        /// The funds were credited to the receiver.
+        Success = "SUCCESS",
+        /// The item failed, see its errors.
+        Failed = "FAILED",
+        /// The item is being processed.
+        Pending = "PENDING",
+        /// The receiver has no account, the funds are returned if it's not claimed within 30 days.
+        Unclaimed = "UNCLAIMED",
+        /// The funds were returned to the sender since the receiver didn't claim them.
+        Returned = "RETURNED",
+        /// The item is on hold for a review.
+        Onhold = "ONHOLD",
+        /// The item was blocked.
+        Blocked = "BLOCKED",
+        /// The item was refunded.
+        Refunded = "REFUNDED",
+        /// The item was reversed.
+        Reversed = "REVERSED",
+    }
+}
+
+/// An amount of a payout, which unlike [Money] names the currency `currency`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PayoutAmount {
+    /// The three-character ISO-4217 currency code.
+    pub currency: Currency,
+    /// The value, which might be an integer or a decimal fraction.
+    pub value: String,
+}
+
+impl PayoutAmount {
+    /// Creates a new amount with the given currency and value.
+    pub fn new(currency: Currency, value: &str) -> Self {
+        PayoutAmount {
+            currency,
+            value: value.to_owned(),
+        }
+    }
+}
+
+/// The sender-provided header of a payout batch.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SenderBatchHeader {
+    /// A sender-specified ID, a batch with the same ID as one sent in the last 30 days is rejected.
+    pub sender_batch_id: Option<String>,
+    /// The subject line of the email the receivers get.
+    pub email_subject: Option<String>,
+    /// The email message the receivers get.
+    pub email_message: Option<String>,
+    /// The default recipient type of the items.
+    pub recipient_type: Option<RecipientType>,
+    /// The payouts and item-level notes are concatenated in the email, up to 1000 characters.
+    pub note: Option<String>,
+}
+
+/// A payment to a single receiver.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutItem {
+    /// How the receiver is identified, overriding the one of the batch.
+    pub recipient_type: Option<RecipientType>,
+    /// The amount to pay.
+    pub amount: PayoutAmount,
+    /// A note for the receiver.
+    pub note: Option<String>,
+    /// The receiver of the payment: an email, a phone number or a PayPal id, depending on the recipient type.
+    pub receiver: String,
+    /// A sender-specified ID to track the item.
+    pub sender_item_id: Option<String>,
+    /// The wallet the payment is sent to, PayPal by default.
+    pub recipient_wallet: Option<RecipientWallet>,
+    /// The language of the notifications sent to the receiver, like `en-US`.
+    pub notification_language: Option<String>,
+}
+
+impl PayoutItem {
+    /// Creates an item paying the given receiver.
+    pub fn new<S: Into<String>>(recipient_type: RecipientType, receiver: S, amount: PayoutAmount) -> Self {
+        PayoutItem {
+            recipient_type: Some(recipient_type),
+            amount,
+            note: None,
+            receiver: receiver.into(),
+            sender_item_id: None,
+            recipient_wallet: None,
+            notification_language: None,
+        }
+    }
+
+    /// Sends the payment to a Venmo account, the receiver must be a phone number or a Venmo handle.
+    pub fn venmo(mut self) -> Self {
+        self.recipient_wallet = Some(RecipientWallet::Venmo);
+        self
+    }
+}
+
+/// The payload to create a payout batch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutPayload {
+    /// The header of the batch.
+    pub sender_batch_header: SenderBatchHeader,
+    /// The items of the batch, up to 15000.
+    pub items: Vec<PayoutItem>,
+}
+
+/// The conversion of a payout item to the currency of the receiver.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurrencyConversion {
+    /// The amount sent.
+    pub from_amount: PayoutAmount,
+    /// The amount received.
+    pub to_amount: PayoutAmount,
+    /// The exchange rate applied.
+    pub exchange_rate: String,
+}
+
+/// The header of a payout batch.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutBatchHeader {
+    /// The ID of the batch.
+    pub payout_batch_id: String,
+    /// The status of the batch.
+    pub batch_status: BatchStatus,
+    /// The date and time when processing of the batch started.
+    pub time_created: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when processing of the batch completed.
+    pub time_completed: Option<chrono::DateTime<chrono::Utc>>,
+    /// The header the batch was created with.
+    pub sender_batch_header: SenderBatchHeader,
+    /// The total amount of the batch.
+    pub amount: Option<PayoutAmount>,
+    /// The total fees of the batch.
+    pub fees: Option<PayoutAmount>,
+}
+
+/// The result of a payout item.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutItemDetails {
+    /// The ID of the item.
+    pub payout_item_id: String,
+    /// The ID of the PayPal transaction, once processed.
+    pub transaction_id: Option<String>,
+    /// The status of the item.
+    pub transaction_status: Option<PayoutItemStatus>,
+    /// The fee charged for the item.
+    pub payout_item_fee: Option<PayoutAmount>,
+    /// The ID of the batch the item belongs to.
+    pub payout_batch_id: String,
+    /// The sender-specified ID of the batch.
+    pub sender_batch_id: Option<String>,
+    /// The item as it was sent.
+    pub payout_item: PayoutItem,
+    /// The conversion to the currency of the receiver, when it differs.
+    pub currency_conversion: Option<CurrencyConversion>,
+    /// The date and time when the item was processed.
+    pub time_processed: Option<chrono::DateTime<chrono::Utc>>,
+    /// Why the item failed.
+    pub errors: Option<PaypalError>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+/// A payout batch.
+///
+/// Its items are only returned by [Client::create_sync_payout] and [Client::show_payout_batch_details].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutBatch {
+    /// The header of the batch.
+    pub batch_header: PayoutBatchHeader,
+    /// The items of the batch.
+    #[serde(default)]
+    pub items: Vec<PayoutItemDetails>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+impl Client {
+    /// Creates a batch payout, which is processed asynchronously: its items are not returned,
+    /// poll [show_payout_batch_details](Client::show_payout_batch_details) or listen to the payout webhooks instead.
+    pub async fn create_batch_payout(
+        &mut self,
+        payload: &PayoutPayload,
+        header_params: HeaderParams,
+    ) -> Result<PayoutBatch, ResponseError> {
+        self.post_payout(payload, false, header_params).await
+    }
+
+    /// Creates a payout with a single item and waits for it to be processed, returning the item.
+    pub async fn create_sync_payout(
+        &mut self,
+        payload: &PayoutPayload,
+        header_params: HeaderParams,
+    ) -> Result<PayoutBatch, ResponseError> {
+        self.post_payout(payload, true, header_params).await
+    }
+
+    async fn post_payout(
+        &mut self,
+        payload: &PayoutPayload,
+        sync_mode: bool,
+        header_params: HeaderParams,
+    ) -> Result<PayoutBatch, ResponseError> {
+        let mut builder = self
+            .client
+            .post(format!("{}/v1/payments/payouts", self.endpoint()).as_str());
+        if sync_mode {
+            builder = builder.query(&[("sync_mode", "true")]);
+        }
+
+        let build = self.setup_headers(builder, header_params).await;
+        let res = self.execute(build.json(payload)).await?;
+
+        if res.status().is_success() {
+            let batch = decode::<PayoutBatch>(res, self.decode_mode).await?;
+            Ok(batch)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Shows the status and the items of a payout batch, by ID.
+    pub async fn show_payout_batch_details(
+        &mut self,
+        payout_batch_id: &str,
+        header_params: HeaderParams,
+    ) -> Result<PayoutBatch, ResponseError> {
+        let build = self
+            .setup_headers(
+                self.client
+                    .get(format!("{}/v1/payments/payouts/{}", self.endpoint(), payout_batch_id).as_str()),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build).await?;
+
+        if res.status().is_success() {
+            let batch = decode::<PayoutBatch>(res, self.decode_mode).await?;
+            Ok(batch)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payouts() {
+        let payload = PayoutPayload {
+            sender_batch_header: SenderBatchHeader {
+                sender_batch_id: Some("Payouts_2020_100007".to_owned()),
+                ..Default::default()
+            },
+            items: vec![PayoutItem::new(
+                RecipientType::Phone,
+                "9999999999",
+                PayoutAmount::new(Currency::USD, "9.87"),
+            )
+            .venmo()],
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["items"][0]["recipient_type"], "PHONE");
+        assert_eq!(json["items"][0]["recipient_wallet"], "VENMO");
+        assert_eq!(json["items"][0]["amount"]["currency"], "USD");
+
+        let batch: PayoutBatch = serde_json::from_str(
+            r#"{
+                "batch_header": {
+                    "payout_batch_id": "5UXD2E8A7EBQJ",
+                    "batch_status": "SUCCESS",
+                    "time_created": "2020-01-17T19:54:30Z",
+                    "time_completed": "2020-01-17T19:54:31Z",
+                    "sender_batch_header": {"sender_batch_id": "Payouts_2020_100007"},
+                    "amount": {"currency": "USD", "value": "9.87"},
+                    "fees": {"currency": "USD", "value": "0.25"}
+                },
+                "items": [
+                    {
+                        "payout_item_id": "8AELMXH8UB2P8",
+                        "transaction_id": "0C413693MN970190K",
+                        "transaction_status": "SUCCESS",
+                        "payout_item_fee": {"currency": "USD", "value": "0.25"},
+                        "payout_batch_id": "5UXD2E8A7EBQJ",
+                        "payout_item": {
+                            "recipient_type": "PHONE",
+                            "amount": {"currency": "USD", "value": "9.87"},
+                            "receiver": "9999999999",
+                            "recipient_wallet": "VENMO"
+                        },
+                        "currency_conversion": {
+                            "from_amount": {"currency": "USD", "value": "9.87"},
+                            "to_amount": {"currency": "EUR", "value": "8.86"},
+                            "exchange_rate": "0.8977"
+                        },
+                        "time_processed": "2020-01-17T19:54:31Z",
+                        "links": []
+                    }
+                ],
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(batch.batch_header.batch_status, BatchStatus::Success);
+        let item = &batch.items[0];
+        assert_eq!(item.transaction_status, Some(PayoutItemStatus::Success));
+        assert_eq!(item.payout_item.recipient_wallet, Some(RecipientWallet::Venmo));
+        assert_eq!(
+            item.currency_conversion.as_ref().unwrap().to_amount.currency,
+            Currency::EUR
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Clients for platforms making api calls on behalf of many merchants.
+//!
+//! A [ClientPool] builds a [Client] the first time a merchant is used and keeps it, along with its access token,
+//! so routing a request to a merchant doesn't require authenticating again.
+//! The credentials of each merchant are given by a [CredentialProvider], merchants can either use their own app
+//! credentials, or the partner credentials of the platform with their payer id sent through PayPal-Auth-Assertion.
+//!
+//! ```no_run
+//! use paypal_rs::pool::{ClientPool, MerchantCredentials};
+//! use paypal_rs::Client;
+//! use std::collections::HashMap;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut merchants = HashMap::new();
+//! merchants.insert("shop-1".to_owned(), MerchantCredentials::Partner { payer_id: "JX6W4QSRMA6YA".to_owned() });
+//! merchants.insert(
+//!     "shop-2".to_owned(),
+//!     MerchantCredentials::Own { client_id: "client_id".to_owned(), secret: "secret".to_owned() },
+//! );
+//!
+//! let builder = Client::builder().client_id("partner_client_id").secret("partner_secret");
+//! let mut pool = ClientPool::new(builder, merchants);
+//!
+//! let order = pool.client("shop-1")?.show_order_details("5O190127TN364715T").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{Client, ClientBuilder};
+use crate::errors::PoolError;
+use crate::token_store::MemoryTokenStore;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// How the requests for a merchant are authenticated.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MerchantCredentials {
+    /// The merchant's own app credentials.
+    Own {
+        /// The client id of the merchant's app.
+        client_id: String,
+        /// The secret of the merchant's app.
+        secret: String,
+    },
+    /// The partner credentials the pool was created with, acting on behalf of the merchant with the given payer id.
+    Partner {
+        /// The payer id of the merchant.
+        payer_id: String,
+    },
+}
+
+/// Gives the credentials of each merchant.
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// Returns the credentials of the given merchant, if it's known.
+    fn credentials(&self, merchant_id: &str) -> Option<MerchantCredentials>;
+}
+
+impl CredentialProvider for HashMap<String, MerchantCredentials> {
+    fn credentials(&self, merchant_id: &str) -> Option<MerchantCredentials> {
+        self.get(merchant_id).cloned()
+    }
+}
+
+/// Keeps a [Client] per merchant, built from a shared configuration.
+#[derive(Debug)]
+pub struct ClientPool {
+    builder: ClientBuilder,
+    provider: Box<dyn CredentialProvider>,
+    clients: HashMap<String, Client>,
+}
+
+impl ClientPool {
+    /// Creates a pool whose clients are configured like the given builder, which also holds the partner credentials.
+    ///
+    /// Unless the builder has a token store, the clients share a [MemoryTokenStore],
+    /// so the merchants using the partner credentials share its access token.
+    pub fn new<P: CredentialProvider + 'static>(mut builder: ClientBuilder, provider: P) -> Self {
+        builder
+            .token_store
+            .get_or_insert_with(|| Arc::new(MemoryTokenStore::new()));
+
+        ClientPool {
+            builder,
+            provider: Box::new(provider),
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Returns the client of the given merchant, building it the first time.
+    pub fn client(&mut self, merchant_id: &str) -> Result<&mut Client, PoolError> {
+        if !self.clients.contains_key(merchant_id) {
+            let credentials = self
+                .provider
+                .credentials(merchant_id)
+                .ok_or_else(|| PoolError::UnknownMerchant(merchant_id.to_owned()))?;
+
+            let builder = match credentials {
+                MerchantCredentials::Own { client_id, secret } => {
+                    self.builder.clone().client_id(client_id).secret(secret)
+                }
+                MerchantCredentials::Partner { payer_id } => self.builder.clone().merchant_payer_id(payer_id),
+            };
+            self.clients.insert(merchant_id.to_owned(), builder.build()?);
+        }
+
+        Ok(self.clients.get_mut(merchant_id).unwrap())
+    }
+
+    /// Drops the client of the given merchant, for example after its credentials changed.
+    pub fn remove(&mut self, merchant_id: &str) -> Option<Client> {
+        self.clients.remove(merchant_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool() {
+        let mut merchants = HashMap::new();
+        merchants.insert(
+            "shop-1".to_owned(),
+            MerchantCredentials::Partner {
+                payer_id: "JX6W4QSRMA6YA".to_owned(),
+            },
+        );
+        merchants.insert(
+            "shop-2".to_owned(),
+            MerchantCredentials::Own {
+                client_id: "shop_client_id".to_owned(),
+                secret: "shop_secret".to_owned(),
+            },
+        );
+
+        let builder = Client::builder()
+            .client_id("partner_client_id")
+            .secret("partner_secret");
+        let mut pool = ClientPool::new(builder, merchants);
+        assert!(!format!("{:?}", pool).contains("partner_secret"));
+
+        let client = pool.client("shop-1").unwrap();
+        assert_eq!(client.auth.client_id, "partner_client_id");
+        assert_eq!(client.merchant_payer_id.as_deref(), Some("JX6W4QSRMA6YA"));
+        assert!(client
+            .headers(Default::default())
+            .unwrap()
+            .contains_key("PayPal-Auth-Assertion"));
+
+        let client = pool.client("shop-2").unwrap();
+        assert_eq!(client.auth.client_id, "shop_client_id");
+        assert_eq!(client.merchant_payer_id, None);
+
+        assert!(matches!(pool.client("shop-3"), Err(PoolError::UnknownMerchant(_))));
+    }
+}
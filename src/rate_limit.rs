@@ -0,0 +1,140 @@
+//! Client side rate limiting, so bulk jobs throttle themselves instead of running into paypal's limits.
+//!
+//! A [RateLimiter] holds token buckets: every request, retries included, takes a token and waits when the bucket is
+//! empty. Requests to the paths given to [RateLimiter::endpoint] use their own bucket, like a slower one for the
+//! payouts or transaction search apis, the others share the default one.
+//!
+//! ```
+//! use paypal_rs::rate_limit::{RateLimit, RateLimiter};
+//!
+//! let limiter = RateLimiter::new(RateLimit::new(20, 40))
+//!     .endpoint("/v1/reporting/transactions", RateLimit::new(2, 2));
+//! ```
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A number of requests per second, with a burst allowed above it.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// The number of requests per second in the long run, 0 doesn't limit the requests.
+    pub per_second: u32,
+    /// The number of requests that can be sent at once after a pause, at least 1.
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Creates a rate limit.
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        RateLimit { per_second, burst }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Bucket {
+            limit,
+            state: Mutex::new((f64::from(limit.burst.max(1)), Instant::now())),
+        }
+    }
+
+    /// Takes a token, returns how long to wait before it can be used.
+    ///
+    /// The token is taken even if the bucket is empty, so the waiting requests are served in order.
+    fn reserve(&self, now: Instant) -> Duration {
+        if self.limit.per_second == 0 {
+            return Duration::ZERO;
+        }
+        let rate = f64::from(self.limit.per_second);
+        let mut state = self.state.lock().unwrap();
+        let (tokens, last) = &mut *state;
+        let elapsed = now.saturating_duration_since(*last).as_secs_f64();
+        *tokens = (*tokens + elapsed * rate).min(f64::from(self.limit.burst.max(1))) - 1.0;
+        *last = now.max(*last);
+        if *tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / rate)
+        }
+    }
+}
+
+/// Delays requests to stay within rate limits, see the [module documentation](self).
+#[derive(Debug)]
+pub struct RateLimiter {
+    default: Bucket,
+    endpoints: Vec<(String, Bucket)>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter applying the given limit to every request.
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            default: Bucket::new(limit),
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// Gives the requests whose path starts with the prefix their own limit, instead of the default one.
+    ///
+    /// When several prefixes match, the first one added is used.
+    pub fn endpoint<S: Into<String>>(mut self, path_prefix: S, limit: RateLimit) -> Self {
+        self.endpoints.push((path_prefix.into(), Bucket::new(limit)));
+        self
+    }
+
+    fn reserve(&self, path: &str, now: Instant) -> Duration {
+        self.endpoints
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map_or(&self.default, |(_, bucket)| bucket)
+            .reserve(now)
+    }
+
+    /// Waits until a request to the given path can be sent.
+    pub async fn acquire(&self, path: &str) {
+        let delay = self.reserve(path, Instant::now());
+        if !delay.is_zero() {
+            log::debug!(target: "paypal-rs", "rate limiting request to {} for {:?}", path, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(RateLimit::new(10, 2)).endpoint("/v1/payments/payouts", RateLimit::new(1, 1));
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve("/v2/checkout/orders", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("/v2/checkout/orders", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("/v2/checkout/orders", now), Duration::from_millis(100));
+        assert_eq!(limiter.reserve("/v2/checkout/orders", now), Duration::from_millis(200));
+
+        // The payouts have their own bucket.
+        assert_eq!(
+            limiter.reserve("/v1/payments/payouts/FYXMPQTX4JC9N", now),
+            Duration::ZERO
+        );
+        assert_eq!(limiter.reserve("/v1/payments/payouts", now), Duration::from_secs(1));
+
+        // After a pause, the bucket refills up to the burst.
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve("/v2/checkout/orders", later), Duration::ZERO);
+        assert_eq!(limiter.reserve("/v2/checkout/orders", later), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve("/v2/checkout/orders", later),
+            Duration::from_millis(100)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Protection against replayed webhook deliveries.
+//!
+//! A valid signature only proves that paypal sent a notification once, anyone who intercepted it can send it
+//! again. A [ReplayGuard] rejects deliveries whose transmission time is too far from now, and remembers the
+//! transmission and event ids it saw in a [SeenStore] to reject them the second time.
+//!
+//! Ids are only recorded once the signature of the delivery was verified, so the guard takes the [Verification]
+//! returned by [Client::verify_signature](crate::client::Client::verify_signature). Otherwise a forged delivery
+//! reusing the id of a genuine one sent later would get the genuine one rejected. The transmission time can be
+//! checked before verifying with [check_transmission_time], to not call paypal for stale deliveries.
+//!
+//! PayPal also delivers the same event several times on its own when the listener doesn't answer with a 2xx,
+//! [ReplayGuard::check_event] tells these apart so the event is only processed once.
+
+use crate::errors::ReplayError;
+use crate::webhooks::{Verification, VerificationStatus, WebhookVerificationPayload};
+use futures_util::future::BoxFuture;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// Remembers the ids of the webhook deliveries already seen.
+///
+/// Share a store between the instances of an application, like one on top of Redis, or a delivery replayed to
+/// another instance is accepted again.
+pub trait SeenStore: Debug + Send + Sync {
+    /// Remembers the key until the given time, returns false if it was already there.
+    ///
+    /// Checking and inserting must be atomic, or two concurrent deliveries could both be accepted.
+    fn insert<'a>(&'a self, key: &'a str, expires_at: chrono::DateTime<chrono::Utc>) -> BoxFuture<'a, bool>;
+}
+
+impl<T: SeenStore + ?Sized> SeenStore for Arc<T> {
+    fn insert<'a>(&'a self, key: &'a str, expires_at: chrono::DateTime<chrono::Utc>) -> BoxFuture<'a, bool> {
+        (**self).insert(key, expires_at)
+    }
+}
+
+/// Keeps the ids in memory, share it with an [Arc] to use it from several guards.
+#[derive(Debug, Default)]
+pub struct MemorySeenStore {
+    keys: Mutex<HashMap<String, chrono::DateTime<chrono::Utc>>>,
+}
+
+impl MemorySeenStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SeenStore for MemorySeenStore {
+    fn insert<'a>(&'a self, key: &'a str, expires_at: chrono::DateTime<chrono::Utc>) -> BoxFuture<'a, bool> {
+        let now = chrono::Utc::now();
+        let mut keys = self.keys.lock().unwrap();
+        keys.retain(|_, expires_at| *expires_at > now);
+        let inserted = !keys.contains_key(key);
+        if inserted {
+            keys.insert(key.to_owned(), expires_at);
+        }
+        Box::pin(async move { inserted })
+    }
+}
+
+/// Checks that the transmission time of a delivery is within the allowed clock skew of now.
+pub fn check_transmission_time<T>(
+    payload: &WebhookVerificationPayload<T>,
+    now: chrono::DateTime<chrono::Utc>,
+    max_skew: chrono::Duration,
+) -> Result<chrono::DateTime<chrono::Utc>, ReplayError> {
+    let time = payload
+        .transmission_time()
+        .ok_or_else(|| ReplayError::InvalidTransmissionTime(payload.transmission_time.clone()))?;
+    let skew = if time > now { time - now } else { now - time };
+    if skew > max_skew {
+        return Err(ReplayError::OutsideWindow {
+            transmission_time: time,
+            skew,
+        });
+    }
+    Ok(time)
+}
+
+/// Rejects replayed webhook deliveries, once their signature was verified.
+#[derive(Debug, Clone)]
+pub struct ReplayGuard {
+    store: Arc<dyn SeenStore>,
+    max_skew: chrono::Duration,
+    event_retention: chrono::Duration,
+}
+
+impl ReplayGuard {
+    /// Creates a guard accepting a clock skew of 5 minutes, and remembering event ids for 3 days,
+    /// as long as paypal retries a delivery.
+    pub fn new(store: impl SeenStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            max_skew: chrono::Duration::minutes(5),
+            event_retention: chrono::Duration::days(3),
+        }
+    }
+
+    /// Sets how far the transmission time can be from now, in both directions.
+    pub fn max_skew(mut self, max_skew: chrono::Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    /// Sets how long event ids are remembered.
+    pub fn event_retention(mut self, event_retention: chrono::Duration) -> Self {
+        self.event_retention = event_retention;
+        self
+    }
+
+    /// Checks the transmission time of a verified delivery, and that its transmission id wasn't seen yet.
+    ///
+    /// Fails with [ReplayError::Unverified] without recording anything unless the verification succeeded.
+    /// Transmission ids are remembered as long as the transmission time is accepted, since older deliveries are
+    /// rejected anyway.
+    pub async fn check<T>(
+        &self,
+        payload: &WebhookVerificationPayload<T>,
+        verification: &Verification,
+    ) -> Result<(), ReplayError> {
+        verified(verification)?;
+        let time = check_transmission_time(payload, chrono::Utc::now(), self.max_skew)?;
+        let key = format!("transmission:{}", payload.transmission_id);
+        if self.store.insert(&key, time + self.max_skew).await {
+            Ok(())
+        } else {
+            Err(ReplayError::DuplicateTransmission(payload.transmission_id.clone()))
+        }
+    }
+
+    /// Checks that the event of a verified delivery wasn't seen yet, paypal retries use a new transmission id but
+    /// keep the event id.
+    ///
+    /// Fails with [ReplayError::Unverified] without recording anything unless the verification succeeded.
+    pub async fn check_event(&self, event_id: &str, verification: &Verification) -> Result<(), ReplayError> {
+        verified(verification)?;
+        let key = format!("event:{}", event_id);
+        if self.store.insert(&key, chrono::Utc::now() + self.event_retention).await {
+            Ok(())
+        } else {
+            Err(ReplayError::DuplicateEvent(event_id.to_owned()))
+        }
+    }
+}
+
+fn verified(verification: &Verification) -> Result<(), ReplayError> {
+    match verification.verification_status {
+        VerificationStatus::Success => Ok(()),
+        _ => Err(ReplayError::Unverified),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(transmission_id: &str, transmission_time: String) -> WebhookVerificationPayload<()> {
+        WebhookVerificationPayload {
+            transmission_id: transmission_id.to_owned(),
+            transmission_time,
+            cert_url: "https://api.paypal.com/v1/notifications/certs/CERT-360caa42-fca2a594-1d93a270".to_owned(),
+            auth_algo: "SHA256withRSA".to_owned(),
+            transmission_sig: "signature".to_owned(),
+            webhook_id: "1JE4291016473214C".to_owned(),
+            webhook_event: (),
+        }
+    }
+
+    #[test]
+    fn test_transmission_time() {
+        let now = chrono::DateTime::parse_from_rfc3339("2022-08-15T14:13:48Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let skew = chrono::Duration::minutes(5);
+        let check = |time: &str| check_transmission_time(&payload("69cd13f0", time.to_owned()), now, skew);
+        assert!(check("2022-08-15T14:10:00Z").is_ok());
+        assert!(check("2022-08-15T14:15:00+00:00").is_ok());
+        assert!(matches!(
+            check("2022-08-15T14:00:00Z"),
+            Err(ReplayError::OutsideWindow { .. })
+        ));
+        assert!(matches!(
+            check("yesterday"),
+            Err(ReplayError::InvalidTransmissionTime(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_guard() {
+        let guard = ReplayGuard::new(MemorySeenStore::new());
+        let now = chrono::Utc::now().to_rfc3339();
+        let success = Verification {
+            verification_status: VerificationStatus::Success,
+        };
+        let failure = Verification {
+            verification_status: VerificationStatus::Failure,
+        };
+
+        // A delivery failing verification doesn't record its id.
+        assert!(matches!(
+            guard.check(&payload("69cd13f0", now.clone()), &failure).await,
+            Err(ReplayError::Unverified)
+        ));
+        guard.check(&payload("69cd13f0", now.clone()), &success).await.unwrap();
+        assert!(matches!(
+            guard.check(&payload("69cd13f0", now.clone()), &success).await,
+            Err(ReplayError::DuplicateTransmission(_))
+        ));
+        guard.check(&payload("2b8d5ad0", now), &success).await.unwrap();
+
+        let old = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        assert!(guard.check(&payload("7e1a3c2f", old), &success).await.is_err());
+
+        let event_id = "WH-1KN88282901968003-82E75604WM969463F";
+        assert!(matches!(
+            guard.check_event(event_id, &failure).await,
+            Err(ReplayError::Unverified)
+        ));
+        guard.check_event(event_id, &success).await.unwrap();
+        assert!(matches!(
+            guard.check_event(event_id, &success).await,
+            Err(ReplayError::DuplicateEvent(_))
+        ));
+    }
+}
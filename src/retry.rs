@@ -0,0 +1,204 @@
+//! Retry policies applied to every request the client makes.
+//!
+//! The client asks its [RetryPolicy] what to do each time a request fails, either because the request
+//! could not be sent or because paypal answered with an error status. By default an [ExponentialBackoff] is used,
+//! implement the trait yourself to enforce your own retry budgets.
+
+use reqwest::StatusCode;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The kind of failure that happened.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorClass {
+    /// The connection could not be established, so the request was never sent.
+    Connect,
+    /// The request timed out, it may or may not have been processed.
+    Timeout,
+    /// Any other error sending the request or reading the response.
+    Transport,
+    /// Paypal is rate limiting the requests, status 429.
+    RateLimited,
+    /// Paypal failed to process the request, status 5xx.
+    ServerError,
+    /// The request was rejected, status 4xx.
+    ClientError,
+}
+
+impl ErrorClass {
+    /// Classifies an error status code.
+    pub fn from_status(status: StatusCode) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            ErrorClass::RateLimited
+        } else if status.is_server_error() {
+            ErrorClass::ServerError
+        } else {
+            ErrorClass::ClientError
+        }
+    }
+
+    /// Classifies a http error.
+    pub fn from_error(error: &reqwest::Error) -> Self {
+        if error.is_connect() {
+            ErrorClass::Connect
+        } else if error.is_timeout() {
+            ErrorClass::Timeout
+        } else if let Some(status) = error.status() {
+            Self::from_status(status)
+        } else {
+            ErrorClass::Transport
+        }
+    }
+}
+
+/// Information about a failed attempt, used by a [RetryPolicy] to decide whether to retry.
+#[derive(Debug, Clone)]
+pub struct RetryContext {
+    /// The attempt that failed, starting at 1.
+    pub attempt: u32,
+    /// The classification of the failure.
+    pub error_class: ErrorClass,
+    /// The response status, if a response was received.
+    pub status: Option<StatusCode>,
+    /// The time elapsed since the first attempt was sent.
+    pub elapsed: Duration,
+    /// The delay requested by paypal through the Retry-After header.
+    pub retry_after: Option<Duration>,
+    /// Whether sending the request again can't duplicate its effects.
+    ///
+    /// True for GET, PUT, DELETE and friends, or for any request carrying a PayPal-Request-Id header.
+    pub idempotent: bool,
+}
+
+/// Decides if and when a failed request is sent again.
+pub trait RetryPolicy: Debug + Send + Sync {
+    /// Returns the delay to wait before retrying, or None to give up and return the error.
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration>;
+}
+
+/// Never retries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn retry_delay(&self, _context: &RetryContext) -> Option<Duration> {
+        None
+    }
+}
+
+/// Retries with an exponentially increasing delay.
+///
+/// Rate limited requests and requests that never reached paypal are always retried,
+/// timeouts and server errors are only retried when the request is idempotent.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    /// The maximum number of retries, not counting the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled on every retry.
+    pub base_delay: Duration,
+    /// The maximum delay between two attempts. The Retry-After sent by paypal is honoured as is, so the request
+    /// isn't retried when it asks to wait longer.
+    pub max_delay: Duration,
+    /// Stop retrying once this much time has passed since the first attempt.
+    pub max_elapsed: Duration,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        ExponentialBackoff {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            max_elapsed: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration> {
+        if context.attempt > self.max_retries || context.elapsed >= self.max_elapsed {
+            return None;
+        }
+
+        let retryable = match context.error_class {
+            ErrorClass::Connect | ErrorClass::RateLimited => true,
+            ErrorClass::Timeout | ErrorClass::Transport | ErrorClass::ServerError => context.idempotent,
+            ErrorClass::ClientError => false,
+        };
+        if !retryable {
+            return None;
+        }
+
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(context.attempt - 1));
+        let delay = match context.retry_after {
+            // Retrying earlier than paypal asked would only be rejected again.
+            Some(retry_after) if retry_after > self.max_delay => return None,
+            Some(retry_after) => retry_after,
+            None => backoff.min(self.max_delay),
+        };
+        if context.elapsed + delay > self.max_elapsed {
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+/// Whether sending the request twice has the same effect as sending it once.
+pub(crate) fn is_idempotent(request: &reqwest::Request) -> bool {
+    request.method().is_idempotent() || request.headers().contains_key("PayPal-Request-Id")
+}
+
+/// Parses the Retry-After header, which paypal sends in seconds.
+pub(crate) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(attempt: u32, error_class: ErrorClass, idempotent: bool) -> RetryContext {
+        RetryContext {
+            attempt,
+            error_class,
+            status: None,
+            elapsed: Duration::from_secs(0),
+            retry_after: None,
+            idempotent,
+        }
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = ExponentialBackoff::default();
+
+        assert_eq!(
+            policy.retry_delay(&context(1, ErrorClass::ServerError, true)),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.retry_delay(&context(3, ErrorClass::RateLimited, false)),
+            Some(Duration::from_millis(800))
+        );
+        assert_eq!(policy.retry_delay(&context(4, ErrorClass::RateLimited, true)), None);
+        assert_eq!(policy.retry_delay(&context(1, ErrorClass::ServerError, false)), None);
+        assert_eq!(policy.retry_delay(&context(1, ErrorClass::ClientError, true)), None);
+
+        let mut limited = context(1, ErrorClass::RateLimited, false);
+        limited.retry_after = Some(Duration::from_secs(2));
+        assert_eq!(policy.retry_delay(&limited), Some(Duration::from_secs(2)));
+        limited.retry_after = Some(Duration::from_secs(60));
+        assert_eq!(policy.retry_delay(&limited), None);
+        limited.retry_after = Some(Duration::from_secs(4));
+        limited.elapsed = Duration::from_secs(28);
+        assert_eq!(policy.retry_delay(&limited), None);
+    }
+}
@@ -0,0 +1,620 @@
+//! Use the Subscriptions API to bill buyers on a recurring basis.
+//!
+//! A plan describes how a product is billed: an optional trial followed by a regular billing cycle,
+//! each with a frequency and a price. [PlanPayload::builder] checks the plan follows the rules of the api
+//! before it's sent.
+//!
+//! ```
+//! use paypal_rs::common::{Currency, Money};
+//! use paypal_rs::subscriptions::{BillingCycle, Frequency, PlanPayload, PricingScheme, PricingTier};
+//!
+//! let usd = |value: &str| Money { currency_code: Currency::USD, value: value.to_owned() };
+//!
+//! let plan = PlanPayload::builder("PROD-XXCD1234QWER65782", "Team plan")
+//!     .cycle(BillingCycle::trial(Frequency::monthly(), 1))
+//!     .cycle(BillingCycle::regular(
+//!         Frequency::monthly(),
+//!         PricingScheme::volume(vec![
+//!             PricingTier::new(1, Some(10), usd("10.00")),
+//!             PricingTier::new(11, None, usd("8.00")),
+//!         ])
+//!         .unwrap(),
+//!     ))
+//!     .setup_fee(usd("5.00"))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(plan.billing_cycles[1].sequence, 2);
+//! ```
+//!
+//! Reference: https://developer.paypal.com/docs/api/subscriptions/v1/
+
+use crate::client::{Client, HeaderParams};
+use crate::common::*;
+use crate::errors::{decode, InvalidPlanError, ResponseError};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// The maximum number of billing cycles of a plan.
+pub const MAX_BILLING_CYCLES: usize = 12;
+
+/// The maximum number of trial billing cycles of a plan.
+pub const MAX_TRIAL_CYCLES: usize = 2;
+
+/// The maximum number of times a billing cycle is executed, 0 meaning forever.
+pub const MAX_TOTAL_CYCLES: u32 = 999;
+
+string_enum! {
+    /// The unit of a billing frequency.
+    pub enum IntervalUnit {
+        /// A daily billing cycle, up to 365 days.
+        Day = "DAY",
+        /// A weekly billing cycle, up to 52 weeks.
+        Week = "WEEK",
+        /// A monthly billing cycle, up to 12 months.
+        Month = "MONTH",
+        /// A yearly billing cycle, only one year.
+        Year = "YEAR",
+    }
+}
+
+string_enum! {
+    /// The type of a billing cycle.
+    pub enum TenureType {
+        /// A regular billing cycle.
+        Regular = "REGULAR",
+        /// A trial billing cycle, before the regular one.
+        Trial = "TRIAL",
+    }
+}
+
+string_enum! {
+    /// How the price of a quantity is computed from the tiers of a pricing scheme.
+    pub enum PricingModel {
+        /// The whole quantity is charged at the price of the tier it falls in.
+        Volume = "VOLUME",
+        /// Each unit is charged at the price of the tier it falls in.
+        Tiered = "TIERED",
+    }
+}
+
+string_enum! {
+    /// What happens to the subscription when its setup fee payment fails.
+    pub enum SetupFeeFailureAction {
+        /// The subscription is activated anyway, and the fee added to the outstanding balance.
+        Continue = "CONTINUE",
+        /// The subscription is cancelled.
+        Cancel = "CANCEL",
+    }
+}
+
+string_enum! {
+    /// The status of a plan.
+    pub enum PlanStatus {
+        /// The plan was created, subscriptions can't be created for it yet.
+        Created = "CREATED",
+        /// The plan is inactive.
+        Inactive = "INACTIVE",
+        /// The plan is active, subscriptions can be created for it.
+        Active = "ACTIVE",
+    }
+}
+
+/// How often a billing cycle is executed.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Frequency {
+    /// The unit of the interval.
+    pub interval_unit: IntervalUnit,
+    /// The number of units in an interval.
+    pub interval_count: u32,
+}
+
+impl Frequency {
+    /// Executes the cycle every `interval_count` units.
+    pub fn new(interval_unit: IntervalUnit, interval_count: u32) -> Self {
+        Frequency {
+            interval_unit,
+            interval_count,
+        }
+    }
+
+    /// Executes the cycle every month.
+    pub fn monthly() -> Self {
+        Frequency::new(IntervalUnit::Month, 1)
+    }
+
+    /// Executes the cycle every year.
+    pub fn yearly() -> Self {
+        Frequency::new(IntervalUnit::Year, 1)
+    }
+
+    fn validate(&self) -> Result<(), InvalidPlanError> {
+        let max = match self.interval_unit {
+            IntervalUnit::Day => 365,
+            IntervalUnit::Week => 52,
+            IntervalUnit::Month => 12,
+            _ => 1,
+        };
+        if self.interval_count == 0 || self.interval_count > max {
+            return Err(InvalidPlanError(format!(
+                "the interval count of a {} frequency must be between 1 and {}",
+                self.interval_unit, max
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A range of quantities with its price.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PricingTier {
+    /// The first quantity of the tier.
+    pub starting_quantity: String,
+    /// The last quantity of the tier, not set for the last tier.
+    pub ending_quantity: Option<String>,
+    /// The price of the tier.
+    pub amount: Money,
+}
+
+impl PricingTier {
+    /// Creates a tier for the quantities from `starting_quantity` to `ending_quantity`, or above for the last tier.
+    pub fn new(starting_quantity: u32, ending_quantity: Option<u32>, amount: Money) -> Self {
+        PricingTier {
+            starting_quantity: starting_quantity.to_string(),
+            ending_quantity: ending_quantity.map(|quantity| quantity.to_string()),
+            amount,
+        }
+    }
+}
+
+/// The price of a billing cycle, either fixed or depending on the quantity subscribed to.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PricingScheme {
+    /// The fixed price of the cycle.
+    pub fixed_price: Option<Money>,
+    /// How the tiers are applied.
+    pub pricing_model: Option<PricingModel>,
+    /// The tiers of the price.
+    pub tiers: Option<Vec<PricingTier>>,
+}
+
+impl PricingScheme {
+    /// Charges a fixed price each cycle.
+    pub fn fixed(price: Money) -> Self {
+        PricingScheme {
+            fixed_price: Some(price),
+            pricing_model: None,
+            tiers: None,
+        }
+    }
+
+    /// Charges each unit at the price of the tier it falls in.
+    ///
+    /// Fails unless the tiers start at 1 and follow each other, with only the last one left open.
+    pub fn tiered(tiers: Vec<PricingTier>) -> Result<Self, InvalidPlanError> {
+        PricingScheme::with_tiers(PricingModel::Tiered, tiers)
+    }
+
+    /// Charges the whole quantity at the price of the tier it falls in.
+    ///
+    /// Fails unless the tiers start at 1 and follow each other, with only the last one left open.
+    pub fn volume(tiers: Vec<PricingTier>) -> Result<Self, InvalidPlanError> {
+        PricingScheme::with_tiers(PricingModel::Volume, tiers)
+    }
+
+    fn with_tiers(pricing_model: PricingModel, tiers: Vec<PricingTier>) -> Result<Self, InvalidPlanError> {
+        let scheme = PricingScheme {
+            fixed_price: None,
+            pricing_model: Some(pricing_model),
+            tiers: Some(tiers),
+        };
+        scheme.validate()?;
+        Ok(scheme)
+    }
+
+    fn validate(&self) -> Result<(), InvalidPlanError> {
+        let tiers = match (&self.fixed_price, &self.tiers) {
+            (Some(_), None) => return Ok(()),
+            (None, Some(tiers)) if !tiers.is_empty() => tiers,
+            _ => {
+                return Err(InvalidPlanError(
+                    "a pricing scheme needs either a fixed price or tiers".to_owned(),
+                ))
+            }
+        };
+
+        let quantity = |quantity: &str| {
+            quantity
+                .parse::<u32>()
+                .map_err(|_| InvalidPlanError(format!("{:?} is not a valid tier quantity", quantity)))
+        };
+
+        let mut next_start = 1;
+        for (i, tier) in tiers.iter().enumerate() {
+            if tier.amount.currency_code != tiers[0].amount.currency_code {
+                return Err(InvalidPlanError("the tiers must use the same currency".to_owned()));
+            }
+
+            let start = quantity(&tier.starting_quantity)?;
+            if start != next_start {
+                return Err(InvalidPlanError(format!(
+                    "tier {} must start at quantity {}, not {}",
+                    i + 1,
+                    next_start,
+                    start
+                )));
+            }
+
+            match &tier.ending_quantity {
+                Some(end) => {
+                    let end = quantity(end)?;
+                    if end < start {
+                        return Err(InvalidPlanError(format!("tier {} ends before it starts", i + 1)));
+                    }
+                    next_start = end + 1;
+                }
+                None if i + 1 < tiers.len() => {
+                    return Err(InvalidPlanError(format!(
+                        "only the last tier can be open-ended, not tier {}",
+                        i + 1
+                    )));
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A billing cycle of a plan.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BillingCycle {
+    /// How often the cycle is executed.
+    pub frequency: Frequency,
+    /// Whether the cycle is a trial or the regular one.
+    pub tenure_type: TenureType,
+    /// The order of the cycle, set by [PlanBuilder::build] from the order the cycles were added in.
+    pub sequence: u32,
+    /// The number of times the cycle is executed, 0 meaning until the subscription is cancelled.
+    pub total_cycles: u32,
+    /// The price of the cycle, trials without one are free.
+    pub pricing_scheme: Option<PricingScheme>,
+}
+
+impl BillingCycle {
+    /// A free trial executed `total_cycles` times, see [price](BillingCycle::price) to discount it instead.
+    pub fn trial(frequency: Frequency, total_cycles: u32) -> Self {
+        BillingCycle {
+            frequency,
+            tenure_type: TenureType::Trial,
+            sequence: 0,
+            total_cycles,
+            pricing_scheme: None,
+        }
+    }
+
+    /// The regular cycle, executed until the subscription is cancelled unless
+    /// [total_cycles](BillingCycle::total_cycles) is set.
+    pub fn regular(frequency: Frequency, pricing_scheme: PricingScheme) -> Self {
+        BillingCycle {
+            frequency,
+            tenure_type: TenureType::Regular,
+            sequence: 0,
+            total_cycles: 0,
+            pricing_scheme: Some(pricing_scheme),
+        }
+    }
+
+    /// Sets the number of times the cycle is executed.
+    pub fn total_cycles(mut self, total_cycles: u32) -> Self {
+        self.total_cycles = total_cycles;
+        self
+    }
+
+    /// Sets the price of the cycle.
+    pub fn price(mut self, pricing_scheme: PricingScheme) -> Self {
+        self.pricing_scheme = Some(pricing_scheme);
+        self
+    }
+}
+
+/// How the payments of the subscriptions are handled.
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PaymentPreferences {
+    /// Whether to automatically bill the outstanding amount in the next billing cycle.
+    pub auto_bill_outstanding: Option<bool>,
+    /// The fee charged when the subscription starts.
+    pub setup_fee: Option<Money>,
+    /// What happens when the setup fee payment fails.
+    pub setup_fee_failure_action: Option<SetupFeeFailureAction>,
+    /// The number of failed payments after which the subscription is suspended.
+    pub payment_failure_threshold: Option<u32>,
+}
+
+/// The taxes of a plan.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Taxes {
+    /// The tax percentage on the billing amount.
+    pub percentage: String,
+    /// Whether the tax is already included in the billing amount.
+    pub inclusive: bool,
+}
+
+/// The payload to create a plan, see [PlanPayload::builder].
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PlanPayload {
+    /// The ID of the product the plan bills.
+    pub product_id: String,
+    /// The plan name.
+    pub name: String,
+    /// The initial status of the plan.
+    pub status: Option<PlanStatus>,
+    /// The detailed description of the plan.
+    pub description: Option<String>,
+    /// The billing cycles, trials first.
+    pub billing_cycles: Vec<BillingCycle>,
+    /// How the payments are handled.
+    pub payment_preferences: PaymentPreferences,
+    /// The taxes of the plan.
+    pub taxes: Option<Taxes>,
+    /// Whether a quantity can be subscribed to, required by tiered pricing schemes.
+    pub quantity_supported: Option<bool>,
+}
+
+impl PlanPayload {
+    /// Starts building a plan for the given product.
+    pub fn builder(product_id: impl Into<String>, name: impl Into<String>) -> PlanBuilder {
+        PlanBuilder {
+            plan: PlanPayload {
+                product_id: product_id.into(),
+                name: name.into(),
+                status: None,
+                description: None,
+                billing_cycles: Vec::new(),
+                payment_preferences: PaymentPreferences::default(),
+                taxes: None,
+                quantity_supported: None,
+            },
+        }
+    }
+}
+
+/// Builds a [PlanPayload], checking its billing cycles.
+#[derive(Debug, Clone)]
+pub struct PlanBuilder {
+    plan: PlanPayload,
+}
+
+impl PlanBuilder {
+    /// Sets the description of the plan.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.plan.description = Some(description.into());
+        self
+    }
+
+    /// Sets the initial status of the plan.
+    pub fn status(mut self, status: PlanStatus) -> Self {
+        self.plan.status = Some(status);
+        self
+    }
+
+    /// Adds a billing cycle, the trials must be added before the regular cycle.
+    pub fn cycle(mut self, cycle: BillingCycle) -> Self {
+        self.plan.billing_cycles.push(cycle);
+        self
+    }
+
+    /// Charges a fee when the subscription starts.
+    pub fn setup_fee(mut self, setup_fee: Money) -> Self {
+        self.plan.payment_preferences.setup_fee = Some(setup_fee);
+        self
+    }
+
+    /// Sets how the payments are handled.
+    pub fn payment_preferences(mut self, payment_preferences: PaymentPreferences) -> Self {
+        self.plan.payment_preferences = payment_preferences;
+        self
+    }
+
+    /// Sets the taxes of the plan.
+    pub fn taxes(mut self, percentage: &str, inclusive: bool) -> Self {
+        self.plan.taxes = Some(Taxes {
+            percentage: percentage.to_owned(),
+            inclusive,
+        });
+        self
+    }
+
+    /// Numbers the billing cycles and checks the plan.
+    pub fn build(mut self) -> Result<PlanPayload, InvalidPlanError> {
+        let cycles = &mut self.plan.billing_cycles;
+        if cycles.is_empty() || cycles.len() > MAX_BILLING_CYCLES {
+            return Err(InvalidPlanError(format!(
+                "a plan must have between 1 and {} billing cycles",
+                MAX_BILLING_CYCLES
+            )));
+        }
+
+        let trials = cycles
+            .iter()
+            .filter(|cycle| cycle.tenure_type == TenureType::Trial)
+            .count();
+        if trials > MAX_TRIAL_CYCLES {
+            return Err(InvalidPlanError(format!(
+                "a plan can't have more than {} trial cycles",
+                MAX_TRIAL_CYCLES
+            )));
+        }
+        if cycles.len() != trials + 1 || cycles.last().map(|cycle| &cycle.tenure_type) != Some(&TenureType::Regular) {
+            return Err(InvalidPlanError(
+                "a plan must have a single regular cycle, after the trials".to_owned(),
+            ));
+        }
+
+        let mut tiered = false;
+        for (i, cycle) in cycles.iter_mut().enumerate() {
+            cycle.sequence = i as u32 + 1;
+            cycle.frequency.validate()?;
+
+            if cycle.total_cycles > MAX_TOTAL_CYCLES {
+                return Err(InvalidPlanError(format!(
+                    "a billing cycle can't be executed more than {} times",
+                    MAX_TOTAL_CYCLES
+                )));
+            }
+            if cycle.tenure_type == TenureType::Trial && cycle.total_cycles == 0 {
+                return Err(InvalidPlanError("a trial cycle can't be infinite".to_owned()));
+            }
+
+            match &cycle.pricing_scheme {
+                Some(scheme) => {
+                    scheme.validate()?;
+                    tiered |= scheme.tiers.is_some();
+                }
+                None if cycle.tenure_type == TenureType::Regular => {
+                    return Err(InvalidPlanError("the regular cycle needs a price".to_owned()));
+                }
+                None => {}
+            }
+        }
+
+        if tiered {
+            self.plan.quantity_supported = Some(true);
+        }
+
+        Ok(self.plan)
+    }
+}
+
+/// A plan, which subscriptions are created for.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    /// The ID of the plan.
+    pub id: String,
+    /// The ID of the product the plan bills.
+    pub product_id: Option<String>,
+    /// The plan name.
+    pub name: Option<String>,
+    /// The status of the plan.
+    pub status: Option<PlanStatus>,
+    /// The detailed description of the plan.
+    pub description: Option<String>,
+    /// The billing cycles of the plan.
+    #[serde(default)]
+    pub billing_cycles: Vec<BillingCycle>,
+    /// How the payments are handled.
+    pub payment_preferences: Option<PaymentPreferences>,
+    /// The date and time when the plan was created.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the plan was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+impl Client {
+    /// Creates a plan that defines pricing and billing cycle details for subscriptions.
+    pub async fn create_plan(
+        &mut self,
+        plan: &PlanPayload,
+        header_params: HeaderParams,
+    ) -> Result<Plan, ResponseError> {
+        let build = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v1/billing/plans", self.endpoint()).as_str()),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build.json(plan)).await?;
+
+        if res.status().is_success() {
+            let plan = decode::<Plan>(res, self.decode_mode).await?;
+            Ok(plan)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(value: &str) -> Money {
+        Money {
+            currency_code: Currency::USD,
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_plan_builder() {
+        let plan = PlanPayload::builder("PROD-XXCD1234QWER65782", "Video streaming")
+            .cycle(BillingCycle::trial(Frequency::monthly(), 1))
+            .cycle(BillingCycle::trial(Frequency::monthly(), 2).price(PricingScheme::fixed(usd("3.00"))))
+            .cycle(BillingCycle::regular(Frequency::monthly(), PricingScheme::fixed(usd("10.00"))).total_cycles(12))
+            .build()
+            .unwrap();
+        let sequences: Vec<_> = plan.billing_cycles.iter().map(|cycle| cycle.sequence).collect();
+        assert_eq!(sequences, [1, 2, 3]);
+        assert_eq!(plan.quantity_supported, None);
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["billing_cycles"][0]["tenure_type"], "TRIAL");
+        assert_eq!(
+            json["billing_cycles"][2]["pricing_scheme"]["fixed_price"]["value"],
+            "10.00"
+        );
+        assert!(json["billing_cycles"][0].get("pricing_scheme").is_none());
+
+        let tiers = PricingScheme::tiered(vec![
+            PricingTier::new(1, Some(10), usd("10.00")),
+            PricingTier::new(11, None, usd("8.00")),
+        ])
+        .unwrap();
+        let plan = PlanPayload::builder(String::from("PROD-XXCD1234QWER65782"), "Seats")
+            .cycle(BillingCycle::regular(Frequency::yearly(), tiers))
+            .build()
+            .unwrap();
+        assert_eq!(plan.quantity_supported, Some(true));
+
+        // The regular cycle must be last.
+        assert!(PlanPayload::builder("PROD-XXCD1234QWER65782", "Invalid")
+            .cycle(BillingCycle::regular(
+                Frequency::monthly(),
+                PricingScheme::fixed(usd("10.00"))
+            ))
+            .cycle(BillingCycle::trial(Frequency::monthly(), 1))
+            .build()
+            .is_err());
+        // A year is the longest frequency.
+        assert!(PlanPayload::builder("PROD-XXCD1234QWER65782", "Invalid")
+            .cycle(BillingCycle::regular(
+                Frequency::new(IntervalUnit::Month, 13),
+                PricingScheme::fixed(usd("1.00"))
+            ))
+            .build()
+            .is_err());
+        // Tiers must follow each other.
+        assert!(PricingScheme::volume(vec![
+            PricingTier::new(1, Some(10), usd("10.00")),
+            PricingTier::new(12, None, usd("8.00")),
+        ])
+        .is_err());
+        // Only the last tier can be open-ended.
+        assert!(PricingScheme::volume(vec![
+            PricingTier::new(1, None, usd("10.00")),
+            PricingTier::new(11, None, usd("8.00")),
+        ])
+        .is_err());
+    }
+}
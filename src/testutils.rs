@@ -0,0 +1,269 @@
+//! Representative api values, to unit test the code handling them without reaching paypal.
+//!
+//! Each value is parsed from a raw JSON fixture, taken from the sandbox, which is also exported so it can be fed
+//! to handlers that receive the raw body, like a webhook endpoint. The ids and links are consistent across fixtures:
+//! the capture belongs to the order, and the refund to the capture.
+//!
+//! Requires the `testutils` feature, usually enabled in the `dev-dependencies` of an application.
+//!
+//! ```
+//! use paypal_rs::orders::OrderStatus;
+//! use paypal_rs::testutils;
+//!
+//! let mut order = testutils::order();
+//! order.status = OrderStatus::Approved;
+//!
+//! let event = testutils::capture_completed_webhook();
+//! assert_eq!(event.resource.order_id(), Some(order.id.as_str()));
+//! ```
+
+use crate::capture::Payment;
+use crate::orders::{Order, Refund};
+use crate::webhooks::{CaptureWebhook, OrderWebhook};
+use serde::de::DeserializeOwned;
+
+/// The ID of the order in the fixtures.
+pub const ORDER_ID: &str = "5O190127TN364715T";
+
+/// The ID of the captured payment in the fixtures.
+pub const CAPTURE_ID: &str = "3C679366HH908993F";
+
+/// The ID of the refund in the fixtures.
+pub const REFUND_ID: &str = "1JU08902781691411";
+
+/// A completed order with a single captured purchase unit, as returned by show order details.
+pub const ORDER_JSON: &str = r#"{
+    "id": "5O190127TN364715T",
+    "create_time": "2018-04-01T21:18:49Z",
+    "update_time": "2018-04-01T21:20:49Z",
+    "intent": "CAPTURE",
+    "status": "COMPLETED",
+    "payer": {
+        "name": {"given_name": "John", "surname": "Doe"},
+        "email_address": "buyer@example.com",
+        "payer_id": "QYR5Z8XDVJNXQ",
+        "address": {"country_code": "US"}
+    },
+    "payment_source": {
+        "paypal": {
+            "email_address": "buyer@example.com",
+            "account_id": "QYR5Z8XDVJNXQ",
+            "account_status": "VERIFIED",
+            "name": {"given_name": "John", "surname": "Doe"},
+            "address": {"country_code": "US"}
+        }
+    },
+    "purchase_units": [
+        {
+            "reference_id": "default",
+            "amount": {"currency_code": "USD", "value": "100.00"},
+            "payee": {"email_address": "seller@example.com", "merchant_id": "7KNGBPH2U58GQ"},
+            "invoice_id": "INV-1234",
+            "shipping": {
+                "name": {"full_name": "John Doe"},
+                "address": {
+                    "address_line_1": "2211 N First Street",
+                    "address_line_2": "Building 17",
+                    "admin_area_2": "San Jose",
+                    "admin_area_1": "CA",
+                    "postal_code": "95131",
+                    "country_code": "US"
+                }
+            },
+            "payments": {
+                "captures": [
+                    {
+                        "id": "3C679366HH908993F",
+                        "status": "COMPLETED",
+                        "amount": {"currency_code": "USD", "value": "100.00"},
+                        "final_capture": true,
+                        "seller_protection": {"status": "ELIGIBLE", "dispute_categories": ["ITEM_NOT_RECEIVED"]},
+                        "create_time": "2018-04-01T21:20:49Z",
+                        "update_time": "2018-04-01T21:20:49Z",
+                        "links": []
+                    }
+                ]
+            }
+        }
+    ],
+    "links": [
+        {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "self", "method": "GET"}
+    ]
+}"#;
+
+/// A completed capture of the order, as returned by show captured payment details.
+pub const CAPTURE_JSON: &str = r#"{
+    "id": "3C679366HH908993F",
+    "amount": {"currency_code": "USD", "value": "100.00"},
+    "final_capture": true,
+    "seller_protection": {
+        "status": "ELIGIBLE",
+        "dispute_categories": ["ITEM_NOT_RECEIVED", "UNAUTHORIZED_TRANSACTION"]
+    },
+    "disbursement_mode": "INSTANT",
+    "seller_receivable_breakdown": {
+        "gross_amount": {"currency_code": "USD", "value": "100.00"},
+        "paypal_fee": {"currency_code": "USD", "value": "3.98"},
+        "net_amount": {"currency_code": "USD", "value": "96.02"}
+    },
+    "invoice_id": "INV-1234",
+    "status": "COMPLETED",
+    "supplementary_data": {"related_ids": {"order_id": "5O190127TN364715T"}},
+    "create_time": "2018-04-01T21:20:49Z",
+    "update_time": "2018-04-01T21:20:49Z",
+    "links": [
+        {"href": "https://api.sandbox.paypal.com/v2/payments/captures/3C679366HH908993F", "rel": "self", "method": "GET"},
+        {"href": "https://api.sandbox.paypal.com/v2/payments/captures/3C679366HH908993F/refund", "rel": "refund", "method": "POST"},
+        {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "up", "method": "GET"}
+    ]
+}"#;
+
+/// A completed refund of the capture, as returned by refund captured payment.
+pub const REFUND_JSON: &str = r#"{
+    "id": "1JU08902781691411",
+    "status": "COMPLETED",
+    "links": [
+        {"href": "https://api.sandbox.paypal.com/v2/payments/refunds/1JU08902781691411", "rel": "self", "method": "GET"},
+        {"href": "https://api.sandbox.paypal.com/v2/payments/captures/3C679366HH908993F", "rel": "up", "method": "GET"}
+    ]
+}"#;
+
+/// A `PAYMENT.CAPTURE.COMPLETED` webhook event for the capture.
+pub const CAPTURE_COMPLETED_WEBHOOK_JSON: &str = r#"{
+    "id": "WH-58D329510W468432D-8HN650336L201105X",
+    "event_version": "1.0",
+    "create_time": "2018-04-01T21:20:53.505Z",
+    "resource_type": "capture",
+    "resource_version": "2.0",
+    "event_type": "PAYMENT.CAPTURE.COMPLETED",
+    "summary": "Payment completed for $ 100.0 USD",
+    "resource": {
+        "id": "3C679366HH908993F",
+        "amount": {"currency_code": "USD", "value": "100.00"},
+        "final_capture": true,
+        "seller_protection": {
+            "status": "ELIGIBLE",
+            "dispute_categories": ["ITEM_NOT_RECEIVED", "UNAUTHORIZED_TRANSACTION"]
+        },
+        "disbursement_mode": "INSTANT",
+        "seller_receivable_breakdown": {
+            "gross_amount": {"currency_code": "USD", "value": "100.00"},
+            "paypal_fee": {"currency_code": "USD", "value": "3.98"},
+            "net_amount": {"currency_code": "USD", "value": "96.02"}
+        },
+        "invoice_id": "INV-1234",
+        "status": "COMPLETED",
+        "supplementary_data": {"related_ids": {"order_id": "5O190127TN364715T"}},
+        "create_time": "2018-04-01T21:20:49Z",
+        "update_time": "2018-04-01T21:20:49Z",
+        "links": [
+            {"href": "https://api.sandbox.paypal.com/v2/payments/captures/3C679366HH908993F", "rel": "self", "method": "GET"},
+            {"href": "https://api.sandbox.paypal.com/v2/payments/captures/3C679366HH908993F/refund", "rel": "refund", "method": "POST"},
+            {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "up", "method": "GET"}
+        ]
+    },
+    "links": [
+        {"href": "https://api.sandbox.paypal.com/v1/notifications/webhooks-events/WH-58D329510W468432D-8HN650336L201105X", "rel": "self", "method": "GET"}
+    ]
+}"#;
+
+/// A `CHECKOUT.ORDER.APPROVED` webhook event for the order, before it was captured.
+pub const ORDER_APPROVED_WEBHOOK_JSON: &str = r#"{
+    "id": "WH-COC11055RA711503B-4YM959094A144403T",
+    "create_time": "2018-04-01T21:20:49.000Z",
+    "event_type": "CHECKOUT.ORDER.APPROVED",
+    "resource_type": "checkout-order",
+    "resource_version": "2.0",
+    "summary": "An order has been approved by buyer",
+    "resource": {
+        "id": "5O190127TN364715T",
+        "create_time": "2018-04-01T21:18:49Z",
+        "update_time": "2018-04-01T21:20:49Z",
+        "intent": "CAPTURE",
+        "status": "APPROVED",
+        "payer": {
+            "name": {"given_name": "John", "surname": "Doe"},
+            "email_address": "buyer@example.com",
+            "payer_id": "QYR5Z8XDVJNXQ",
+            "address": {"country_code": "US"}
+        },
+        "purchase_units": [
+            {
+                "reference_id": "default",
+                "amount": {"currency_code": "USD", "value": "100.00"},
+                "payee": {"email_address": "seller@example.com", "merchant_id": "7KNGBPH2U58GQ"},
+                "invoice_id": "INV-1234"
+            }
+        ],
+        "links": [
+            {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "self", "method": "GET"},
+            {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T/capture", "rel": "capture", "method": "POST"}
+        ]
+    },
+    "links": [
+        {"href": "https://api.sandbox.paypal.com/v1/notifications/webhooks-events/WH-COC11055RA711503B-4YM959094A144403T", "rel": "self", "method": "GET"}
+    ],
+    "event_version": "1.0"
+}"#;
+
+fn parse<T: DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("invalid fixture")
+}
+
+/// The order of [ORDER_JSON].
+pub fn order() -> Order {
+    parse(ORDER_JSON)
+}
+
+/// The captured payment of [CAPTURE_JSON].
+pub fn capture() -> Payment {
+    parse(CAPTURE_JSON)
+}
+
+/// The refund of [REFUND_JSON].
+pub fn refund() -> Refund {
+    parse(REFUND_JSON)
+}
+
+/// The webhook event of [CAPTURE_COMPLETED_WEBHOOK_JSON].
+pub fn capture_completed_webhook() -> CaptureWebhook {
+    parse(CAPTURE_COMPLETED_WEBHOOK_JSON)
+}
+
+/// The webhook event of [ORDER_APPROVED_WEBHOOK_JSON].
+pub fn order_approved_webhook() -> OrderWebhook {
+    parse(ORDER_APPROVED_WEBHOOK_JSON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orders::{CaptureStatus, OrderStatus, RefundStatus};
+    use crate::webhooks::EventType;
+
+    #[test]
+    fn test_fixtures() {
+        let order = order();
+        assert_eq!(order.id, ORDER_ID);
+        assert_eq!(order.status, OrderStatus::Completed);
+        let units = order.purchase_units.unwrap();
+        assert_eq!(
+            units[0].payments.as_ref().unwrap().captures[0].status,
+            CaptureStatus::Completed
+        );
+
+        let capture = capture();
+        assert_eq!(capture.id, CAPTURE_ID);
+        assert_eq!(capture.order_id(), Some(ORDER_ID));
+
+        assert_eq!(refund().status, RefundStatus::Completed);
+
+        let event = capture_completed_webhook();
+        assert_eq!(event.event_type, EventType::PaymentCaptureCompleted);
+        assert_eq!(event.resource.id, CAPTURE_ID);
+
+        let event = order_approved_webhook();
+        assert_eq!(event.event_type, EventType::CheckoutOrderApproved);
+        assert_eq!(event.resource.status, OrderStatus::Approved);
+    }
+}
@@ -0,0 +1,183 @@
+//! Caching of access tokens, so several clients can share them.
+//!
+//! Every [Client](crate::client::Client) looks up its access token in a [TokenStore] before asking paypal for a new one,
+//! and saves the tokens it gets there. By default each client has its own [MemoryTokenStore], implement [TokenStore]
+//! on top of a shared cache like Redis so all the instances of an application reuse the same token
+//! instead of each one fetching its own.
+
+use crate::client::AccessToken;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// An access token along with the time it expires at.
+///
+/// It can be serialized to keep it between runs, see [Client::with_access_token](crate::client::Client::with_access_token).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedToken {
+    /// The access token.
+    pub token: AccessToken,
+    /// When the token expires.
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl CachedToken {
+    /// Whether the token expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= chrono::Utc::now()
+    }
+}
+
+/// Stores access tokens, keyed by the environment and client id they belong to, like `sandbox:client_id`.
+///
+/// A failing store should behave as if it had no token, the client then fetches a new one.
+pub trait TokenStore: Debug + Send + Sync {
+    /// Returns the token stored for the given key.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedToken>>;
+
+    /// Stores the token for the given key, it can be dropped once it expires.
+    fn put<'a>(&'a self, key: &'a str, token: CachedToken) -> BoxFuture<'a, ()>;
+
+    /// The locks the clients using this store take to refresh their token one at a time.
+    ///
+    /// None by default, each client then only serializes its own refreshes. Keep a [RefreshLocks] in the store
+    /// and return it so the clients sharing the store also wait for each other's refresh.
+    fn refresh_locks(&self) -> Option<&RefreshLocks> {
+        None
+    }
+}
+
+impl<T: TokenStore + ?Sized> TokenStore for Arc<T> {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedToken>> {
+        (**self).get(key)
+    }
+
+    fn put<'a>(&'a self, key: &'a str, token: CachedToken) -> BoxFuture<'a, ()> {
+        (**self).put(key, token)
+    }
+
+    fn refresh_locks(&self) -> Option<&RefreshLocks> {
+        (**self).refresh_locks()
+    }
+}
+
+pub(crate) type RefreshSlot = Arc<futures_util::lock::Mutex<Option<CachedToken>>>;
+
+/// The locks serializing the token refreshes of a [TokenStore], one per key, each holding the last token fetched.
+#[derive(Debug, Default)]
+pub struct RefreshLocks {
+    slots: Mutex<HashMap<String, RefreshSlot>>,
+}
+
+impl RefreshLocks {
+    /// Creates the locks, with no refresh in progress.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the lock serializing the refreshes for the given key.
+    pub(crate) fn slot(&self, key: &str) -> RefreshSlot {
+        self.slots.lock().unwrap().entry(key.to_owned()).or_default().clone()
+    }
+}
+
+/// Keeps the tokens in memory, share it with an [Arc] to use it from several clients.
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Mutex<HashMap<String, CachedToken>>,
+    refresh_locks: RefreshLocks,
+}
+
+impl MemoryTokenStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedToken>> {
+        let token = self
+            .tokens
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|token| !token.is_expired())
+            .cloned();
+        Box::pin(async move { token })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, token: CachedToken) -> BoxFuture<'a, ()> {
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|_, token| !token.is_expired());
+        tokens.insert(key.to_owned(), token);
+        Box::pin(async {})
+    }
+
+    fn refresh_locks(&self) -> Option<&RefreshLocks> {
+        Some(&self.refresh_locks)
+    }
+}
+
+#[cfg(all(test, feature = "vcr"))]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+    use crate::vcr::{replay_cassette, token_interaction, Interaction};
+
+    fn client(store: &Arc<MemoryTokenStore>, interactions: &[Interaction]) -> Client {
+        Client::builder()
+            .client_id("id")
+            .secret("secret")
+            .token_store(store.clone())
+            .cassette(replay_cassette(interactions))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_shared_store() {
+        let store = Arc::new(MemoryTokenStore::new());
+        client(&store, &[token_interaction()]).get_access_token().await.unwrap();
+
+        // The second client can't reach paypal, so it must use the token fetched by the first one.
+        let mut second = client(&store, &[]);
+        second.get_access_token().await.unwrap();
+        assert_eq!(second.auth.access_token.unwrap().access_token, "A21AA");
+        assert!(store.get("live:id").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_refresh() {
+        // The first refresh is rate limited and retried, the second client must wait for it instead of fetching.
+        let interactions = [
+            Interaction {
+                status: 429,
+                body: r#"{"name":"RATE_LIMIT_REACHED","message":"Too many requests.","details":[]}"#.to_owned(),
+                ..token_interaction()
+            },
+            token_interaction(),
+        ];
+
+        let store = Arc::new(MemoryTokenStore::new());
+        let cassette = replay_cassette(&interactions);
+        let client = || {
+            Client::builder()
+                .client_id("id")
+                .secret("secret")
+                .token_store(store.clone())
+                .cassette(cassette.clone())
+                .build()
+                .unwrap()
+        };
+        let (mut first, mut second) = (client(), client());
+
+        let (a, b) = tokio::join!(first.get_access_token(), second.get_access_token());
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(first.auth.access_token.unwrap().access_token, "A21AA");
+        assert_eq!(second.auth.access_token.unwrap().access_token, "A21AA");
+    }
+}
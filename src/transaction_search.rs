@@ -0,0 +1,463 @@
+//! Use the Transaction Search API to get the history of transactions for a PayPal account,
+//! for example to reconcile the settlements with the orders of a shop.
+//!
+//! Transactions appear up to three hours after they're executed, and only the last three years can be searched.
+//!
+//! Reference: https://developer.paypal.com/docs/api/transaction-search/v1/
+
+use crate::client::{Client, HeaderParams};
+use crate::common::*;
+use crate::countries::Country;
+use crate::errors::{decode, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::skip_serializing_none;
+
+string_enum! {
+    /// A group of fields of the transaction details to include in the search results.
+    pub enum TransactionField {
+        /// The transaction information, always included.
+        TransactionInfo = "transaction_info",
+        /// The information about the payer.
+        PayerInfo = "payer_info",
+        /// The shipping information.
+        ShippingInfo = "shipping_info",
+        /// The auction information.
+        AuctionInfo = "auction_info",
+        /// The cart information, including the details of the items.
+        CartInfo = "cart_info",
+        /// The incentive information.
+        IncentiveInfo = "incentive_info",
+        /// The store information.
+        StoreInfo = "store_info",
+        /// Every group of fields.
+        All = "all",
+    }
+}
+
+/// The filters of a transaction search.
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct TransactionQuery {
+    /// The start of the range, the range can't exceed 31 days.
+    pub start_date: DateTime<Utc>,
+    /// The end of the range.
+    pub end_date: DateTime<Utc>,
+    /// Filters the transactions by ID.
+    pub transaction_id: Option<String>,
+    /// Filters the transactions by event code, like `T0006` for express checkout payments.
+    pub transaction_type: Option<String>,
+    /// Filters the transactions by status: `D` denied, `P` pending, `S` successful or `V` reversed.
+    pub transaction_status: Option<String>,
+    /// Filters the transactions by the three-character ISO-4217 code of their currency.
+    pub transaction_currency: Option<Currency>,
+    /// Filters the transactions by the ID of the store they were made in.
+    pub store_id: Option<String>,
+    /// Filters the transactions by the ID of the terminal they were made with.
+    pub terminal_id: Option<String>,
+    /// The groups of fields to include, only the transaction information by default.
+    #[serde(serialize_with = "serialize_fields", skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<TransactionField>,
+    /// Whether to include only the transactions affecting the balance.
+    pub balance_affecting_records_only: Option<String>,
+    /// The number of items to return in the response, up to 500.
+    pub page_size: Option<i32>,
+    /// The page of results to return.
+    pub page: Option<i32>,
+}
+
+impl TransactionQuery {
+    /// Searches the transactions in the given range, with the default filters.
+    pub fn new(start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Self {
+        TransactionQuery {
+            start_date,
+            end_date,
+            transaction_id: None,
+            transaction_type: None,
+            transaction_status: None,
+            transaction_currency: None,
+            store_id: None,
+            terminal_id: None,
+            fields: Vec::new(),
+            balance_affecting_records_only: None,
+            page_size: None,
+            page: None,
+        }
+    }
+
+    /// Includes the given groups of fields in the results.
+    pub fn fields(mut self, fields: &[TransactionField]) -> Self {
+        self.fields = fields.to_vec();
+        self
+    }
+}
+
+/// The fields are sent as a comma separated list.
+fn serialize_fields<S: Serializer>(fields: &[TransactionField], serializer: S) -> Result<S::Ok, S::Error> {
+    let fields: Vec<&str> = fields.iter().map(TransactionField::as_str).collect();
+    serializer.serialize_str(&fields.join(","))
+}
+
+/// The dates of the transaction details have offsets without a colon, like `2014-07-11T04:03:52+0000`.
+fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let date = match Option::<String>::deserialize(deserializer)? {
+        Some(date) => date,
+        None => return Ok(None),
+    };
+
+    DateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S%z")
+        .or_else(|_| DateTime::parse_from_rfc3339(&date))
+        .map(|date| Some(date.with_timezone(&Utc)))
+        .map_err(serde::de::Error::custom)
+}
+
+/// The transaction information.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    /// The ID of the PayPal account of the counterparty.
+    pub paypal_account_id: Option<String>,
+    /// The PayPal-generated transaction ID.
+    pub transaction_id: String,
+    /// The PayPal-generated base ID, like the ID of the original transaction of a refund.
+    pub paypal_reference_id: Option<String>,
+    /// The type of the reference ID: `ODR` order, `TXN` transaction, `SUB` subscription or `PAP` pre-approved payment.
+    pub paypal_reference_id_type: Option<String>,
+    /// The event code of the transaction, like `T0006`.
+    pub transaction_event_code: Option<String>,
+    /// The date and time when the transaction was processed.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub transaction_initiation_date: Option<DateTime<Utc>>,
+    /// The date and time when the transaction was last updated.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub transaction_updated_date: Option<DateTime<Utc>>,
+    /// The gross amount of the transaction.
+    pub transaction_amount: Option<Money>,
+    /// The fee that PayPal charged for the transaction.
+    pub fee_amount: Option<Money>,
+    /// The insurance amount of the transaction.
+    pub insurance_amount: Option<Money>,
+    /// The shipping amount of the transaction.
+    pub shipping_amount: Option<Money>,
+    /// The shipping discount amount of the transaction.
+    pub shipping_discount_amount: Option<Money>,
+    /// The status of the transaction: `D` denied, `P` pending, `S` successful or `V` reversed.
+    pub transaction_status: Option<String>,
+    /// The subject of payment.
+    pub transaction_subject: Option<String>,
+    /// A special note that the payer passes to the payee.
+    pub transaction_note: Option<String>,
+    /// The invoice ID that is sent with the transaction.
+    pub invoice_id: Option<String>,
+    /// The merchant-provided custom text.
+    pub custom_field: Option<String>,
+    /// Whether the transaction is eligible for protection.
+    pub protection_eligibility: Option<String>,
+    /// The balance of the account after the transaction.
+    pub ending_balance: Option<Money>,
+    /// The available balance of the account after the transaction.
+    pub available_balance: Option<Money>,
+}
+
+/// The phone number of a payer.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionPhone {
+    /// The country calling code.
+    pub country_code: Option<String>,
+    /// The national number.
+    pub national_number: Option<String>,
+}
+
+/// The name of a payer.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionPayerName {
+    /// The given, or first, name.
+    pub given_name: Option<String>,
+    /// The surname or family name.
+    pub surname: Option<String>,
+    /// The party's alternate name, like a business name.
+    pub alternate_full_name: Option<String>,
+}
+
+/// The information about the payer.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayerInfo {
+    /// The PayPal account ID of the payer.
+    pub account_id: Option<String>,
+    /// The email address of the payer.
+    pub email_address: Option<String>,
+    /// The phone number of the payer.
+    pub phone_number: Option<TransactionPhone>,
+    /// Whether the address of the payer is confirmed: `Y` or `N`.
+    pub address_status: Option<String>,
+    /// Whether the payer is verified: `Y` or `N`.
+    pub payer_status: Option<String>,
+    /// The name of the payer.
+    pub payer_name: Option<TransactionPayerName>,
+    /// The two-character ISO 3166-1 code that identifies the country of the payer.
+    pub country_code: Option<Country>,
+}
+
+/// An address of the transaction details.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionAddress {
+    /// The first line of the address.
+    pub line1: Option<String>,
+    /// The second line of the address.
+    pub line2: Option<String>,
+    /// The city.
+    pub city: Option<String>,
+    /// The state.
+    pub state: Option<String>,
+    /// The two-character ISO 3166-1 code that identifies the country.
+    pub country_code: Option<Country>,
+    /// The postal code.
+    pub postal_code: Option<String>,
+}
+
+/// The shipping information.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShippingInfo {
+    /// The name of the recipient.
+    pub name: Option<String>,
+    /// The shipping method.
+    pub method: Option<String>,
+    /// The shipping address.
+    pub address: Option<TransactionAddress>,
+}
+
+/// A tax of an item.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaxAmount {
+    /// The amount of the tax.
+    pub tax_amount: Money,
+}
+
+/// The details of an item of the cart.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemDetail {
+    /// The item code, usually the SKU.
+    pub item_code: Option<String>,
+    /// The item name.
+    pub item_name: Option<String>,
+    /// The item description.
+    pub item_description: Option<String>,
+    /// The item options.
+    pub item_options: Option<String>,
+    /// The number of purchased units.
+    pub item_quantity: Option<String>,
+    /// The price of a single unit.
+    pub item_unit_price: Option<Money>,
+    /// The amount of the item, the unit price times the quantity.
+    pub item_amount: Option<Money>,
+    /// The discount on the item.
+    pub discount_amount: Option<Money>,
+    /// The adjustment on the item.
+    pub adjustment_amount: Option<Money>,
+    /// The gift wrap amount of the item.
+    pub gift_wrap_amount: Option<Money>,
+    /// The tax rate applied to the item.
+    pub tax_percentage: Option<String>,
+    /// The taxes of the item.
+    #[serde(default)]
+    pub tax_amounts: Vec<TaxAmount>,
+    /// The basic shipping amount of the item.
+    pub basic_shipping_amount: Option<Money>,
+    /// The extra shipping amount of the item.
+    pub extra_shipping_amount: Option<Money>,
+    /// The handling amount of the item.
+    pub handling_amount: Option<Money>,
+    /// The insurance amount of the item.
+    pub insurance_amount: Option<Money>,
+    /// The total amount of the item, including taxes and shipping.
+    pub total_item_amount: Option<Money>,
+    /// The invoice number of the item.
+    pub invoice_number: Option<String>,
+}
+
+/// The cart information.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CartInfo {
+    /// The details of the items of the cart.
+    #[serde(default)]
+    pub item_details: Vec<ItemDetail>,
+    /// Whether the item amounts include taxes.
+    pub tax_inclusive: Option<bool>,
+    /// The ID of the PayPal invoice of the transaction.
+    pub paypal_invoice_id: Option<String>,
+}
+
+/// The store information.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreInfo {
+    /// The ID of the store.
+    pub store_id: Option<String>,
+    /// The ID of the terminal.
+    pub terminal_id: Option<String>,
+}
+
+/// The details of a transaction, only the groups requested with [TransactionQuery::fields] are set.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionDetail {
+    /// The transaction information.
+    pub transaction_info: Option<TransactionInfo>,
+    /// The information about the payer.
+    pub payer_info: Option<PayerInfo>,
+    /// The shipping information.
+    pub shipping_info: Option<ShippingInfo>,
+    /// The cart information.
+    pub cart_info: Option<CartInfo>,
+    /// The store information.
+    pub store_info: Option<StoreInfo>,
+}
+
+/// A page of transactions.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionSearchResponse {
+    /// The transactions of the page.
+    #[serde(default)]
+    pub transaction_details: Vec<TransactionDetail>,
+    /// The merchant account number.
+    pub account_number: Option<String>,
+    /// The start of the searched range.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub start_date: Option<DateTime<Utc>>,
+    /// The end of the searched range.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub end_date: Option<DateTime<Utc>>,
+    /// The date and time until which the transactions are available.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub last_refreshed_datetime: Option<DateTime<Utc>>,
+    /// The page number.
+    pub page: Option<i32>,
+    /// The total number of transactions.
+    pub total_items: Option<i32>,
+    /// The total number of pages.
+    pub total_pages: Option<i32>,
+    /// An array of request-related HATEOAS links.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+impl Client {
+    /// Lists the transactions matching the given query.
+    pub async fn list_transactions(
+        &mut self,
+        query: &TransactionQuery,
+        header_params: HeaderParams,
+    ) -> Result<TransactionSearchResponse, ResponseError> {
+        let build = self
+            .setup_headers(
+                self.client
+                    .get(format!("{}/v1/reporting/transactions", self.endpoint()).as_str())
+                    .query(query),
+                header_params,
+            )
+            .await;
+
+        let res = self.execute(build).await?;
+
+        if res.status().is_success() {
+            let transactions = decode::<TransactionSearchResponse>(res, self.decode_mode).await?;
+            Ok(transactions)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_search() {
+        let query = TransactionQuery::new(
+            "2014-07-01T00:00:00Z".parse().unwrap(),
+            "2014-07-30T00:00:00Z".parse().unwrap(),
+        )
+        .fields(&[TransactionField::CartInfo, TransactionField::PayerInfo]);
+        let request = reqwest::Client::new()
+            .get("https://api-m.sandbox.paypal.com/v1/reporting/transactions")
+            .query(&query)
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("start_date=2014-07-01T00%3A00%3A00Z&end_date=2014-07-30T00%3A00%3A00Z&fields=cart_info%2Cpayer_info")
+        );
+
+        let response: TransactionSearchResponse = serde_json::from_str(
+            r#"{
+                "transaction_details": [
+                    {
+                        "transaction_info": {
+                            "paypal_account_id": "6STWC2LSUYYYE",
+                            "transaction_id": "5TY05013RG002845M",
+                            "transaction_event_code": "T0006",
+                            "transaction_initiation_date": "2014-07-11T04:03:52+0000",
+                            "transaction_updated_date": "2014-07-11T04:03:52+0000",
+                            "transaction_amount": {"currency_code": "USD", "value": "465.00"},
+                            "fee_amount": {"currency_code": "USD", "value": "-13.79"},
+                            "transaction_status": "S",
+                            "invoice_id": "Invoice-005",
+                            "protection_eligibility": "01"
+                        },
+                        "payer_info": {
+                            "account_id": "6STWC2LSUYYYE",
+                            "email_address": "consumer@example.com",
+                            "address_status": "Y",
+                            "payer_status": "Y",
+                            "payer_name": {"given_name": "test", "surname": "consumer", "alternate_full_name": "test consumer"},
+                            "country_code": "US"
+                        },
+                        "cart_info": {
+                            "item_details": [
+                                {
+                                    "item_code": "ITEM-1",
+                                    "item_name": "Item1 - radio",
+                                    "item_quantity": "1",
+                                    "item_unit_price": {"currency_code": "USD", "value": "200.00"},
+                                    "item_amount": {"currency_code": "USD", "value": "200.00"},
+                                    "tax_amounts": [{"tax_amount": {"currency_code": "USD", "value": "20.00"}}],
+                                    "total_item_amount": {"currency_code": "USD", "value": "230.00"},
+                                    "invoice_number": "Invoice-005"
+                                }
+                            ]
+                        },
+                        "store_info": {"store_id": "STORE-1", "terminal_id": "TERM-1"}
+                    }
+                ],
+                "account_number": "XZXSPECPDZHZU",
+                "last_refreshed_datetime": "2017-01-02T06:59:59+0000",
+                "page": 1,
+                "total_items": 1,
+                "total_pages": 1,
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        let detail = &response.transaction_details[0];
+        let info = detail.transaction_info.as_ref().unwrap();
+        assert_eq!(
+            info.transaction_initiation_date,
+            Some("2014-07-11T04:03:52Z".parse().unwrap())
+        );
+        assert_eq!(detail.payer_info.as_ref().unwrap().country_code, Some(Country::US));
+        let item = &detail.cart_info.as_ref().unwrap().item_details[0];
+        assert_eq!(item.item_code.as_deref(), Some("ITEM-1"));
+        assert_eq!(item.tax_amounts[0].tax_amount.value, "20.00");
+        assert_eq!(detail.store_info.as_ref().unwrap().store_id.as_deref(), Some("STORE-1"));
+        assert!(detail.shipping_info.is_none());
+    }
+}
@@ -0,0 +1,257 @@
+//! Validation of request bodies against the paypal api schemas bundled with this crate.
+//!
+//! Requires the `validation` feature, meant for tests and development: every request body the client sends is
+//! checked first, and a request that doesn't match its schema is not sent.
+//! [ResponseError::InvalidRequest](crate::errors::ResponseError::InvalidRequest) lists every violation instead,
+//! where paypal would only answer with the first one.
+//!
+//! Only the request bodies of the orders, payments and subscriptions apis are covered, with the schemas trimmed down
+//! to the fields and keywords this crate uses: `type`, `enum`, `required`, `properties`, `items`, `minLength`,
+//! `maxLength`, `minItems`, `maxItems`, `minimum`, `maximum` and local `$ref`s.
+
+use reqwest::Method;
+use serde_json::Value;
+use std::fmt;
+
+const ORDERS: &str = include_str!("../schemas/orders.json");
+const PAYMENTS: &str = include_str!("../schemas/payments.json");
+const SUBSCRIPTIONS: &str = include_str!("../schemas/subscriptions.json");
+
+/// The operations with a schema: the method, the path with `{id}` placeholders, the schema document and the name
+/// of the request body schema in it.
+const OPERATIONS: &[(&str, &str, &str, &str)] = &[
+    ("POST", "/v2/checkout/orders", ORDERS, "order_request"),
+    ("PATCH", "/v2/checkout/orders/{id}", ORDERS, "patch_request"),
+    (
+        "POST",
+        "/v2/checkout/orders/{id}/capture",
+        ORDERS,
+        "order_capture_request",
+    ),
+    (
+        "POST",
+        "/v2/payments/authorizations/{id}/capture",
+        PAYMENTS,
+        "capture_request",
+    ),
+    ("POST", "/v2/payments/captures/{id}/refund", PAYMENTS, "refund_request"),
+    ("POST", "/v1/billing/plans", SUBSCRIPTIONS, "plan_request"),
+];
+
+/// A value of a request body that doesn't match the schema.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchemaViolation {
+    /// The json pointer to the value, like `/purchase_units/0/amount`.
+    pub pointer: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "/" } else { &self.pointer };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Validates the body of a request, returns no violation when there is no schema for the request.
+pub fn validate(method: &Method, path: &str, body: &Value) -> Vec<SchemaViolation> {
+    let operation = OPERATIONS
+        .iter()
+        .find(|(m, template, ..)| method.as_str() == *m && path_matches(template, path));
+    let (document, name) = match operation {
+        Some((_, _, document, name)) => (document, name),
+        None => return Vec::new(),
+    };
+
+    let document: Value = serde_json::from_str(document).expect("the bundled schemas are valid json");
+    let mut violations = Vec::new();
+    let schema = serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) });
+    check(&document, &schema, body, String::new(), &mut violations);
+    violations
+}
+
+/// Validates a request about to be sent, requests without a json body are not checked.
+pub(crate) fn validate_request(request: &reqwest::Request) -> Vec<SchemaViolation> {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .and_then(|body| serde_json::from_slice(body).ok());
+    match body {
+        Some(body) => validate(request.method(), request.url().path(), &body),
+        None => Vec::new(),
+    }
+}
+
+fn path_matches(template: &str, path: &str) -> bool {
+    let template = template.split('/');
+    let path = path.trim_end_matches('/').split('/');
+    template.clone().count() == path.clone().count()
+        && template
+            .zip(path)
+            .all(|(expected, segment)| expected == segment || (expected == "{id}" && !segment.is_empty()))
+}
+
+fn push(violations: &mut Vec<SchemaViolation>, pointer: &str, message: String) {
+    violations.push(SchemaViolation {
+        pointer: pointer.to_owned(),
+        message,
+    });
+}
+
+fn check(document: &Value, schema: &Value, value: &Value, pointer: String, violations: &mut Vec<SchemaViolation>) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match document.pointer(reference.trim_start_matches('#')) {
+            Some(schema) => check(document, schema, value, pointer, violations),
+            None => push(violations, &pointer, format!("unknown schema {}", reference)),
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            _ => true,
+        };
+        if !matches {
+            push(violations, &pointer, format!("expected {}, found {}", expected, value));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            push(
+                violations,
+                &pointer,
+                format!("{} is not one of {}", value, Value::Array(allowed.clone())),
+            );
+        }
+    }
+
+    let limit = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+    match value {
+        Value::String(s) => {
+            let length = s.chars().count() as u64;
+            if let Some(min) = limit("minLength").filter(|min| length < *min) {
+                push(
+                    violations,
+                    &pointer,
+                    format!("must be at least {} characters long", min),
+                );
+            }
+            if let Some(max) = limit("maxLength").filter(|max| length > *max) {
+                push(
+                    violations,
+                    &pointer,
+                    format!("must be at most {} characters long, not {}", max, length),
+                );
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64).filter(|min| n < *min) {
+                push(violations, &pointer, format!("must be at least {}", min));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64).filter(|max| n > *max) {
+                push(violations, &pointer, format!("must be at most {}", max));
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = limit("minItems").filter(|min| count < *min) {
+                push(violations, &pointer, format!("must have at least {} items", min));
+            }
+            if let Some(max) = limit("maxItems").filter(|max| count > *max) {
+                push(
+                    violations,
+                    &pointer,
+                    format!("must have at most {} items, not {}", max, count),
+                );
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(document, item_schema, item, format!("{}/{}", pointer, i), violations);
+                }
+            }
+        }
+        Value::Object(fields) => {
+            for field in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                if let Some(field) = field.as_str().filter(|field| !fields.contains_key(*field)) {
+                    push(violations, &pointer, format!("missing required field {}", field));
+                }
+            }
+            for (field, field_schema) in schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(value) = fields.get(field) {
+                    check(
+                        document,
+                        field_schema,
+                        value,
+                        format!("{}/{}", pointer, field),
+                        violations,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(all(test, feature = "orders"))]
+mod tests {
+    use super::*;
+    use crate::common::Currency;
+    use crate::orders::{Amount, Intent, OrderPayload, PurchaseUnit};
+
+    #[test]
+    fn test_validate_order() {
+        let unit = || PurchaseUnit::new(Amount::new(Currency::EUR, "10.00"));
+        let order = serde_json::to_value(OrderPayload::new(Intent::Capture, vec![unit()])).unwrap();
+        assert!(validate(&Method::POST, "/v2/checkout/orders", &order).is_empty());
+
+        let mut unit = unit();
+        unit.description = Some("x".repeat(128));
+        unit.soft_descriptor = Some(String::new());
+        let order = serde_json::to_value(OrderPayload::new(Intent::Capture, vec![unit])).unwrap();
+        let violations = validate(&Method::POST, "/v2/checkout/orders", &order);
+        assert_eq!(
+            violations.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "/purchase_units/0/description: must be at most 127 characters long, not 128",
+                "/purchase_units/0/soft_descriptor: must be at least 1 characters long",
+            ]
+        );
+
+        let order = serde_json::to_value(OrderPayload::new(Intent::Capture, vec![])).unwrap();
+        let violations = validate(&Method::POST, "/v2/checkout/orders", &order);
+        assert_eq!(violations[0].pointer, "/purchase_units");
+    }
+
+    #[test]
+    fn test_validate_paths() {
+        let refund = serde_json::json!({ "amount": { "currency_code": "EURO", "value": "1.00" } });
+        let violations = validate(&Method::POST, "/v2/payments/captures/3C679366HH908993F/refund", &refund);
+        assert_eq!(violations[0].pointer, "/amount/currency_code");
+
+        assert!(validate(&Method::POST, "/v2/payments/captures/3C679366HH908993F/other", &refund).is_empty());
+        assert!(validate(&Method::GET, "/v2/payments/captures/3C679366HH908993F/refund", &refund).is_empty());
+    }
+
+    #[test]
+    fn test_bundled_schemas() {
+        for (_, _, document, name) in OPERATIONS {
+            let document: Value = serde_json::from_str(document).unwrap();
+            assert!(document.pointer(&format!("/components/schemas/{}", name)).is_some());
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! The Vault API saves payment methods of customers, to charge them later without asking for their details again.
+//!
+//! Saved payment methods are created, used and removed outside of the application too, listen to the `VAULT.*`
+//! webhooks to keep them in sync.
+//!
+//! Reference: https://developer.paypal.com/docs/api/payment-tokens/v3/
+
+use crate::common::*;
+use crate::countries::Country;
+use crate::orders::{CardBrand, PayerName};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// The customer a payment method is saved for.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultCustomer {
+    /// The ID of the customer, generated by PayPal or given when saving the payment method.
+    pub id: String,
+}
+
+/// A saved card.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultCard {
+    /// The card holder's name.
+    pub name: Option<String>,
+    /// The last digits of the card.
+    pub last_digits: Option<String>,
+    /// The card brand or network.
+    pub brand: Option<CardBrand>,
+    /// The expiry date of the card, in the `YYYY-MM` format.
+    pub expiry: Option<String>,
+    /// The billing address of the card.
+    pub billing_address: Option<Address>,
+}
+
+/// A saved paypal account.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultPaypal {
+    /// The email address of the paypal account holder.
+    pub email_address: Option<String>,
+    /// The PayPal-assigned ID for the paypal account holder.
+    pub payer_id: Option<String>,
+    /// The name of the paypal account holder.
+    pub name: Option<PayerName>,
+}
+
+/// The payment method behind a payment token.
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PaymentTokenSource {
+    /// A saved card.
+    pub card: Option<VaultCard>,
+    /// A saved paypal account.
+    pub paypal: Option<VaultPaypal>,
+}
+
+/// A saved payment method, the resource of the `VAULT.PAYMENT-TOKEN.*` webhooks.
+///
+/// Deleted tokens usually only carry their ID.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentToken {
+    /// The ID of the payment token, used as the vault id of a payment source.
+    pub id: String,
+    /// The customer the payment method is saved for.
+    pub customer: Option<VaultCustomer>,
+    /// The saved payment method.
+    #[serde(default)]
+    pub payment_source: PaymentTokenSource,
+    /// Links to operations on the payment token.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+/// The billing address of a card saved with the v1 vault.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreditCardAddress {
+    /// The first line of the address.
+    pub line1: Option<String>,
+    /// The second line of the address.
+    pub line2: Option<String>,
+    /// The city.
+    pub city: Option<String>,
+    /// The state or province.
+    pub state: Option<String>,
+    /// The postal code.
+    pub postal_code: Option<String>,
+    /// The two-character ISO 3166-1 code of the country.
+    pub country_code: Option<Country>,
+}
+
+string_enum! {
+    /// The state of a card saved with the v1 vault.
+    pub enum CreditCardState {
+        /// The card can be charged.
+        Ok = "ok",
+        /// The card expired.
+        Expired = "expired",
+    }
+}
+
+/// A card saved with the v1 vault, the resource of the `VAULT.CREDIT-CARD.*` webhooks.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreditCard {
+    /// The ID of the card.
+    pub id: String,
+    /// The ID of the customer the card is saved for.
+    pub external_customer_id: Option<String>,
+    /// The masked card number, only showing the last digits.
+    pub number: Option<String>,
+    /// The card type, like `visa` or `mastercard`.
+    #[serde(rename = "type")]
+    pub card_type: Option<String>,
+    /// The expiry month of the card.
+    pub expire_month: Option<String>,
+    /// The expiry year of the card.
+    pub expire_year: Option<String>,
+    /// The first name of the card holder.
+    pub first_name: Option<String>,
+    /// The last name of the card holder.
+    pub last_name: Option<String>,
+    /// The billing address of the card.
+    pub billing_address: Option<CreditCardAddress>,
+    /// The state of the card.
+    pub state: Option<CreditCardState>,
+    /// The date until the card is kept in the vault.
+    pub valid_until: Option<String>,
+    /// The date and time when the card was saved.
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The date and time when the card was last updated.
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Links to operations on the card.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
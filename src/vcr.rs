@@ -0,0 +1,367 @@
+//! Record and replay api interactions, so integration tests are fast and deterministic.
+//!
+//! A [Cassette] stores every request made by a [Client](crate::client::Client) along with the response paypal gave to it.
+//! Interactions are keyed by the request method, path and a hash of the request body, so a cassette recorded
+//! against the sandbox can be replayed in CI without network access or credentials.
+//!
+//! Access tokens and other secrets are scrubbed from the recorded bodies before they are written to disk,
+//! use [Cassette::scrub_key] to redact additional fields.
+//!
+//! Requires the `vcr` feature.
+//!
+//! ```no_run
+//! use paypal_rs::{client::Client, vcr::Cassette};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! // Records when PAYPAL_VCR_RECORD is set, replays the fixture otherwise.
+//! let cassette = Cassette::from_env("fixtures/create_order.json")?;
+//!
+//! let mut client = Client::new("client_id".to_owned(), "secret".to_owned(), true);
+//! client.set_cassette(cassette.clone());
+//!
+//! // Use the client as usual...
+//!
+//! cassette.save()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::errors::ResponseError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The environment variable that makes [Cassette::from_env] record instead of replaying.
+pub const RECORD_ENV_VAR: &str = "PAYPAL_VCR_RECORD";
+
+/// The value recorded in place of a scrubbed field.
+pub const REDACTED: &str = "REDACTED";
+
+/// The fields scrubbed by default from recorded response bodies.
+pub const DEFAULT_SCRUB_KEYS: &[&str] = &["access_token", "refresh_token", "id_token", "nonce", "app_id"];
+
+/// Whether a cassette performs real requests or answers them from its fixture file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Mode {
+    /// Requests are sent to paypal and the responses are stored in the cassette.
+    Record,
+    /// Requests are answered with the interactions stored in the cassette, no network access is made.
+    Replay,
+}
+
+/// A recorded request and its response.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Interaction {
+    /// The http method of the request.
+    pub method: String,
+    /// The path and query of the request, without the host.
+    pub path: String,
+    /// A hash of the request body.
+    pub body_hash: String,
+    /// The response status code.
+    pub status: u16,
+    /// The response body, with secrets scrubbed.
+    pub body: String,
+}
+
+impl Interaction {
+    fn matches(&self, key: &Key) -> bool {
+        self.method == key.method && self.path == key.path && self.body_hash == key.body_hash
+    }
+
+    fn to_response(&self) -> Result<reqwest::Response, String> {
+        let status = reqwest::StatusCode::from_u16(self.status)
+            .map_err(|_| format!("invalid recorded status {}", self.status))?;
+        let mut response = http::Response::new(self.body.clone());
+        *response.status_mut() = status;
+        response.headers_mut().insert(
+            reqwest::header::CONTENT_TYPE,
+            reqwest::header::HeaderValue::from_static("application/json"),
+        );
+        Ok(response.into())
+    }
+}
+
+/// What identifies a request inside a cassette.
+#[derive(Debug)]
+struct Key {
+    method: String,
+    path: String,
+    body_hash: String,
+}
+
+impl Key {
+    fn from_request(request: &reqwest::Request) -> Self {
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        let body = request.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+
+        Key {
+            method: request.method().as_str().to_owned(),
+            path,
+            body_hash: hash_body(body),
+        }
+    }
+}
+
+/// Hashes a request body using 64 bit FNV-1a, which is stable across platforms and rust versions.
+pub fn hash_body(body: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in body {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: PathBuf,
+    mode: Mode,
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+    scrub_keys: Vec<String>,
+}
+
+/// A set of recorded interactions backed by a json fixture file.
+///
+/// Cloning a cassette is cheap and all clones share the same interactions,
+/// so you can keep a handle to [save](Cassette::save) it after the client is done.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Cassette {
+    fn new(path: PathBuf, mode: Mode, interactions: Vec<Interaction>) -> Self {
+        let used = vec![false; interactions.len()];
+        Cassette {
+            inner: Arc::new(Mutex::new(Inner {
+                path,
+                mode,
+                interactions,
+                used,
+                scrub_keys: DEFAULT_SCRUB_KEYS.iter().map(|k| (*k).to_owned()).collect(),
+            })),
+        }
+    }
+
+    /// Creates an empty cassette that records into the given file once saved.
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        Self::new(path.as_ref().to_owned(), Mode::Record, Vec::new())
+    }
+
+    /// Loads a cassette from the given file to replay it.
+    pub fn replay<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let data = std::fs::read(path.as_ref())?;
+        let interactions: Vec<Interaction> = serde_json::from_slice(&data)?;
+        Ok(Self::new(path.as_ref().to_owned(), Mode::Replay, interactions))
+    }
+
+    /// Records if the `PAYPAL_VCR_RECORD` environment variable is set, replays otherwise.
+    pub fn from_env<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        if std::env::var_os(RECORD_ENV_VAR).is_some() {
+            Ok(Self::record(path))
+        } else {
+            Self::replay(path)
+        }
+    }
+
+    /// Adds a json field whose string values are replaced with [REDACTED] when recording.
+    pub fn scrub_key(self, key: &str) -> Self {
+        self.lock().scrub_keys.push(key.to_owned());
+        self
+    }
+
+    /// The mode of this cassette.
+    pub fn mode(&self) -> Mode {
+        self.lock().mode
+    }
+
+    /// The interactions stored in this cassette.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().interactions.clone()
+    }
+
+    /// Writes the recorded interactions to the fixture file. Does nothing when replaying.
+    pub fn save(&self) -> std::io::Result<()> {
+        let inner = self.lock();
+        if inner.mode == Mode::Replay {
+            return Ok(());
+        }
+
+        if let Some(parent) = inner.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(&inner.interactions)?;
+        std::fs::write(&inner.path, data)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Answers the request from the cassette, or sends it and records the response.
+    pub(crate) async fn execute(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, ResponseError> {
+        let key = Key::from_request(&request);
+
+        if self.mode() == Mode::Replay {
+            let mut inner = self.lock();
+            let Inner { interactions, used, .. } = &mut *inner;
+            let (interaction, used) = interactions
+                .iter()
+                .zip(used.iter_mut())
+                .find(|(interaction, used)| !**used && interaction.matches(&key))
+                .ok_or_else(|| {
+                    ResponseError::VcrError(format!("no recorded interaction for {} {}", key.method, key.path))
+                })?;
+            *used = true;
+            return interaction.to_response().map_err(ResponseError::VcrError);
+        }
+
+        let res = client.execute(request).await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?;
+
+        {
+            let mut inner = self.lock();
+            let recorded = scrub(&body, &inner.scrub_keys);
+            inner.interactions.push(Interaction {
+                method: key.method,
+                path: key.path,
+                body_hash: key.body_hash,
+                status: status.as_u16(),
+                body: recorded,
+            });
+            inner.used.push(true);
+        }
+
+        let mut response = http::Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(response.into())
+    }
+}
+
+/// Replaces the string values of the given keys anywhere in a json body.
+fn scrub(body: &[u8], keys: &[String]) -> String {
+    fn scrub_value(value: &mut serde_json::Value, keys: &[String]) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map.iter_mut() {
+                    if v.is_string() && keys.iter().any(|key| key == k) {
+                        *v = serde_json::Value::String(REDACTED.to_owned());
+                    } else {
+                        scrub_value(v, keys);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|v| scrub_value(v, keys)),
+            _ => {}
+        }
+    }
+
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            scrub_value(&mut value, keys);
+            value.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+/// An interaction answering the token request of a client with the access token `A21AA`.
+#[cfg(test)]
+pub(crate) fn token_interaction() -> Interaction {
+    Interaction {
+        method: "POST".to_owned(),
+        path: "/v1/oauth2/token".to_owned(),
+        body_hash: hash_body(b"grant_type=client_credentials"),
+        status: 200,
+        body: r#"{"scope":"","access_token":"A21AA","token_type":"Bearer","app_id":"APP-80W284485P519543T","expires_in":32400,"nonce":"nonce"}"#.to_owned(),
+    }
+}
+
+/// An interaction answering a request with the given method, path and body.
+#[cfg(test)]
+pub(crate) fn interaction(method: &str, path: &str, body: &[u8], status: u16, response: &str) -> Interaction {
+    Interaction {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        body_hash: hash_body(body),
+        status,
+        body: response.to_owned(),
+    }
+}
+
+/// A cassette replaying the given interactions, without a fixture file.
+#[cfg(test)]
+pub(crate) fn replay_cassette(interactions: &[Interaction]) -> Cassette {
+    Cassette::new(PathBuf::new(), Mode::Replay, interactions.to_vec())
+}
+
+#[cfg(all(test, feature = "orders"))]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+    use crate::orders::OrderStatus;
+
+    fn fixture(name: &str, interactions: &[Interaction]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("paypal-rs-vcr-{}-{}.json", name, std::process::id()));
+        std::fs::write(&path, serde_json::to_vec(interactions).unwrap()).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let path = fixture(
+            "replay",
+            &[
+                token_interaction(),
+                Interaction {
+                    method: "GET".to_owned(),
+                    path: "/v2/checkout/orders/5O190127TN364715T/".to_owned(),
+                    body_hash: hash_body(b""),
+                    status: 200,
+                    body: r#"{"id":"5O190127TN364715T","status":"APPROVED","links":[]}"#.to_owned(),
+                },
+            ],
+        );
+
+        let mut client = Client::new("id".to_owned(), "secret".to_owned(), true);
+        client.set_cassette(Cassette::replay(&path).unwrap());
+
+        let order = client.show_order_details("5O190127TN364715T").await.unwrap();
+        assert_eq!(order.id, "5O190127TN364715T");
+        assert_eq!(order.status, OrderStatus::Approved);
+
+        // Every interaction is only replayed once.
+        assert!(matches!(
+            client.show_order_details("5O190127TN364715T").await,
+            Err(ResponseError::VcrError(_))
+        ));
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_scrub() {
+        let keys = vec!["access_token".to_owned(), "email_address".to_owned()];
+        let scrubbed = scrub(
+            br#"{"access_token":"A21AA","expires_in":100,"payer":{"email_address":"buyer@example.com"}}"#,
+            &keys,
+        );
+        let value: serde_json::Value = serde_json::from_str(&scrubbed).unwrap();
+        assert_eq!(value["access_token"], REDACTED);
+        assert_eq!(value["expires_in"], 100);
+        assert_eq!(value["payer"]["email_address"], REDACTED);
+    }
+}
@@ -7,19 +7,21 @@
 
 use crate::common::*;
 use crate::client::HeaderParams;
-use crate::errors::{PaypalError, ResponseError};
+use crate::errors::{decode, InvalidWebhookRequestError, ResponseError};
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_with::skip_serializing_none;
+use std::collections::HashMap;
 use crate::client::{Client};
 
-/// The verification status
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum VerificationStatus {
-    /// Webhook signature verified
-    Success,
-    /// Webhook signature was a failure
-    Failure
+string_enum! {
+    /// The verification status
+    pub enum VerificationStatus {
+        /// Webhook signature verified
+        Success = "SUCCESS",
+        /// Webhook signature was a failure
+        Failure = "FAILURE",
+    }
 }
 
 /// Verification represents the status of the webhook signature
@@ -37,6 +39,8 @@ pub struct WebhookVerificationPayload<T> {
     /// The ID of the HTTP transmission. Contained in the PAYPAL-TRANSMISSION-ID header of the notification message.
     pub transmission_id: String,
     /// The date and time of the HTTP transmission, in Internet date and time format. Appears in the PAYPAL-TRANSMISSION-TIME header of the notification message.
+    ///
+    /// Kept as the raw header value, since it is part of the signed data.
     pub transmission_time: String,
     /// The X.509 public key certificate. Download the certificate from this URL and use it to verify the signature. Extract this value from the PAYPAL-CERT-URL response header, which is received with the webhook notification.
     pub cert_url: String,
@@ -50,19 +54,152 @@ pub struct WebhookVerificationPayload<T> {
     pub webhook_event: T
 }
 
+impl<T> WebhookVerificationPayload<T> {
+    /// Parses the transmission time, None if it isn't a valid Internet date and time.
+    pub fn transmission_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(&self.transmission_time)
+            .ok()
+            .map(|time| time.with_timezone(&chrono::Utc))
+    }
+}
+
+impl WebhookVerificationPayload<Box<RawValue>> {
+    /// Builds the payload from the headers and the raw body of a webhook delivery.
+    ///
+    /// The header names are compared case insensitively, so the headers of any http framework can be passed as
+    /// name and value pairs. The body is kept as it was received, since the signature is computed over it.
+    pub fn from_request<I, K, V>(headers: I, body: &str, webhook_id: &str) -> Result<Self, InvalidWebhookRequestError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        const HEADERS: [&str; 5] = [
+            "PAYPAL-TRANSMISSION-ID",
+            "PAYPAL-TRANSMISSION-TIME",
+            "PAYPAL-CERT-URL",
+            "PAYPAL-AUTH-ALGO",
+            "PAYPAL-TRANSMISSION-SIG",
+        ];
+        let mut values: [Option<String>; 5] = Default::default();
+        for (name, value) in headers {
+            if let Some(i) = HEADERS.iter().position(|h| h.eq_ignore_ascii_case(name.as_ref())) {
+                values[i] = Some(value.as_ref().to_owned());
+            }
+        }
+        let mut take = |i: usize| {
+            values[i]
+                .take()
+                .ok_or(InvalidWebhookRequestError::MissingHeader(HEADERS[i]))
+        };
+
+        Ok(WebhookVerificationPayload {
+            transmission_id: take(0)?,
+            transmission_time: take(1)?,
+            cert_url: take(2)?,
+            auth_algo: take(3)?,
+            transmission_sig: take(4)?,
+            webhook_id: webhook_id.to_owned(),
+            webhook_event: serde_json::from_str(body).map_err(InvalidWebhookRequestError::InvalidBody)?,
+        })
+    }
+}
+
+string_enum! {
+    /// The type of event a webhook notifies about.
+    ///
+    /// Deserializing an event type this enum doesn't know about yields [EventType::Other] instead of failing, unless the response is decoded
+    /// [strictly](crate::errors::DecodeMode::Strict).
+    pub enum EventType(Other) {
+        /// A payment capture completes, the resource is a [Payment](crate::capture::Payment).
+        PaymentCaptureCompleted = "PAYMENT.CAPTURE.COMPLETED",
+        /// A payment capture is denied, the resource is a [Payment](crate::capture::Payment).
+        PaymentCaptureDenied = "PAYMENT.CAPTURE.DENIED",
+        /// The state of a payment capture changes to pending, the resource is a [Payment](crate::capture::Payment).
+        PaymentCapturePending = "PAYMENT.CAPTURE.PENDING",
+        /// A merchant refunds a payment capture, the resource is the refund, which deserializes as a [Payment](crate::capture::Payment).
+        PaymentCaptureRefunded = "PAYMENT.CAPTURE.REFUNDED",
+        /// PayPal reverses a payment capture, the resource is the refund, which deserializes as a [Payment](crate::capture::Payment).
+        PaymentCaptureReversed = "PAYMENT.CAPTURE.REVERSED",
+        /// A buyer approves a checkout order, the resource is an [Order](crate::orders::Order).
+        CheckoutOrderApproved = "CHECKOUT.ORDER.APPROVED",
+        /// A checkout order is processed, the resource is an [Order](crate::orders::Order).
+        CheckoutOrderCompleted = "CHECKOUT.ORDER.COMPLETED",
+        /// A batch payout is denied, the resource is a [PayoutBatch](crate::payouts::PayoutBatch).
+        PaymentPayoutsBatchDenied = "PAYMENT.PAYOUTSBATCH.DENIED",
+        /// A batch payout is being processed, the resource is a [PayoutBatch](crate::payouts::PayoutBatch).
+        PaymentPayoutsBatchProcessing = "PAYMENT.PAYOUTSBATCH.PROCESSING",
+        /// A batch payout completes successfully, the resource is a [PayoutBatch](crate::payouts::PayoutBatch).
+        PaymentPayoutsBatchSuccess = "PAYMENT.PAYOUTSBATCH.SUCCESS",
+        /// A payout item is blocked, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemBlocked = "PAYMENT.PAYOUTS-ITEM.BLOCKED",
+        /// A payout item is canceled, since it was unclaimed, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemCanceled = "PAYMENT.PAYOUTS-ITEM.CANCELED",
+        /// A payout item is denied, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemDenied = "PAYMENT.PAYOUTS-ITEM.DENIED",
+        /// A payout item fails, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemFailed = "PAYMENT.PAYOUTS-ITEM.FAILED",
+        /// A payout item is held for a review, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemHeld = "PAYMENT.PAYOUTS-ITEM.HELD",
+        /// A payout item is refunded, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemRefunded = "PAYMENT.PAYOUTS-ITEM.REFUNDED",
+        /// A payout item is returned, since it was unclaimed for 30 days, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemReturned = "PAYMENT.PAYOUTS-ITEM.RETURNED",
+        /// A payout item succeeds, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemSucceeded = "PAYMENT.PAYOUTS-ITEM.SUCCEEDED",
+        /// A payout item is unclaimed, since the receiver has no account, the resource is a [PayoutItemDetails](crate::payouts::PayoutItemDetails).
+        PaymentPayoutsItemUnclaimed = "PAYMENT.PAYOUTS-ITEM.UNCLAIMED",
+        /// A payment method is saved, the resource is a [PaymentToken](crate::vault::PaymentToken).
+        VaultPaymentTokenCreated = "VAULT.PAYMENT-TOKEN.CREATED",
+        /// A payment method is removed, the resource is a [PaymentToken](crate::vault::PaymentToken).
+        VaultPaymentTokenDeleted = "VAULT.PAYMENT-TOKEN.DELETED",
+        /// A card is saved with the v1 vault, the resource is a [CreditCard](crate::vault::CreditCard).
+        VaultCreditCardCreated = "VAULT.CREDIT-CARD.CREATED",
+        /// A card saved with the v1 vault is updated, the resource is a [CreditCard](crate::vault::CreditCard).
+        VaultCreditCardUpdated = "VAULT.CREDIT-CARD.UPDATED",
+        /// A card is removed from the v1 vault, the resource is a [CreditCard](crate::vault::CreditCard).
+        VaultCreditCardDeleted = "VAULT.CREDIT-CARD.DELETED",
+    }
+}
+
+/// A webhook for the `PAYMENT.CAPTURE.*` events.
+#[cfg(feature = "payments")]
+pub type CaptureWebhook = Webhook<crate::capture::Payment>;
+
+/// A webhook for the `CHECKOUT.ORDER.*` events.
+#[cfg(feature = "orders")]
+pub type OrderWebhook = Webhook<crate::orders::Order>;
+
+/// A webhook for the `PAYMENT.PAYOUTSBATCH.*` events.
+#[cfg(feature = "payouts")]
+pub type PayoutBatchWebhook = Webhook<crate::payouts::PayoutBatch>;
+
+/// A webhook for the `PAYMENT.PAYOUTS-ITEM.*` events.
+#[cfg(feature = "payouts")]
+pub type PayoutItemWebhook = Webhook<crate::payouts::PayoutItemDetails>;
+
+/// A webhook for the `VAULT.PAYMENT-TOKEN.*` events.
+#[cfg(feature = "orders")]
+pub type PaymentTokenWebhook = Webhook<crate::vault::PaymentToken>;
+
+/// A webhook for the `VAULT.CREDIT-CARD.*` events.
+#[cfg(feature = "orders")]
+pub type CreditCardWebhook = Webhook<crate::vault::CreditCard>;
+
 /// Webhook callback
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Webhook<T> {
     /// The ID of the webhook.
     pub id: String,
-    /// The creation type of the webhook.
-    pub create_time: String,
+    /// The date and time when the event was created.
+    pub create_time: chrono::DateTime<chrono::Utc>,
     /// The event type of the webhook.
-    pub event_type: String,
+    pub event_type: EventType,
     /// The resource type of the webhook body.
     pub resource_type: String,
-    /// The resource version from the api.
+    /// The resource version from the api, empty on events of the v1 apis like payouts.
+    #[serde(default)]
     pub resource_version: String,
     /// Webhook summary description.
     pub summary: String,
@@ -74,32 +211,565 @@ pub struct Webhook<T> {
     pub event_version: String,
     /// An array of request-related HATEOAS links
     pub links: Vec<LinkDescription>,
+    /// The fields paypal sent that this crate doesn't know about yet, as they were received.
+    #[serde(flatten, deserialize_with = "crate::errors::deserialize_extra")]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// An event type a webhook is subscribed to.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscribedEventType {
+    /// The event type, `*` for every event.
+    pub name: String,
+    /// A description of the event.
+    pub description: Option<String>,
+    /// The status of the event type.
+    pub status: Option<String>,
+}
+
+/// A webhook configured for the app, receiving the events it is subscribed to.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    /// The ID of the webhook.
+    pub id: String,
+    /// The url the events are sent to.
+    pub url: String,
+    /// The event types the webhook is subscribed to.
+    #[serde(default)]
+    pub event_types: Vec<SubscribedEventType>,
+    /// Links to operations on the webhook.
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookList {
+    #[serde(default)]
+    webhooks: Vec<WebhookSubscription>,
+}
+
+/// The payload to simulate a webhook event, sent with sample data.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulateEventPayload {
+    /// The ID of the webhook to send the event to, either this or the url is required.
+    pub webhook_id: Option<String>,
+    /// The url to send the event to, either this or the webhook id is required.
+    pub url: Option<String>,
+    /// The type of event to simulate.
+    pub event_type: EventType,
+    /// The version of the resource, like `2.0`.
+    pub resource_version: Option<String>,
 }
 
 impl Client {
+    /// Lists the webhooks configured for the app.
+    pub async fn list_webhooks(
+        &mut self,
+        header_params: HeaderParams,
+    ) -> Result<Vec<WebhookSubscription>, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client
+                    .get(format!("{}/v1/notifications/webhooks", self.endpoint())),
+                header_params,
+            )
+            .await;
+        let res = self.execute(builder).await?;
+
+        if res.status().is_success() {
+            Ok(decode::<WebhookList>(res, self.decode_mode).await?.webhooks)
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
+    /// Sends a sample event to a webhook, only available in the sandbox.
+    pub async fn simulate_event(
+        &mut self,
+        payload: &SimulateEventPayload,
+        header_params: HeaderParams,
+    ) -> Result<Webhook<serde_json::Value>, ResponseError> {
+        let builder = self
+            .setup_headers(
+                self.client
+                    .post(format!("{}/v1/notifications/simulate-event", self.endpoint())),
+                header_params,
+            )
+            .await;
+        let res = self.execute(builder.json(payload)).await?;
+
+        if res.status().is_success() {
+            decode::<Webhook<serde_json::Value>>(res, self.decode_mode).await
+        } else {
+            Err(ResponseError::from_response(res).await)
+        }
+    }
+
     /// Verify webhook signature
+    ///
+    /// A valid signature doesn't tell a replayed delivery apart, check it with a [ReplayGuard](crate::replay::ReplayGuard) too.
     pub async fn verify_signature<T: Serialize>(
         &mut self,
         signature: WebhookVerificationPayload<T>,
-        _header_params: HeaderParams,
+        header_params: HeaderParams,
     ) -> Result<Verification, ResponseError> {
         let builder = {
             self.setup_headers(
-                self.client.post(&format!("{}/v1/notifications/verify-webhook-signature", self.endpoint())),
+                self.client.post(format!("{}/v1/notifications/verify-webhook-signature", self.endpoint())),
                 crate::client::HeaderParams {
-                    content_type: Some(String::from("application/json")),
-                    ..Default::default()
+                    content_type: header_params
+                        .content_type
+                        .or_else(|| Some(String::from("application/json"))),
+                    ..header_params
                 },
             )
             .await
         };
-        let res = builder.json(&signature).send().await?;
+        let res = self.execute(builder.json(&signature)).await?;
 
         if res.status().is_success() {
-            let verification = res.json::<Verification>().await?;
+            let verification = decode::<Verification>(res, self.decode_mode).await?;
             Ok(verification)
         } else {
-            Err(ResponseError::ApiError(res.json::<PaypalError>().await?))
+            Err(ResponseError::from_response(res).await)
         }
     }
-}
\ No newline at end of file
+
+    /// Verifies the signature of a webhook delivery from its headers and raw body,
+    /// see [WebhookVerificationPayload::from_request].
+    pub async fn verify_webhook_request<I, K, V>(
+        &mut self,
+        headers: I,
+        body: &str,
+        webhook_id: &str,
+        header_params: HeaderParams,
+    ) -> Result<Verification, ResponseError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let payload = WebhookVerificationPayload::from_request(headers, body, webhook_id)
+            .map_err(ResponseError::InvalidWebhookRequest)?;
+        self.verify_signature(payload, header_params).await
+    }
+}
+#[cfg(all(test, feature = "orders", feature = "payments", feature = "payouts"))]
+mod tests {
+    use super::*;
+    use crate::orders::{AccountStatus, CaptureStatus, CaptureStatusDetailsReason, OrderStatus};
+    use crate::payouts::{BatchStatus, PayoutItemStatus};
+    use crate::vault::CreditCardState;
+
+    #[test]
+    fn test_capture_webhooks() {
+        let completed: CaptureWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-58D329510W468432D-8HN650336L201105X",
+                "event_version": "1.0",
+                "create_time": "2022-08-23T18:29:53.505Z",
+                "resource_type": "capture",
+                "resource_version": "2.0",
+                "event_type": "PAYMENT.CAPTURE.COMPLETED",
+                "summary": "Payment completed for $ 5.0 USD",
+                "resource": {
+                    "id": "42311647XV020574X",
+                    "amount": {"currency_code": "USD", "value": "5.00"},
+                    "final_capture": true,
+                    "seller_protection": {
+                        "status": "ELIGIBLE",
+                        "dispute_categories": ["ITEM_NOT_RECEIVED", "UNAUTHORIZED_TRANSACTION"]
+                    },
+                    "disbursement_mode": "INSTANT",
+                    "seller_receivable_breakdown": {
+                        "gross_amount": {"currency_code": "USD", "value": "5.00"},
+                        "paypal_fee": {"currency_code": "USD", "value": "0.47"},
+                        "net_amount": {"currency_code": "USD", "value": "4.53"}
+                    },
+                    "invoice_id": "INV-1234",
+                    "status": "COMPLETED",
+                    "supplementary_data": {"related_ids": {"order_id": "1AB234567A1234567"}},
+                    "create_time": "2022-08-23T18:29:50Z",
+                    "update_time": "2022-08-23T18:29:50Z",
+                    "links": [
+                        {"href": "https://api.sandbox.paypal.com/v2/payments/captures/42311647XV020574X", "rel": "self", "method": "GET"},
+                        {"href": "https://api.sandbox.paypal.com/v2/payments/captures/42311647XV020574X/refund", "rel": "refund", "method": "POST"},
+                        {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/1AB234567A1234567", "rel": "up", "method": "GET"}
+                    ]
+                },
+                "links": [
+                    {"href": "https://api.sandbox.paypal.com/v1/notifications/webhooks-events/WH-58D329510W468432D-8HN650336L201105X", "rel": "self", "method": "GET"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(completed.event_type, EventType::PaymentCaptureCompleted);
+        assert_eq!(completed.resource.order_id(), Some("1AB234567A1234567"));
+        assert_eq!(completed.resource.status, Some(CaptureStatus::Completed));
+        let breakdown = completed.resource.seller_receivable_breakdown.unwrap();
+        assert_eq!(breakdown.net_amount.value, "4.53");
+
+        let pending: CaptureWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-1GE84257G0350133W-6RW800890C634293G",
+                "event_version": "1.0",
+                "create_time": "2022-08-23T18:29:53.505Z",
+                "resource_type": "capture",
+                "resource_version": "2.0",
+                "event_type": "PAYMENT.CAPTURE.PENDING",
+                "summary": "Payment pending for $ 5.0 USD",
+                "resource": {
+                    "id": "42311647XV020574X",
+                    "amount": {"currency_code": "USD", "value": "5.00"},
+                    "status": "PENDING",
+                    "status_details": {"reason": "PENDING_REVIEW"},
+                    "supplementary_data": {"related_ids": {"order_id": "1AB234567A1234567"}}
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(pending.event_type, EventType::PaymentCapturePending);
+        assert_eq!(
+            pending.resource.status_details.unwrap().reason,
+            CaptureStatusDetailsReason::PendingReview
+        );
+    }
+
+    #[test]
+    fn test_order_webhooks() {
+        let approved: OrderWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-COC11055RA711503B-4YM959094A144403T",
+                "create_time": "2018-04-16T21:21:49.000Z",
+                "event_type": "CHECKOUT.ORDER.APPROVED",
+                "resource_type": "checkout-order",
+                "resource_version": "2.0",
+                "summary": "An order has been approved by buyer",
+                "resource": {
+                    "id": "5O190127TN364715T",
+                    "create_time": "2018-04-01T21:18:49Z",
+                    "update_time": "2018-04-01T21:20:49Z",
+                    "intent": "CAPTURE",
+                    "status": "APPROVED",
+                    "payer": {
+                        "name": {"given_name": "John", "surname": "Doe"},
+                        "email_address": "buyer@example.com",
+                        "payer_id": "QYR5Z8XDVJNXQ",
+                        "address": {"country_code": "US"}
+                    },
+                    "payment_source": {
+                        "paypal": {
+                            "email_address": "buyer@example.com",
+                            "account_id": "QYR5Z8XDVJNXQ",
+                            "account_status": "VERIFIED",
+                            "name": {"given_name": "John", "surname": "Doe"},
+                            "address": {"country_code": "US"}
+                        }
+                    },
+                    "purchase_units": [
+                        {
+                            "reference_id": "d9f80740-38f0-11e8-b467-0ed5f89f718b",
+                            "amount": {"currency_code": "USD", "value": "100.00"},
+                            "payee": {"email_address": "seller@example.com", "merchant_id": "7KNGBPH2U58GQ"},
+                            "shipping": {
+                                "name": {"full_name": "John Doe"},
+                                "address": {
+                                    "address_line_1": "2211 N First Street",
+                                    "address_line_2": "Building 17",
+                                    "admin_area_2": "San Jose",
+                                    "admin_area_1": "CA",
+                                    "postal_code": "95131",
+                                    "country_code": "US"
+                                }
+                            }
+                        }
+                    ],
+                    "links": [
+                        {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "self", "method": "GET"},
+                        {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T/capture", "rel": "capture", "method": "POST"}
+                    ]
+                },
+                "links": [
+                    {"href": "https://api.sandbox.paypal.com/v1/notifications/webhooks-events/WH-COC11055RA711503B-4YM959094A144403T", "rel": "self", "method": "GET"}
+                ],
+                "event_version": "1.0"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(approved.event_type, EventType::CheckoutOrderApproved);
+        assert_eq!(approved.resource.status, OrderStatus::Approved);
+        let paypal = approved.resource.payment_source.unwrap().paypal.unwrap();
+        assert_eq!(paypal.account_status, Some(AccountStatus::Verified));
+        let units = approved.resource.purchase_units.unwrap();
+        let shipping_name = units[0].shipping.as_ref().unwrap().name.as_ref().unwrap();
+        assert_eq!(shipping_name.full_name.as_deref(), Some("John Doe"));
+
+        let completed: OrderWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-55TG7562XN2588878-8YH955435R661687G",
+                "create_time": "2018-04-16T21:21:49.000Z",
+                "event_type": "CHECKOUT.ORDER.COMPLETED",
+                "resource_type": "checkout-order",
+                "resource_version": "2.0",
+                "summary": "Checkout Order Completed",
+                "resource": {
+                    "id": "5O190127TN364715T",
+                    "intent": "CAPTURE",
+                    "status": "COMPLETED",
+                    "purchase_units": [
+                        {
+                            "reference_id": "default",
+                            "amount": {"currency_code": "USD", "value": "100.00"},
+                            "payments": {
+                                "captures": [
+                                    {
+                                        "id": "3C679366HH908993F",
+                                        "status": "COMPLETED",
+                                        "amount": {"currency_code": "USD", "value": "100.00"},
+                                        "final_capture": true,
+                                        "seller_protection": {"status": "ELIGIBLE", "dispute_categories": ["ITEM_NOT_RECEIVED"]},
+                                        "create_time": "2018-04-01T21:20:49Z",
+                                        "update_time": "2018-04-01T21:20:49Z",
+                                        "links": []
+                                    }
+                                ]
+                            }
+                        }
+                    ],
+                    "links": []
+                },
+                "links": [],
+                "event_version": "1.0"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(completed.event_type, EventType::CheckoutOrderCompleted);
+        assert_eq!(completed.resource.status, OrderStatus::Completed);
+        let units = completed.resource.purchase_units.unwrap();
+        let payments = units[0].payments.as_ref().unwrap();
+        assert_eq!(payments.captures[0].status, CaptureStatus::Completed);
+    }
+
+    #[test]
+    fn test_payout_webhooks() {
+        let batch: PayoutBatchWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-0LU96374794024348-4WG31854RU4949452",
+                "create_time": "2018-02-18T16:01:25.000Z",
+                "event_type": "PAYMENT.PAYOUTSBATCH.SUCCESS",
+                "event_version": "1.0",
+                "resource_type": "payouts",
+                "summary": "Payouts batch completed successfully.",
+                "resource": {
+                    "batch_header": {
+                        "payout_batch_id": "QYTAV6EZV2FRQ",
+                        "batch_status": "SUCCESS",
+                        "time_created": "2018-02-18T16:00:34Z",
+                        "time_completed": "2018-02-18T16:01:24Z",
+                        "sender_batch_header": {"sender_batch_id": "2018021807"},
+                        "amount": {"currency": "USD", "value": "1.00"},
+                        "fees": {"currency": "USD", "value": "0.25"}
+                    },
+                    "links": [{
+                        "href": "https://api.paypal.com/v1/payments/payouts/QYTAV6EZV2FRQ",
+                        "rel": "self",
+                        "method": "GET"
+                    }]
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(batch.event_type, EventType::PaymentPayoutsBatchSuccess);
+        assert_eq!(batch.resource.batch_header.batch_status, BatchStatus::Success);
+
+        let item: PayoutItemWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-7Y7254563A4550640-11V2185806837105M",
+                "create_time": "2018-02-18T16:01:25.000Z",
+                "event_type": "PAYMENT.PAYOUTS-ITEM.UNCLAIMED",
+                "event_version": "1.0",
+                "resource_type": "payouts_item",
+                "summary": "A payout item is unclaimed.",
+                "resource": {
+                    "payout_item_id": "8AELMXH8UB2P8",
+                    "transaction_id": "0C413693MN970190K",
+                    "transaction_status": "UNCLAIMED",
+                    "payout_item_fee": {"currency": "USD", "value": "0.00"},
+                    "payout_batch_id": "QYTAV6EZV2FRQ",
+                    "payout_item": {
+                        "recipient_type": "EMAIL",
+                        "amount": {"currency": "USD", "value": "1.00"},
+                        "receiver": "receiver@example.com"
+                    },
+                    "time_processed": "2018-02-18T16:01:24Z",
+                    "errors": {
+                        "name": "RECEIVER_UNREGISTERED",
+                        "message": "Receiver is unregistered",
+                        "information_link": "https://developer.paypal.com/docs/api/payments.payouts-batch/#errors"
+                    },
+                    "links": []
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(item.event_type, EventType::PaymentPayoutsItemUnclaimed);
+        assert_eq!(item.resource.transaction_status, Some(PayoutItemStatus::Unclaimed));
+        assert_eq!(item.resource.errors.unwrap().name, "RECEIVER_UNREGISTERED");
+    }
+
+    #[test]
+    fn test_vault_webhooks() {
+        let created: PaymentTokenWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-1KN88282901968003-82E75604WM969463F",
+                "create_time": "2022-08-15T14:13:48.978Z",
+                "event_type": "VAULT.PAYMENT-TOKEN.CREATED",
+                "event_version": "1.0",
+                "resource_type": "payment_token",
+                "resource_version": "3.0",
+                "summary": "A payment token has been created.",
+                "resource": {
+                    "id": "8kk8451t",
+                    "customer": {"id": "customer_4029352050"},
+                    "payment_source": {
+                        "card": {
+                            "name": "John Doe",
+                            "last_digits": "1111",
+                            "brand": "VISA",
+                            "expiry": "2027-02",
+                            "billing_address": {"postal_code": "95131", "country_code": "US"}
+                        }
+                    },
+                    "links": []
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(created.event_type, EventType::VaultPaymentTokenCreated);
+        assert_eq!(created.resource.customer.unwrap().id, "customer_4029352050");
+        let card = created.resource.payment_source.card.unwrap();
+        assert_eq!(card.last_digits.as_deref(), Some("1111"));
+
+        let deleted: PaymentTokenWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-8T2212326D2165212-5RL81476BM3372519",
+                "create_time": "2022-08-15T14:20:12.251Z",
+                "event_type": "VAULT.PAYMENT-TOKEN.DELETED",
+                "event_version": "1.0",
+                "resource_type": "payment_token",
+                "resource_version": "3.0",
+                "summary": "A payment token has been deleted.",
+                "resource": {"id": "8kk8451t"},
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(deleted.event_type, EventType::VaultPaymentTokenDeleted);
+        assert_eq!(deleted.resource.id, "8kk8451t");
+        assert!(deleted.resource.payment_source.card.is_none());
+
+        let card: CreditCardWebhook = serde_json::from_str(
+            r#"{
+                "id": "WH-4M0448861G563140B-9EX36365822141321",
+                "create_time": "2015-05-12T18:14:14Z",
+                "event_type": "VAULT.CREDIT-CARD.CREATED",
+                "event_version": "1.0",
+                "resource_type": "credit-card",
+                "summary": "A credit card was created",
+                "resource": {
+                    "id": "CARD-5BT058015C739554AKVFHO6Y",
+                    "external_customer_id": "customer_4029352050",
+                    "number": "xxxxxxxxxxxx1111",
+                    "type": "visa",
+                    "expire_month": "11",
+                    "expire_year": "2027",
+                    "state": "ok",
+                    "billing_address": {"line1": "2211 N First St", "city": "San Jose", "country_code": "US"},
+                    "valid_until": "2027-11-30T00:00:00Z",
+                    "create_time": "2015-05-12T18:13:47Z",
+                    "update_time": "2015-05-12T18:13:47Z",
+                    "links": []
+                },
+                "links": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(card.event_type, EventType::VaultCreditCardCreated);
+        assert_eq!(card.resource.state, Some(CreditCardState::Ok));
+        assert_eq!(
+            card.resource.billing_address.unwrap().country_code,
+            Some(crate::countries::Country::US)
+        );
+    }
+
+    #[test]
+    fn test_webhook_management() {
+        let list: WebhookList = serde_json::from_str(
+            r#"{
+                "webhooks": [{
+                    "id": "40Y916089Y8324740",
+                    "url": "https://example.com/example_webhook",
+                    "event_types": [
+                        {"name": "PAYMENT.AUTHORIZATION.CREATED", "description": "A payment authorization was created."},
+                        {"name": "PAYMENT.AUTHORIZATION.VOIDED", "description": "A payment authorization was voided."}
+                    ],
+                    "links": []
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(list.webhooks[0].id, "40Y916089Y8324740");
+        assert_eq!(list.webhooks[0].event_types[1].name, "PAYMENT.AUTHORIZATION.VOIDED");
+
+        let payload = SimulateEventPayload {
+            webhook_id: Some("40Y916089Y8324740".to_owned()),
+            url: None,
+            event_type: EventType::PaymentCaptureCompleted,
+            resource_version: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({"webhook_id": "40Y916089Y8324740", "event_type": "PAYMENT.CAPTURE.COMPLETED"})
+        );
+    }
+
+    #[test]
+    fn test_verification_from_request() {
+        let body = r#"{"id":"WH-0G2756385H040842W-5Y612302CV158622M", "event_type":"PAYMENT.CAPTURE.COMPLETED"}"#;
+        let mut headers = vec![
+            ("Content-Type", "application/json"),
+            ("Paypal-Transmission-Id", "69cd13f0-d67a-11e5-baa3-778b53f4ae55"),
+            ("PAYPAL-TRANSMISSION-TIME", "2016-02-18T20:01:35Z"),
+            (
+                "paypal-cert-url",
+                "https://api.sandbox.paypal.com/v1/notifications/certs/CERT-360caa42-fca2a594-a5cafa77",
+            ),
+            ("PAYPAL-AUTH-ALGO", "SHA256withRSA"),
+            (
+                "PAYPAL-TRANSMISSION-SIG",
+                "lmI95Jx3Y9nhR5SJWlHVIWpg4AgFk7n9bCHSRxbrd8A9zrhdu2rMyFrmz+Zjh3s3boXB07VXCXUZy",
+            ),
+        ];
+
+        let payload = WebhookVerificationPayload::from_request(headers.clone(), body, "1JE4291016473214C").unwrap();
+        assert_eq!(payload.transmission_id, "69cd13f0-d67a-11e5-baa3-778b53f4ae55");
+        assert_eq!(payload.auth_algo, "SHA256withRSA");
+        // The event is sent as it was received, with its original whitespace and key order.
+        assert!(serde_json::to_string(&payload)
+            .unwrap()
+            .contains(&format!(r#""webhook_event":{}"#, body)));
+
+        headers.retain(|(name, _)| *name != "PAYPAL-AUTH-ALGO");
+        assert!(matches!(
+            WebhookVerificationPayload::from_request(headers, body, "1JE4291016473214C"),
+            Err(InvalidWebhookRequestError::MissingHeader("PAYPAL-AUTH-ALGO"))
+        ));
+    }
+}